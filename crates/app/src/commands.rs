@@ -10,6 +10,8 @@ pub fn open_video_player(
     state: State<PlayerProcess>,
     player_path: String,
     url: String,
+    subtitles: Option<Vec<String>>,
+    audio_tracks: Option<Vec<String>>,
 ) -> Result<()> {
     if player_path.is_empty() {
         return Err(tauri::Error::Anyhow(anyhow::anyhow!(
@@ -41,8 +43,15 @@ pub fn open_video_player(
     #[cfg(not(target_os = "macos"))]
     let exec_path = player_path;
 
+    let track_args = external_track_args(
+        &exec_path,
+        subtitles.unwrap_or_default(),
+        audio_tracks.unwrap_or_default(),
+    );
+
     let child = Command::new(exec_path)
         .arg(&url)
+        .args(track_args)
         .spawn()
         .map_err(|e| anyhow::anyhow!("Failed to start player: {}", e))?;
 
@@ -50,3 +59,36 @@ pub fn open_video_player(
 
     Ok(())
 }
+
+/// Builds the command-line arguments that load external subtitle and audio
+/// tracks.
+///
+/// VLC expects `--input-slave` for remote tracks; every other player is
+/// assumed to understand mpv's `--sub-file` and `--audio-file`.
+fn external_track_args(
+    exec_path: &str,
+    subtitles: Vec<String>,
+    audio_tracks: Vec<String>,
+) -> Vec<String> {
+    let is_vlc = std::path::Path::new(exec_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("vlc"));
+
+    let subtitles = subtitles.into_iter().map(|url| {
+        if is_vlc {
+            format!("--input-slave={url}")
+        } else {
+            format!("--sub-file={url}")
+        }
+    });
+    let audio_tracks = audio_tracks.into_iter().map(|url| {
+        if is_vlc {
+            format!("--input-slave={url}")
+        } else {
+            format!("--audio-file={url}")
+        }
+    });
+
+    subtitles.chain(audio_tracks).collect()
+}
@@ -1,23 +1,35 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod network;
 
 use std::{net::SocketAddr, sync::Mutex};
 
-use crate::commands::PlayerProcess;
+use tauri::Manager;
+
+use crate::{commands::PlayerProcess, network::NetworkConfig};
 
 fn main() {
     tracing_subscriber::fmt().init();
 
-    let processor_port = portpicker::pick_unused_port()
-        .expect("failed to pick an unused port for the extensions processor");
-    let processor_addr = SocketAddr::from(([127, 0, 0, 1], processor_port));
+    // Port 0 lets the OS assign a free port when the processor binds.
+    let processor_addr = SocketAddr::from(([127, 0, 0, 1], 0));
 
     tauri::Builder::default()
         .manage(PlayerProcess(Mutex::new(None)))
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_nero_extensions::Builder::new(processor_addr).build())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .setup(move |app| {
+            // The processor config depends on the config directory, which is
+            // only known once the app is set up.
+            let network = NetworkConfig::load(&app.path().app_config_dir()?)?;
+            app.handle().plugin(
+                tauri_plugin_nero_extensions::Builder::new(processor_addr)
+                    .processor_config(network.processor_config()?)
+                    .build(),
+            )?;
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![commands::open_video_player,])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,59 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use nero_processor::ProcessorConfig;
+use serde::Deserialize;
+use url::Url;
+
+const FILE_NAME: &str = "network.json";
+
+/// Network settings of the processor, read from `network.json` in the app
+/// config directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfig {
+    /// Routes every outbound request through this proxy, e.g.
+    /// `socks5h://127.0.0.1:9050`.
+    proxy: Option<Url>,
+    /// PEM files of additional root certificates to trust, relative to the
+    /// config directory.
+    #[serde(default)]
+    root_certificates: Vec<PathBuf>,
+}
+
+impl NetworkConfig {
+    /// Reads the config from `dir`, falling back to the default one if it
+    /// does not exist.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        let mut config: Self = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("invalid network config {}", path.display()))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        for certificate in &mut config.root_certificates {
+            *certificate = dir.join(&*certificate);
+        }
+        Ok(config)
+    }
+
+    pub fn processor_config(&self) -> Result<ProcessorConfig> {
+        let mut config = ProcessorConfig::default();
+        if let Some(proxy) = &self.proxy {
+            config = config.with_proxy(proxy.clone());
+        }
+        for path in &self.root_certificates {
+            let pem = fs::read(path)
+                .with_context(|| format!("failed to read root certificate {}", path.display()))?;
+            config = config.with_root_certificate(pem);
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,49 @@
+//! Embeds the current WIT tree of `nero-extensions`, which `nero-cli new`
+//! copies into the projects it generates.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// The WIT version new extensions are built against.
+const WIT_VERSION: &str = "0.2.0-draft";
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("the WIT tree is readable") {
+        let path = entry.expect("the WIT tree is readable").path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "wit") {
+            files.push(path);
+        }
+    }
+}
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../extensions/wit")
+        .join(format!("v{WIT_VERSION}"))
+        .canonicalize()
+        .expect("the WIT tree exists");
+    let deps = root.join("deps");
+    println!("cargo:rerun-if-changed={}", deps.display());
+    println!("cargo:rustc-env=NERO_WIT_VERSION={WIT_VERSION}");
+
+    let mut files = Vec::new();
+    collect(&deps, &mut files);
+    files.sort();
+
+    let mut out = String::from("&[\n");
+    for path in files {
+        let relative = path.strip_prefix(&root).unwrap().to_string_lossy();
+        out.push_str(&format!(
+            "    ({relative:?}, include_str!({:?})),\n",
+            path.display().to_string()
+        ));
+    }
+    out.push_str("]\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("wit_files.rs"), out).unwrap();
+}
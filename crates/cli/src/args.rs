@@ -0,0 +1,314 @@
+//! Parsing of the `ID=VALUE` arguments into the values an extension
+//! declared.
+
+use anyhow::{Context, Result, anyhow};
+use nero_extensions::types::{
+    FilterCategory, FilterKind, FilterValue, SearchFilter, Setting, SettingKind, SettingValue,
+    SortDirection,
+};
+
+fn split_assignment(arg: &str) -> Result<(&str, &str)> {
+    arg.split_once('=')
+        .ok_or_else(|| anyhow!("`{arg}` is not of the form ID=VALUE"))
+}
+
+/// Parses `arg` into the value of one of `settings`.
+pub fn parse_setting(settings: &[Setting], arg: &str) -> Result<(String, SettingValue)> {
+    let (id, value) = split_assignment(arg)?;
+    let setting = settings
+        .iter()
+        .find(|setting| setting.id == id)
+        .ok_or_else(|| anyhow!("unknown setting `{id}`"))?;
+
+    let value = match setting.kind {
+        SettingKind::Text { .. } => SettingValue::Text(value.to_owned()),
+        SettingKind::Secret => SettingValue::Secret(value.to_owned()),
+        SettingKind::Toggle { .. } => SettingValue::Toggle(
+            value
+                .parse()
+                .with_context(|| format!("setting `{id}` expects `true` or `false`"))?,
+        ),
+        SettingKind::Choice { .. } => SettingValue::Choice(value.to_owned()),
+        SettingKind::Number { .. } => SettingValue::Number(
+            value
+                .parse()
+                .with_context(|| format!("setting `{id}` expects a number"))?,
+        ),
+    };
+    Ok((id.to_owned(), value))
+}
+
+/// Parses `arg` into a filter of one of `categories`.
+///
+/// Values are written according to the kind of the category:
+///
+/// - select: `genre=action,drama`
+/// - tri-state: `genre=+action,-drama`
+/// - range: `year=2000..2010`, either bound being optional
+/// - sort: `sort=popularity` or `sort=popularity:asc`
+/// - text: `studio=any text`
+pub fn parse_filter(categories: &[FilterCategory], arg: &str) -> Result<SearchFilter> {
+    let (id, value) = split_assignment(arg)?;
+    let category = categories
+        .iter()
+        .find(|category| category.id == id)
+        .ok_or_else(|| anyhow!("unknown filter category `{id}`"))?;
+
+    let list = |value: &str| {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+
+    let value = match &category.kind {
+        FilterKind::MultiSelect(_) | FilterKind::SingleSelect(_) => {
+            FilterValue::Selected(list(value))
+        }
+        FilterKind::TriState(_) => {
+            let (mut included, mut excluded) = (Vec::new(), Vec::new());
+            for item in list(value) {
+                match item.strip_prefix('-') {
+                    Some(item) => excluded.push(item.to_owned()),
+                    None => included.push(item.trim_start_matches('+').to_owned()),
+                }
+            }
+            FilterValue::TriState { included, excluded }
+        }
+        FilterKind::Range(_) => {
+            let (min, max) = value
+                .split_once("..")
+                .ok_or_else(|| anyhow!("filter `{id}` expects a range such as `2000..2010`"))?;
+            let bound = |bound: &str| -> Result<Option<f64>> {
+                match bound.trim() {
+                    "" => Ok(None),
+                    bound => Ok(Some(bound.parse().with_context(|| {
+                        format!("invalid bound `{bound}` for filter `{id}`")
+                    })?)),
+                }
+            };
+            FilterValue::Range {
+                min: bound(min)?,
+                max: bound(max)?,
+            }
+        }
+        FilterKind::Sort(_) => {
+            // Only a trailing `:asc` or `:desc` is a direction, so that sort
+            // ids may contain `:` themselves.
+            let (sort_id, direction) = if let Some(sort_id) = value.strip_suffix(":asc") {
+                (sort_id, SortDirection::Ascending)
+            } else if let Some(sort_id) = value.strip_suffix(":desc") {
+                (sort_id, SortDirection::Descending)
+            } else {
+                (value, SortDirection::Descending)
+            };
+            FilterValue::Sort {
+                id: sort_id.to_owned(),
+                direction,
+            }
+        }
+        FilterKind::Text { .. } => FilterValue::Text(value.to_owned()),
+    };
+
+    Ok(SearchFilter {
+        id: id.to_owned(),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use nero_extensions::types::{Filter, RangeBounds, SettingOption};
+
+    use super::*;
+
+    fn category(id: &str, kind: FilterKind) -> FilterCategory {
+        FilterCategory {
+            id: id.to_owned(),
+            display_name: id.to_owned(),
+            kind,
+            required: false,
+        }
+    }
+
+    fn categories() -> Vec<FilterCategory> {
+        let filters = || {
+            vec![Filter {
+                id: "action".to_owned(),
+                display_name: "Action".to_owned(),
+            }]
+        };
+        vec![
+            category("genre", FilterKind::MultiSelect(filters())),
+            category("tags", FilterKind::TriState(filters())),
+            category(
+                "year",
+                FilterKind::Range(RangeBounds {
+                    min: 1990.0,
+                    max: 2030.0,
+                    step: None,
+                }),
+            ),
+            category("sort", FilterKind::Sort(filters())),
+            category("studio", FilterKind::Text { placeholder: None }),
+        ]
+    }
+
+    fn filter(arg: &str) -> FilterValue {
+        parse_filter(&categories(), arg).unwrap().value
+    }
+
+    fn filter_error(arg: &str) -> String {
+        parse_filter(&categories(), arg).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_select_filters() {
+        let FilterValue::Selected(ids) = filter("genre=action, drama,") else {
+            panic!("not a select value");
+        };
+        assert_eq!(ids, ["action", "drama"]);
+    }
+
+    #[test]
+    fn parses_tri_state_filters() {
+        let FilterValue::TriState { included, excluded } = filter("tags=+action,-gore,romance")
+        else {
+            panic!("not a tri-state value");
+        };
+        assert_eq!(included, ["action", "romance"]);
+        assert_eq!(excluded, ["gore"]);
+    }
+
+    #[test]
+    fn parses_ranges_with_optional_bounds() {
+        for (arg, expected) in [
+            ("year=2000..2010", (Some(2000.0), Some(2010.0))),
+            ("year=2000..", (Some(2000.0), None)),
+            ("year=..2010", (None, Some(2010.0))),
+            ("year=..", (None, None)),
+        ] {
+            let FilterValue::Range { min, max } = filter(arg) else {
+                panic!("not a range value");
+            };
+            assert_eq!((min, max), expected, "{arg}");
+        }
+    }
+
+    #[test]
+    fn parses_sort_directions() {
+        for (arg, expected_id, ascending) in [
+            ("sort=popularity", "popularity", false),
+            ("sort=popularity:asc", "popularity", true),
+            ("sort=popularity:desc", "popularity", false),
+            ("sort=score:mal", "score:mal", false),
+            ("sort=score:mal:asc", "score:mal", true),
+        ] {
+            let FilterValue::Sort { id, direction } = filter(arg) else {
+                panic!("not a sort value");
+            };
+            assert_eq!(id, expected_id, "{arg}");
+            assert_eq!(
+                matches!(direction, SortDirection::Ascending),
+                ascending,
+                "{arg}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_text_filters_verbatim() {
+        let FilterValue::Text(text) = filter("studio=a=b, c") else {
+            panic!("not a text value");
+        };
+        assert_eq!(text, "a=b, c");
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        assert_eq!(filter_error("genre"), "`genre` is not of the form ID=VALUE");
+        assert_eq!(
+            filter_error("format=tv"),
+            "unknown filter category `format`"
+        );
+        assert_eq!(
+            filter_error("year=2000"),
+            "filter `year` expects a range such as `2000..2010`"
+        );
+        assert_eq!(
+            filter_error("year=2000..soon"),
+            "invalid bound `soon` for filter `year`"
+        );
+    }
+
+    fn settings() -> Vec<Setting> {
+        let setting = |id: &str, kind| Setting {
+            id: id.to_owned(),
+            display_name: id.to_owned(),
+            description: None,
+            kind,
+            required: false,
+        };
+        vec![
+            setting(
+                "domain",
+                SettingKind::Text {
+                    default: None,
+                    placeholder: None,
+                },
+            ),
+            setting("token", SettingKind::Secret),
+            setting("adult", SettingKind::Toggle { default: false }),
+            setting(
+                "language",
+                SettingKind::Choice {
+                    options: vec![SettingOption {
+                        id: "sub".to_owned(),
+                        display_name: "Subtitled".to_owned(),
+                    }],
+                    default: None,
+                },
+            ),
+            setting(
+                "quality",
+                SettingKind::Number {
+                    min: None,
+                    max: None,
+                    default: None,
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn parses_settings_by_kind() {
+        let settings = settings();
+        for (arg, expected) in [
+            ("domain=a=b", SettingValue::Text("a=b".to_owned())),
+            ("token=abc", SettingValue::Secret("abc".to_owned())),
+            ("adult=true", SettingValue::Toggle(true)),
+            ("language=sub", SettingValue::Choice("sub".to_owned())),
+            ("quality=2.5", SettingValue::Number(2.5)),
+        ] {
+            let (id, value) = parse_setting(&settings, arg).unwrap();
+            assert_eq!(Some(id.as_str()), arg.split('=').next());
+            assert_eq!(value, expected, "{arg}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let settings = settings();
+        let error = |arg| parse_setting(&settings, arg).unwrap_err().to_string();
+
+        assert_eq!(error("adult"), "`adult` is not of the form ID=VALUE");
+        assert_eq!(error("proxy=a"), "unknown setting `proxy`");
+        assert_eq!(
+            error("adult=yes"),
+            "setting `adult` expects `true` or `false`"
+        );
+        assert_eq!(error("quality=high"), "setting `quality` expects a number");
+    }
+}
@@ -0,0 +1,314 @@
+//! Runs an extension component from the terminal, for developing and
+//! debugging extensions without the app.
+
+mod args;
+mod output;
+mod package;
+mod scaffold;
+
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use nero_extensions::{
+    WasmExtension, fixtures::HttpFixtures, host::WasmHost, types::MediaResource,
+};
+use nero_processor::Processor;
+
+use crate::{
+    args::{parse_filter, parse_setting},
+    output::{
+        EpisodeView, FilterCategoryView, Format, InfoView, PageView, SeasonView, SeriesView,
+        VideoView, print, resource_url,
+    },
+    package::PackageArgs,
+};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// The extension component the command runs.
+    #[arg(short, long, global = true, value_name = "FILE")]
+    extension: Option<PathBuf>,
+
+    #[arg(short, long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    /// Sets a setting of the extension before running the command.
+    #[arg(short, long = "setting", global = true, value_name = "ID=VALUE")]
+    settings: Vec<String>,
+
+    /// Records the HTTP traffic of the extension to a fixture file.
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answers the HTTP requests of the extension from a fixture file.
+    #[arg(long, global = true, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates a Rust extension project against the current WIT.
+    New {
+        /// The directory of the project, which must not exist yet.
+        path: PathBuf,
+        /// The name of the project, defaulting to the name of its
+        /// directory.
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Embeds metadata into a built extension, checks that the host can
+    /// load it and optionally signs it.
+    Package(PackageArgs),
+    /// Generates a key to sign packages with and prints its public key.
+    Keygen {
+        /// Where the secret key is written.
+        path: PathBuf,
+    },
+    /// Shows the metadata of the extension and the interface version it
+    /// was built against.
+    Info,
+    /// Lists the search filters of the extension.
+    Filters,
+    /// Searches for series.
+    Search {
+        query: String,
+        #[arg(short, long)]
+        page: Option<u16>,
+        /// Filters the results, e.g. `genre=action,drama` or
+        /// `year=2000..2010`.
+        #[arg(long = "filter", value_name = "CATEGORY=VALUE")]
+        filters: Vec<String>,
+    },
+    /// Shows the info of a series.
+    Series { series_id: String },
+    /// Lists the seasons of a series.
+    Seasons { series_id: String },
+    /// Lists the episodes of a series.
+    Episodes {
+        series_id: String,
+        /// Only lists the episodes of a season, as listed by `seasons`.
+        #[arg(long, value_name = "SEASON_ID")]
+        season: Option<String>,
+        #[arg(short, long)]
+        page: Option<u16>,
+    },
+    /// Lists the videos of an episode.
+    Videos {
+        series_id: String,
+        episode_id: String,
+    },
+    /// Serves a video of an episode through a local processor and prints
+    /// its URL until interrupted.
+    Play {
+        series_id: String,
+        episode_id: String,
+        /// The video to play, as listed by `videos`.
+        #[arg(short, long, default_value_t = 0)]
+        index: usize,
+        /// The address the processor listens on.
+        #[arg(long, default_value = "127.0.0.1:0")]
+        addr: SocketAddr,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    match &cli.command {
+        Command::New { path, name } => {
+            let name = match name {
+                Some(name) => name.clone(),
+                None => path
+                    .file_name()
+                    .context("the project path has no directory name")?
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            scaffold::generate(path, &name)?;
+            println!("created `{name}` in `{}`", path.display());
+            return Ok(());
+        }
+        Command::Package(args) => return package::package(args),
+        Command::Keygen { path } => return package::keygen(path),
+        _ => {}
+    }
+
+    let extension = load_extension(&cli).await?;
+    let format = cli.format;
+
+    match cli.command {
+        Command::New { .. } | Command::Package(_) | Command::Keygen { .. } => unreachable!(),
+        Command::Info => {
+            let settings = if extension.has_settings() {
+                extension.settings().await?
+            } else {
+                &[]
+            };
+            print(format, &InfoView::new(&extension, settings))
+        }
+        Command::Filters => {
+            let categories = extension.filters().await?;
+            print(
+                format,
+                &categories
+                    .iter()
+                    .map(FilterCategoryView::from)
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Command::Search {
+            query,
+            page,
+            filters,
+        } => {
+            let filters = if filters.is_empty() {
+                Vec::new()
+            } else {
+                let categories = extension.filters().await?;
+                filters
+                    .iter()
+                    .map(|arg| parse_filter(&categories, arg))
+                    .collect::<Result<_>>()?
+            };
+            let page = extension.search(&query, page, filters).await?;
+            print(format, &PageView::<SeriesView>::from(&page))
+        }
+        Command::Series { series_id } => {
+            let series = extension.get_series_info(&series_id).await?;
+            print(format, &SeriesView::from(&series))
+        }
+        Command::Seasons { series_id } => {
+            let seasons = extension.get_series_seasons(&series_id).await?;
+            print(
+                format,
+                &seasons.iter().map(SeasonView::from).collect::<Vec<_>>(),
+            )
+        }
+        Command::Episodes {
+            series_id,
+            season,
+            page,
+        } => {
+            let page = match season {
+                Some(season_id) => {
+                    extension
+                        .get_season_episodes(&series_id, &season_id, page)
+                        .await?
+                }
+                None => extension.get_series_episodes(&series_id, page).await?,
+            };
+            print(format, &PageView::<EpisodeView>::from(&page))
+        }
+        Command::Videos {
+            series_id,
+            episode_id,
+        } => {
+            let videos = extension.get_series_videos(&series_id, &episode_id).await?;
+            print(
+                format,
+                &videos.iter().map(VideoView::from).collect::<Vec<_>>(),
+            )
+        }
+        Command::Play {
+            series_id,
+            episode_id,
+            index,
+            addr,
+        } => play(&extension, &series_id, &episode_id, index, addr).await,
+    }
+}
+
+async fn load_extension(cli: &Cli) -> Result<WasmExtension> {
+    let path = cli
+        .extension
+        .as_ref()
+        .context("this command runs an extension, pass it with `--extension`")?;
+    let mut extension = WasmHost::default()
+        .load_extension_async(path)
+        .await
+        .with_context(|| format!("failed to load `{}`", path.display()))?;
+
+    let fixtures = match (&cli.record, &cli.replay) {
+        (Some(path), _) => Some(HttpFixtures::record(path)),
+        (_, Some(path)) => Some(
+            HttpFixtures::replay(path)
+                .await
+                .with_context(|| format!("failed to read fixtures `{}`", path.display()))?,
+        ),
+        (None, None) => None,
+    };
+    extension.set_http_fixtures(fixtures);
+
+    if !cli.settings.is_empty() {
+        let settings = extension.settings().await?;
+        for arg in &cli.settings {
+            let (id, value) = parse_setting(settings, arg)?;
+            extension.set_setting(&id, Some(value)).await?;
+        }
+    }
+
+    Ok(extension)
+}
+
+async fn play(
+    extension: &WasmExtension,
+    series_id: &str,
+    episode_id: &str,
+    index: usize,
+    addr: SocketAddr,
+) -> Result<()> {
+    let videos = extension.get_series_videos(series_id, episode_id).await?;
+    let Some(video) = videos.get(index) else {
+        bail!("episode `{episode_id}` has {} videos", videos.len());
+    };
+    let MediaResource::HttpRequest(_) = &video.media_resource else {
+        bail!(
+            "video {index} is not served over HTTP: {}",
+            resource_url(&video.media_resource)
+        );
+    };
+
+    let processor = Processor::new(addr);
+    let handle = processor.start().await?;
+
+    // The other HTTP videos of the same language and variant are registered
+    // as fallbacks of the chosen one.
+    let alternatives = videos
+        .iter()
+        .map(|other| other.is_alternative_of(video))
+        .collect::<Vec<_>>();
+    let mut urls = Vec::new();
+    for (i, video) in videos.into_iter().enumerate() {
+        if !alternatives[i] {
+            continue;
+        }
+        if let MediaResource::HttpRequest(request) = video.media_resource {
+            let url = processor
+                .register_video_request(*request)
+                .await
+                .with_context(|| format!("failed to register video {i}"))?;
+            if i == index {
+                urls.insert(0, url);
+            } else {
+                urls.push(url);
+            }
+        }
+    }
+    processor.link_video_alternatives(&urls).await;
+
+    println!("{}", urls[0]);
+    eprintln!("serving on {}, press Ctrl-C to stop", handle.local_addr());
+    tokio::signal::ctrl_c().await?;
+
+    handle.shutdown(Duration::from_secs(5)).await?;
+    Ok(())
+}
@@ -0,0 +1,849 @@
+//! The views printed by the commands, as a table or as JSON.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use nero_extensions::{
+    WasmExtension,
+    manifest::Manifest,
+    types::{
+        Episode, EpisodeKind, ExternalId, FilterCategory, FilterKind, MediaResource, Page, Season,
+        Series, SeriesStatus, Setting, SettingKind, SubtitleFormat, Video,
+    },
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+/// A view that can be printed as a table.
+pub trait Render: Serialize {
+    fn table(&self) -> String;
+}
+
+pub fn print(format: Format, view: &impl Render) -> Result<()> {
+    match format {
+        Format::Table => print!("{}", view.table()),
+        Format::Json => println!("{}", serde_json::to_string_pretty(view)?),
+    }
+    Ok(())
+}
+
+/// Columns aligned on the widest cell, the last one left unpadded.
+fn table(headers: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let rows = rows.into_iter().collect::<Vec<_>>();
+    let mut widths = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(&headers).chain(&rows) {
+        let last = row.len().saturating_sub(1);
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            if i == last {
+                out.push_str(cell);
+            } else {
+                let _ = write!(out, "{cell:width$}  ");
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Aligned `key: value` lines, skipping empty values.
+fn fields<'a>(fields: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let fields = fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect::<Vec<_>>();
+    let width = fields.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for (key, value) in fields {
+        let _ = writeln!(
+            out,
+            "{:width$}  {value}",
+            format!("{key}:"),
+            width = width + 1
+        );
+    }
+    out
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// The URL of a resource, which the processor fetches for `play`.
+pub fn resource_url(resource: &MediaResource) -> String {
+    match resource {
+        MediaResource::HttpRequest(request) => request.uri().to_string(),
+        MediaResource::MagnetUri(uri) => uri.clone(),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoView {
+    id: String,
+    name: Option<String>,
+    version: Option<String>,
+    /// The version of the `nero:extension` interfaces.
+    interface_version: String,
+    description: Option<String>,
+    authors: Option<String>,
+    licenses: Option<String>,
+    homepage: Option<String>,
+    source: Option<String>,
+    revision: Option<String>,
+    has_feeds: bool,
+    manifest: Option<Manifest>,
+    settings: Vec<SettingView>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingView {
+    id: String,
+    display_name: String,
+    kind: &'static str,
+    required: bool,
+    description: Option<String>,
+}
+
+impl From<&Setting> for SettingView {
+    fn from(setting: &Setting) -> Self {
+        Self {
+            id: setting.id.clone(),
+            display_name: setting.display_name.clone(),
+            kind: match setting.kind {
+                SettingKind::Text { .. } => "text",
+                SettingKind::Secret => "secret",
+                SettingKind::Toggle { .. } => "toggle",
+                SettingKind::Choice { .. } => "choice",
+                SettingKind::Number { .. } => "number",
+            },
+            required: setting.required,
+            description: setting.description.clone(),
+        }
+    }
+}
+
+impl InfoView {
+    pub fn new(extension: &WasmExtension, settings: &[Setting]) -> Self {
+        let metadata = extension.metadata();
+        Self {
+            id: extension.id().to_owned(),
+            name: metadata.name.clone(),
+            version: metadata.version.as_ref().map(ToString::to_string),
+            interface_version: extension.version().to_string(),
+            description: metadata.description.as_ref().map(ToString::to_string),
+            authors: metadata.authors.as_ref().map(ToString::to_string),
+            licenses: metadata.licenses.as_ref().map(ToString::to_string),
+            homepage: metadata.homepage.as_ref().map(ToString::to_string),
+            source: metadata.source.as_ref().map(ToString::to_string),
+            revision: metadata.revision.as_ref().map(ToString::to_string),
+            has_feeds: extension.has_feeds(),
+            manifest: extension.manifest().cloned(),
+            settings: settings.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Render for InfoView {
+    fn table(&self) -> String {
+        let mut out = fields([
+            ("id", self.id.clone()),
+            ("name", optional(self.name.as_ref())),
+            ("version", optional(self.version.as_ref())),
+            ("interface version", self.interface_version.clone()),
+            ("description", optional(self.description.as_ref())),
+            ("authors", optional(self.authors.as_ref())),
+            ("licenses", optional(self.licenses.as_ref())),
+            ("homepage", optional(self.homepage.as_ref())),
+            ("source", optional(self.source.as_ref())),
+            ("revision", optional(self.revision.as_ref())),
+            ("feeds", self.has_feeds.to_string()),
+        ]);
+        if let Some(manifest) = &self.manifest {
+            out.push('\n');
+            out.push_str(&fields([
+                ("display name", manifest.display_name.clone()),
+                ("languages", manifest.languages.join(", ")),
+                ("content types", manifest.content_types.join(", ")),
+                ("imports", manifest.imports.join(", ")),
+                ("network hosts", manifest.network_hosts.join(", ")),
+            ]));
+        }
+        if !self.settings.is_empty() {
+            out.push('\n');
+            out.push_str(&table(
+                &["SETTING", "KIND", "REQUIRED", "NAME"],
+                self.settings.iter().map(|setting| {
+                    vec![
+                        setting.id.clone(),
+                        setting.kind.to_owned(),
+                        setting.required.to_string(),
+                        setting.display_name.clone(),
+                    ]
+                }),
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterCategoryView {
+    id: String,
+    display_name: String,
+    required: bool,
+    kind: FilterKindView,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum FilterKindView {
+    MultiSelect {
+        filters: Vec<FilterView>,
+    },
+    SingleSelect {
+        filters: Vec<FilterView>,
+    },
+    TriState {
+        filters: Vec<FilterView>,
+    },
+    Range {
+        min: f64,
+        max: f64,
+        step: Option<f64>,
+    },
+    Sort {
+        filters: Vec<FilterView>,
+    },
+    Text {
+        placeholder: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FilterView {
+    id: String,
+    display_name: String,
+}
+
+impl From<&FilterCategory> for FilterCategoryView {
+    fn from(category: &FilterCategory) -> Self {
+        let filters = |filters: &[nero_extensions::types::Filter]| {
+            filters
+                .iter()
+                .map(|filter| FilterView {
+                    id: filter.id.clone(),
+                    display_name: filter.display_name.clone(),
+                })
+                .collect()
+        };
+
+        Self {
+            id: category.id.clone(),
+            display_name: category.display_name.clone(),
+            required: category.required,
+            kind: match &category.kind {
+                FilterKind::MultiSelect(list) => FilterKindView::MultiSelect {
+                    filters: filters(list),
+                },
+                FilterKind::SingleSelect(list) => FilterKindView::SingleSelect {
+                    filters: filters(list),
+                },
+                FilterKind::TriState(list) => FilterKindView::TriState {
+                    filters: filters(list),
+                },
+                FilterKind::Range(bounds) => FilterKindView::Range {
+                    min: bounds.min,
+                    max: bounds.max,
+                    step: bounds.step,
+                },
+                FilterKind::Sort(list) => FilterKindView::Sort {
+                    filters: filters(list),
+                },
+                FilterKind::Text { placeholder } => FilterKindView::Text {
+                    placeholder: placeholder.clone(),
+                },
+            },
+        }
+    }
+}
+
+impl Render for Vec<FilterCategoryView> {
+    fn table(&self) -> String {
+        table(
+            &["CATEGORY", "KIND", "REQUIRED", "VALUES"],
+            self.iter().map(|category| {
+                let (kind, values) = match &category.kind {
+                    FilterKindView::MultiSelect { filters } => ("multi-select", ids(filters)),
+                    FilterKindView::SingleSelect { filters } => ("single-select", ids(filters)),
+                    FilterKindView::TriState { filters } => ("tri-state", ids(filters)),
+                    FilterKindView::Range { min, max, step } => (
+                        "range",
+                        match step {
+                            Some(step) => format!("{min}..{max} by {step}"),
+                            None => format!("{min}..{max}"),
+                        },
+                    ),
+                    FilterKindView::Sort { filters } => ("sort", ids(filters)),
+                    FilterKindView::Text { placeholder } => {
+                        ("text", optional(placeholder.as_ref()))
+                    }
+                };
+                vec![
+                    category.id.clone(),
+                    kind.to_owned(),
+                    category.required.to_string(),
+                    values,
+                ]
+            }),
+        )
+    }
+}
+
+fn ids(filters: &[FilterView]) -> String {
+    filters
+        .iter()
+        .map(|filter| filter.id.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageView<T> {
+    items: Vec<T>,
+    has_next_page: bool,
+}
+
+impl<'a, T, U: From<&'a T>> From<&'a Page<T>> for PageView<U> {
+    fn from(page: &'a Page<T>) -> Self {
+        Self {
+            items: page.items.iter().map(Into::into).collect(),
+            has_next_page: page.has_next_page,
+        }
+    }
+}
+
+impl<T> PageView<T> {
+    fn footer(&self) -> &'static str {
+        if self.has_next_page {
+            "(more on the next page)\n"
+        } else {
+            ""
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesView {
+    id: String,
+    title: String,
+    poster_url: Option<String>,
+    synopsis: Option<String>,
+    r#type: Option<String>,
+    alternative_titles: Vec<String>,
+    genres: Vec<String>,
+    status: Option<&'static str>,
+    start_year: Option<u16>,
+    end_year: Option<u16>,
+    total_episodes: Option<u16>,
+    rating: Option<f32>,
+    studios: Vec<String>,
+    banner_url: Option<String>,
+    /// External ids as `database:id`, e.g. `anilist:21`.
+    external_ids: Vec<String>,
+}
+
+impl From<&Series> for SeriesView {
+    fn from(series: &Series) -> Self {
+        Self {
+            id: series.id.clone(),
+            title: series.title.clone(),
+            poster_url: series.poster_resource.as_ref().map(resource_url),
+            synopsis: series.synopsis.clone(),
+            r#type: series.r#type.clone(),
+            alternative_titles: series.alternative_titles.clone(),
+            genres: series.genres.clone(),
+            status: series.status.as_ref().map(|status| match status {
+                SeriesStatus::Upcoming => "upcoming",
+                SeriesStatus::Airing => "airing",
+                SeriesStatus::Finished => "finished",
+                SeriesStatus::Hiatus => "hiatus",
+                SeriesStatus::Cancelled => "cancelled",
+            }),
+            start_year: series.start_year,
+            end_year: series.end_year,
+            total_episodes: series.total_episodes,
+            rating: series.rating,
+            studios: series.studios.clone(),
+            banner_url: series.banner_resource.as_ref().map(resource_url),
+            external_ids: series
+                .external_ids
+                .iter()
+                .map(|id| match id {
+                    ExternalId::Anilist(id) => format!("anilist:{id}"),
+                    ExternalId::MyAnimeList(id) => format!("myanimelist:{id}"),
+                    ExternalId::Tmdb(id) => format!("tmdb:{id}"),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SeriesView {
+    fn years(&self) -> String {
+        match (self.start_year, self.end_year) {
+            (Some(start), Some(end)) if start != end => format!("{start}-{end}"),
+            (Some(start), _) => start.to_string(),
+            (None, _) => String::new(),
+        }
+    }
+}
+
+impl Render for SeriesView {
+    fn table(&self) -> String {
+        fields([
+            ("id", self.id.clone()),
+            ("title", self.title.clone()),
+            ("alternative titles", self.alternative_titles.join(", ")),
+            ("type", optional(self.r#type.as_ref())),
+            ("status", optional(self.status)),
+            ("years", self.years()),
+            ("episodes", optional(self.total_episodes)),
+            ("rating", optional(self.rating)),
+            ("genres", self.genres.join(", ")),
+            ("studios", self.studios.join(", ")),
+            ("external ids", self.external_ids.join(", ")),
+            ("poster", optional(self.poster_url.as_ref())),
+            ("banner", optional(self.banner_url.as_ref())),
+            ("synopsis", optional(self.synopsis.as_ref())),
+        ])
+    }
+}
+
+impl Render for PageView<SeriesView> {
+    fn table(&self) -> String {
+        let rows = self.items.iter().map(|series| {
+            vec![
+                series.id.clone(),
+                series.years(),
+                optional(series.total_episodes),
+                series.title.clone(),
+            ]
+        });
+        table(&["ID", "YEARS", "EPISODES", "TITLE"], rows) + self.footer()
+    }
+}
+
+fn season_label(season: &Season) -> String {
+    match (&season.title, season.number) {
+        (Some(title), _) => title.clone(),
+        (None, Some(number)) => format!("Season {number}"),
+        (None, None) => season.id.clone(),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeasonView {
+    id: String,
+    number: Option<u16>,
+    title: Option<String>,
+}
+
+impl From<&Season> for SeasonView {
+    fn from(season: &Season) -> Self {
+        Self {
+            id: season.id.clone(),
+            number: season.number,
+            title: season.title.clone(),
+        }
+    }
+}
+
+impl Render for Vec<SeasonView> {
+    fn table(&self) -> String {
+        table(
+            &["ID", "NUMBER", "TITLE"],
+            self.iter().map(|season| {
+                vec![
+                    season.id.clone(),
+                    optional(season.number),
+                    optional(season.title.as_ref()),
+                ]
+            }),
+        )
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeView {
+    id: String,
+    number: Option<f32>,
+    kind: &'static str,
+    season: Option<String>,
+    air_date: Option<String>,
+    /// Duration in seconds.
+    duration: Option<u32>,
+    title: Option<String>,
+    thumbnail_url: Option<String>,
+    description: Option<String>,
+}
+
+impl From<&Episode> for EpisodeView {
+    fn from(episode: &Episode) -> Self {
+        Self {
+            id: episode.id.clone(),
+            number: episode.number,
+            kind: match episode.kind {
+                EpisodeKind::Regular => "regular",
+                EpisodeKind::Special => "special",
+                EpisodeKind::Recap => "recap",
+                EpisodeKind::Ova => "ova",
+                EpisodeKind::Movie => "movie",
+            },
+            season: episode.season.as_ref().map(season_label),
+            air_date: episode.air_date.clone(),
+            duration: episode.duration,
+            title: episode.title.clone(),
+            thumbnail_url: episode.thumbnail_resource.as_ref().map(resource_url),
+            description: episode.description.clone(),
+        }
+    }
+}
+
+impl Render for PageView<EpisodeView> {
+    fn table(&self) -> String {
+        let rows = self.items.iter().map(|episode| {
+            vec![
+                episode.id.clone(),
+                optional(episode.number),
+                episode.kind.to_owned(),
+                optional(episode.season.as_ref()),
+                optional(episode.title.as_ref()),
+            ]
+        });
+        table(&["ID", "NUMBER", "KIND", "SEASON", "TITLE"], rows) + self.footer()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoView {
+    url: String,
+    server: String,
+    /// Height and width.
+    resolution: (u16, u16),
+    subtitles: Vec<SubtitleView>,
+    audio_language: Option<String>,
+    is_dub: bool,
+    has_hardsubs: bool,
+    audio_tracks: Vec<AudioTrackView>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubtitleView {
+    url: String,
+    language: Option<String>,
+    label: String,
+    format: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioTrackView {
+    url: String,
+    language: Option<String>,
+    label: String,
+    is_dub: bool,
+}
+
+impl From<&Video> for VideoView {
+    fn from(video: &Video) -> Self {
+        Self {
+            url: resource_url(&video.media_resource),
+            server: video.server.clone(),
+            resolution: video.resolution,
+            subtitles: video
+                .subtitles
+                .iter()
+                .map(|subtitle| SubtitleView {
+                    url: resource_url(&subtitle.media_resource),
+                    language: subtitle.language.clone(),
+                    label: subtitle.label.clone(),
+                    format: match subtitle.format {
+                        SubtitleFormat::Vtt => "vtt",
+                        SubtitleFormat::Srt => "srt",
+                        SubtitleFormat::Ass => "ass",
+                    },
+                })
+                .collect(),
+            audio_language: video.audio_language.clone(),
+            is_dub: video.is_dub,
+            has_hardsubs: video.has_hardsubs,
+            audio_tracks: video
+                .audio_tracks
+                .iter()
+                .map(|track| AudioTrackView {
+                    url: resource_url(&track.media_resource),
+                    language: track.language.clone(),
+                    label: track.label.clone(),
+                    is_dub: track.is_dub,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Render for Vec<VideoView> {
+    fn table(&self) -> String {
+        table(
+            &["#", "SERVER", "RESOLUTION", "AUDIO", "SUBTITLES", "URL"],
+            self.iter().enumerate().map(|(i, video)| {
+                let (height, width) = video.resolution;
+                let mut audio = optional(video.audio_language.as_ref());
+                if video.is_dub {
+                    audio.push_str(" (dub)");
+                }
+                vec![
+                    i.to_string(),
+                    video.server.clone(),
+                    format!("{width}x{height}"),
+                    audio.trim().to_owned(),
+                    video
+                        .subtitles
+                        .iter()
+                        .map(|subtitle| subtitle.label.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    video.url.clone(),
+                ]
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nero_extensions::types::{
+        AudioTrack, Filter, HttpRequest, RangeBounds, Subtitle, SubtitleFormat,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    fn http(url: &str) -> MediaResource {
+        let mut request = HttpRequest::new(None);
+        *request.uri_mut() = url.parse().unwrap();
+        MediaResource::HttpRequest(Box::new(request))
+    }
+
+    fn series() -> Series {
+        Series {
+            id: "21".to_owned(),
+            title: "One Piece".to_owned(),
+            poster_resource: Some(http("https://example.com/poster.jpg")),
+            synopsis: None,
+            r#type: Some("TV".to_owned()),
+            alternative_titles: Vec::new(),
+            genres: vec!["Action".to_owned(), "Adventure".to_owned()],
+            status: Some(SeriesStatus::Airing),
+            start_year: Some(1999),
+            end_year: None,
+            total_episodes: None,
+            rating: Some(8.7),
+            studios: Vec::new(),
+            banner_resource: None,
+            external_ids: vec![ExternalId::Anilist(21), ExternalId::MyAnimeList(21)],
+        }
+    }
+
+    #[test]
+    fn aligns_table_columns() {
+        let out = table(
+            &["ID", "TITLE"],
+            [
+                vec!["1".to_owned(), "A".to_owned()],
+                vec!["1234".to_owned(), "B".to_owned()],
+            ],
+        );
+        assert_eq!(out, "ID    TITLE\n1     A\n1234  B\n");
+    }
+
+    #[test]
+    fn aligns_fields_and_skips_empty_ones() {
+        let out = fields([
+            ("id", "1".to_owned()),
+            ("synopsis", String::new()),
+            ("title", "A".to_owned()),
+        ]);
+        assert_eq!(out, "id:     1\ntitle:  A\n");
+    }
+
+    #[test]
+    fn prints_resource_urls() {
+        assert_eq!(
+            resource_url(&http("https://example.com/a?b=c")),
+            "https://example.com/a?b=c"
+        );
+        let magnet = "magnet:?xt=urn:btih:0".to_owned();
+        assert_eq!(
+            resource_url(&MediaResource::MagnetUri(magnet.clone())),
+            magnet
+        );
+    }
+
+    #[test]
+    fn renders_series() {
+        let view = SeriesView::from(&series());
+        assert_eq!(
+            view.table(),
+            "id:            21\n\
+             title:         One Piece\n\
+             type:          TV\n\
+             status:        airing\n\
+             years:         1999\n\
+             rating:        8.7\n\
+             genres:        Action, Adventure\n\
+             external ids:  anilist:21, myanimelist:21\n\
+             poster:        https://example.com/poster.jpg\n"
+        );
+
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["posterUrl"], "https://example.com/poster.jpg");
+        assert_eq!(json["startYear"], 1999);
+        assert_eq!(json["externalIds"], json!(["anilist:21", "myanimelist:21"]));
+    }
+
+    #[test]
+    fn renders_series_pages() {
+        let mut finished = series();
+        finished.start_year = Some(2001);
+        finished.end_year = Some(2004);
+        finished.total_episodes = Some(26);
+        let page = Page {
+            items: vec![series(), finished],
+            has_next_page: true,
+        };
+
+        assert_eq!(
+            PageView::<SeriesView>::from(&page).table(),
+            "ID  YEARS      EPISODES  TITLE\n\
+             21  1999                 One Piece\n\
+             21  2001-2004  26        One Piece\n\
+             (more on the next page)\n"
+        );
+    }
+
+    #[test]
+    fn renders_filter_categories() {
+        let categories = [
+            FilterCategory {
+                id: "genre".to_owned(),
+                display_name: "Genre".to_owned(),
+                kind: FilterKind::TriState(vec![
+                    Filter {
+                        id: "action".to_owned(),
+                        display_name: "Action".to_owned(),
+                    },
+                    Filter {
+                        id: "drama".to_owned(),
+                        display_name: "Drama".to_owned(),
+                    },
+                ]),
+                required: false,
+            },
+            FilterCategory {
+                id: "year".to_owned(),
+                display_name: "Year".to_owned(),
+                kind: FilterKind::Range(RangeBounds {
+                    min: 1990.0,
+                    max: 2030.0,
+                    step: Some(1.0),
+                }),
+                required: true,
+            },
+        ];
+        let views = categories
+            .iter()
+            .map(FilterCategoryView::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            views.table(),
+            "CATEGORY  KIND       REQUIRED  VALUES\n\
+             genre     tri-state  false     action,drama\n\
+             year      range      true      1990..2030 by 1\n"
+        );
+
+        let json = serde_json::to_value(&views).unwrap();
+        assert_eq!(
+            json[1],
+            json!({
+                "id": "year",
+                "displayName": "Year",
+                "required": true,
+                "kind": { "type": "range", "min": 1990.0, "max": 2030.0, "step": 1.0 },
+            })
+        );
+    }
+
+    #[test]
+    fn renders_videos() {
+        let video = Video {
+            media_resource: http("https://example.com/video.m3u8"),
+            server: "main".to_owned(),
+            resolution: (1080, 1920),
+            subtitles: vec![Subtitle {
+                language: Some("en".to_owned()),
+                label: "English".to_owned(),
+                format: SubtitleFormat::Srt,
+                media_resource: http("https://example.com/en.srt"),
+            }],
+            audio_language: Some("en".to_owned()),
+            is_dub: true,
+            has_hardsubs: false,
+            audio_tracks: vec![AudioTrack {
+                language: Some("ja".to_owned()),
+                label: "Japanese".to_owned(),
+                is_dub: false,
+                media_resource: http("https://example.com/ja.m4a"),
+            }],
+        };
+        let views = vec![VideoView::from(&video)];
+
+        assert_eq!(
+            views.table(),
+            "#  SERVER  RESOLUTION  AUDIO     SUBTITLES  URL\n\
+             0  main    1920x1080   en (dub)  English    https://example.com/video.m3u8\n"
+        );
+
+        let json = serde_json::to_value(&views[0]).unwrap();
+        assert_eq!(json["resolution"], json!([1080, 1920]));
+        assert_eq!(json["subtitles"][0]["format"], "srt");
+        assert_eq!(json["audioTracks"][0]["url"], "https://example.com/ja.m4a");
+    }
+}
@@ -0,0 +1,147 @@
+//! Packaging of a built component for distribution: embedding its
+//! metadata, validating it against the host and signing it.
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use nero_extensions::{
+    host::WasmHost,
+    manifest::{MANIFEST_SECTION, Manifest, set_custom_section},
+    signing::SigningKey,
+};
+use wasm_metadata::{AddMetadata, Authors, Description, Homepage, Licenses, Source};
+
+#[derive(Args)]
+pub struct PackageArgs {
+    /// The component built for `wasm32-wasip2`.
+    input: PathBuf,
+
+    /// Where the package is written.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// The name of the extension, which it is known by in the app.
+    #[arg(long)]
+    name: Option<String>,
+
+    #[arg(long)]
+    version: Option<String>,
+
+    #[arg(long = "author", value_name = "AUTHOR")]
+    authors: Vec<String>,
+
+    #[arg(long)]
+    description: Option<String>,
+
+    #[arg(long)]
+    homepage: Option<String>,
+
+    /// The source repository of the extension.
+    #[arg(long)]
+    source: Option<String>,
+
+    /// An SPDX license expression, e.g. `MIT OR Apache-2.0`.
+    #[arg(long)]
+    license: Option<String>,
+
+    /// The manifest declaring the capabilities of the extension, as JSON.
+    #[arg(long, value_name = "FILE")]
+    manifest: Option<PathBuf>,
+
+    /// Signs the package with the key in FILE, writing the signature to
+    /// `<output>.sig`.
+    #[arg(long, value_name = "FILE")]
+    sign: Option<PathBuf>,
+}
+
+fn signature_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".sig");
+    path.into()
+}
+
+pub fn package(args: &PackageArgs) -> Result<()> {
+    let input = fs::read(&args.input)
+        .with_context(|| format!("failed to read `{}`", args.input.display()))?;
+
+    let metadata = AddMetadata {
+        name: args.name.clone(),
+        version: args.version.clone().map(wasm_metadata::Version::new),
+        authors: (!args.authors.is_empty()).then(|| Authors::new(args.authors.join(", "))),
+        description: args.description.clone().map(Description::new),
+        homepage: args.homepage.as_deref().map(Homepage::new).transpose()?,
+        source: args.source.as_deref().map(Source::new).transpose()?,
+        licenses: args.license.as_deref().map(Licenses::new).transpose()?,
+        ..AddMetadata::default()
+    };
+    let mut wasm = metadata
+        .to_wasm(&input)
+        .with_context(|| format!("`{}` is not a component", args.input.display()))?;
+
+    if let Some(manifest_path) = &args.manifest {
+        let manifest = fs::read(manifest_path)
+            .with_context(|| format!("failed to read `{}`", manifest_path.display()))?;
+        let manifest: Manifest = serde_json::from_slice(&manifest)
+            .with_context(|| format!("`{}` is not a manifest", manifest_path.display()))?;
+        wasm = set_custom_section(&wasm, MANIFEST_SECTION, &serde_json::to_vec(&manifest)?)?;
+    }
+
+    let version = WasmHost::default()
+        .validate_component(&wasm)
+        .with_context(|| {
+            format!(
+                "`{}` is not an extension the host can load",
+                args.input.display()
+            )
+        })?;
+
+    fs::write(&args.output, &wasm)
+        .with_context(|| format!("failed to write `{}`", args.output.display()))?;
+    println!(
+        "packaged `{}` against nero:extension@{version}",
+        args.output.display()
+    );
+
+    if let Some(key_path) = &args.sign {
+        let key = fs::read_to_string(key_path)
+            .with_context(|| format!("failed to read `{}`", key_path.display()))?;
+        let key = SigningKey::from_base64(&key)
+            .with_context(|| format!("`{}` is not a signing key", key_path.display()))?;
+
+        let signature_path = signature_path(&args.output);
+        fs::write(&signature_path, key.sign(&wasm))
+            .with_context(|| format!("failed to write `{}`", signature_path.display()))?;
+        println!(
+            "signed `{}` with public key {}",
+            signature_path.display(),
+            key.public_key()
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes a new signing key to `path`, which must not exist yet, and
+/// prints its public key.
+pub fn keygen(path: &Path) -> Result<()> {
+    let key = SigningKey::generate();
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("failed to create `{}`", path.display()))?;
+    file.write_all(key.to_base64().as_bytes())?;
+
+    println!("{}", key.public_key());
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Generation of new extension projects.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+/// The WIT version generated projects are built against.
+pub const WIT_VERSION: &str = env!("NERO_WIT_VERSION");
+
+/// The `deps` tree of the current WIT version, relative to its root.
+const WIT_FILES: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/wit_files.rs"));
+
+/// The files of a project besides its WIT tree, and their templates.
+const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("../templates/extension/Cargo.toml.tmpl"),
+    ),
+    (
+        ".cargo/config.toml",
+        include_str!("../templates/extension/config.toml.tmpl"),
+    ),
+    (
+        ".gitignore",
+        include_str!("../templates/extension/gitignore.tmpl"),
+    ),
+    (
+        "README.md",
+        include_str!("../templates/extension/README.md.tmpl"),
+    ),
+    (
+        "manifest.json",
+        include_str!("../templates/extension/manifest.json.tmpl"),
+    ),
+    (
+        "src/lib.rs",
+        include_str!("../templates/extension/lib.rs.tmpl"),
+    ),
+    (
+        "wit/world.wit",
+        include_str!("../templates/extension/world.wit.tmpl"),
+    ),
+];
+
+/// Whether `name` is usable both as a crate name and as a WIT identifier:
+/// lowercase words of letters and digits, each starting with a letter,
+/// joined by `-`.
+fn is_valid_name(name: &str) -> bool {
+    name.split('-').all(|word| {
+        word.starts_with(|c: char| c.is_ascii_lowercase())
+            && word
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    })
+}
+
+/// Generates a Rust extension project named `name` in `dir`, which must
+/// not exist yet.
+pub fn generate(dir: &Path, name: &str) -> Result<()> {
+    if !is_valid_name(name) {
+        bail!("`{name}` is not a valid name, use lowercase words joined by `-` such as `my-site`");
+    }
+    if dir.exists() {
+        bail!("`{}` already exists", dir.display());
+    }
+
+    let render = |template: &str| {
+        template
+            .replace("{{name}}", name)
+            .replace("{{file_name}}", &name.replace('-', "_"))
+            .replace("{{wit_version}}", WIT_VERSION)
+    };
+    let files = TEMPLATES
+        .iter()
+        .map(|(path, template)| (dir.join(path), render(template)))
+        .chain(
+            WIT_FILES
+                .iter()
+                .map(|(path, content)| (dir.join("wit").join(path), content.to_string())),
+        );
+
+    for (path, content) in files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create `{}`", parent.display()))?;
+        }
+        fs::write(&path, content)
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn wit_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                wit_files(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "wit") {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        for name in ["", "My-Site", "my_site", "1site", "my--site"] {
+            assert!(!is_valid_name(name), "{name}");
+        }
+        assert!(is_valid_name("my-site2"));
+    }
+
+    #[test]
+    fn generates_the_current_wit_tree() {
+        let dir = std::env::temp_dir().join(format!("nero-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        generate(&dir, "my-site").unwrap();
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../extensions/wit")
+            .join(format!("v{WIT_VERSION}"));
+        let mut files = Vec::new();
+        wit_files(&root.join("deps"), &mut files);
+        assert!(!files.is_empty());
+        for path in files {
+            let relative = path.strip_prefix(&root).unwrap();
+            let generated = fs::read_to_string(dir.join("wit").join(relative))
+                .unwrap_or_else(|err| panic!("`{}`: {err}", relative.display()));
+            assert_eq!(
+                generated,
+                fs::read_to_string(&path).unwrap(),
+                "`{}` differs",
+                relative.display()
+            );
+        }
+
+        let world = fs::read_to_string(dir.join("wit/world.wit")).unwrap();
+        assert!(world.contains(WIT_VERSION), "{world}");
+        assert!(generate(&dir, "my-site").is_err(), "the directory exists");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+use crate::wasi::{
+    http::{
+        outgoing_handler,
+        types::{ErrorCode, Fields, IncomingBody, OutgoingRequest, Scheme},
+    },
+    io::streams::StreamError,
+};
+
+/// Longest chunk read from a response body at once, in bytes.
+const READ_CHUNK_LEN: u64 = 64 * 1024;
+
+/// Builds a `GET` request to `url`, which must be an absolute `http` or
+/// `https` URL.
+pub fn request(url: &str) -> Result<OutgoingRequest, ErrorCode> {
+    let invalid = || ErrorCode::InternalError(Some(format!("invalid URL `{url}`")));
+
+    let (scheme, rest) = if let Some(rest) = url.strip_prefix("http://") {
+        (Scheme::Http, rest)
+    } else if let Some(rest) = url.strip_prefix("https://") {
+        (Scheme::Https, rest)
+    } else {
+        return Err(invalid());
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+
+    let request = OutgoingRequest::new(Fields::new());
+    request.set_scheme(Some(&scheme)).map_err(|()| invalid())?;
+    request
+        .set_authority(Some(authority))
+        .map_err(|()| invalid())?;
+    request
+        .set_path_with_query(Some(path))
+        .map_err(|()| invalid())?;
+    Ok(request)
+}
+
+/// Sends a `GET` request to `url` and returns the body of its successful
+/// response.
+pub fn get(url: &str) -> Result<Vec<u8>, ErrorCode> {
+    let response = outgoing_handler::handle(request(url)?, None)?;
+    response.subscribe().block();
+    let response = response
+        .get()
+        .expect("the response is ready")
+        .expect("the response is taken once")?;
+
+    let status = response.status();
+    if !(200..300).contains(&status) {
+        return Err(ErrorCode::InternalError(Some(format!(
+            "unexpected status {status} from `{url}`"
+        ))));
+    }
+
+    let body = response.consume().expect("the body is consumed once");
+    let stream = body.stream().expect("the stream is taken once");
+    let mut bytes = Vec::new();
+    loop {
+        match stream.blocking_read(READ_CHUNK_LEN) {
+            Ok(chunk) => bytes.extend(chunk),
+            Err(StreamError::Closed) => break,
+            Err(StreamError::LastOperationFailed(err)) => {
+                return Err(ErrorCode::InternalError(Some(err.to_debug_string())));
+            }
+        }
+    }
+    drop(stream);
+    IncomingBody::finish(body);
+
+    Ok(bytes)
+}
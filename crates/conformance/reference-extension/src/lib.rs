@@ -0,0 +1,310 @@
+//! A minimal extension serving the catalog of a fake site, used to test the
+//! host and the conformance suite.
+//!
+//! The site is a single `catalog.json` file under the `base-url` setting.
+//! Every call fetches it, then pages and filters it in memory.
+
+mod http;
+
+use serde::Deserialize;
+
+use self::{
+    exports::nero::extension::{configurable, extractor},
+    nero::extension::{
+        settings,
+        types::{
+            Episode, EpisodeKind, EpisodesPage, Filter, FilterCategory, FilterKind, FilterValue,
+            MediaResource, SearchFilter, Season, Series, SeriesPage, Setting, SettingKind,
+            SettingValue, TextSetting, Video,
+        },
+    },
+    wasi::http::types::ErrorCode,
+};
+
+wit_bindgen::generate!({
+    path: "../../extensions/wit/v0.2.0-draft",
+    world: "nero:extension/configurable-extension",
+    generate_all,
+});
+
+const BASE_URL_SETTING: &str = "base-url";
+const GENRE_CATEGORY: &str = "genre";
+const PAGE_SIZE: usize = 2;
+
+#[derive(Deserialize)]
+struct Catalog {
+    series: Vec<CatalogSeries>,
+}
+
+#[derive(Deserialize)]
+struct CatalogSeries {
+    id: String,
+    title: String,
+    synopsis: Option<String>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    episodes: Vec<CatalogEpisode>,
+}
+
+#[derive(Deserialize)]
+struct CatalogEpisode {
+    id: String,
+    number: Option<f32>,
+    /// The number of the season, which is also its id.
+    season: Option<u16>,
+    title: Option<String>,
+    #[serde(default)]
+    videos: Vec<CatalogVideo>,
+}
+
+#[derive(Deserialize)]
+struct CatalogVideo {
+    server: String,
+    /// A URL relative to the base URL, or a magnet URI.
+    source: String,
+    resolution: (u16, u16),
+}
+
+fn base_url() -> Result<String, ErrorCode> {
+    match settings::get(BASE_URL_SETTING) {
+        Some(SettingValue::Text(url)) => Ok(url.trim_end_matches('/').to_owned()),
+        _ => Err(ErrorCode::InternalError(Some(
+            "the `base-url` setting is not set".to_owned(),
+        ))),
+    }
+}
+
+fn fetch_catalog() -> Result<(String, Catalog), ErrorCode> {
+    let base_url = base_url()?;
+    let body = http::get(&format!("{base_url}/catalog.json"))?;
+    let catalog = serde_json::from_slice(&body)
+        .map_err(|err| ErrorCode::InternalError(Some(format!("invalid catalog: {err}"))))?;
+    Ok((base_url, catalog))
+}
+
+fn find_series<'a>(catalog: &'a Catalog, series_id: &str) -> Result<&'a CatalogSeries, ErrorCode> {
+    catalog
+        .series
+        .iter()
+        .find(|series| series.id == series_id)
+        .ok_or_else(|| ErrorCode::InternalError(Some(format!("unknown series `{series_id}`"))))
+}
+
+/// Returns the items of the 1-based `page`, and whether there is a next page.
+fn paginate<T>(items: impl IntoIterator<Item = T>, page: Option<u16>) -> (Vec<T>, bool) {
+    let page = usize::from(page.unwrap_or(1).max(1));
+    let mut items = items.into_iter().skip((page - 1) * PAGE_SIZE);
+    let page_items = items.by_ref().take(PAGE_SIZE).collect();
+    (page_items, items.next().is_some())
+}
+
+fn resource(base_url: &str, source: &str) -> Result<MediaResource, ErrorCode> {
+    if source.starts_with("magnet:") {
+        return Ok(MediaResource::MagnetUri(source.to_owned()));
+    }
+    let request = http::request(&format!("{base_url}/{}", source.trim_start_matches('/')))?;
+    Ok(MediaResource::HttpRequest(request))
+}
+
+fn series(base_url: &str, series: &CatalogSeries) -> Result<Series, ErrorCode> {
+    Ok(Series {
+        id: series.id.clone(),
+        title: series.title.clone(),
+        poster_resource: Some(resource(base_url, &format!("posters/{}.jpg", series.id))?),
+        synopsis: series.synopsis.clone(),
+        type_: None,
+        alternative_titles: Vec::new(),
+        genres: series.genres.clone(),
+        status: None,
+        start_year: None,
+        end_year: None,
+        total_episodes: u16::try_from(series.episodes.len()).ok(),
+        rating: None,
+        studios: Vec::new(),
+        banner_resource: None,
+        external_ids: Vec::new(),
+    })
+}
+
+fn season(number: u16) -> Season {
+    Season {
+        id: number.to_string(),
+        number: Some(number),
+        title: None,
+    }
+}
+
+fn episodes_page(episodes: Vec<&CatalogEpisode>, page: Option<u16>) -> EpisodesPage {
+    let (episodes, has_next_page) = paginate(episodes, page);
+
+    EpisodesPage {
+        episodes: episodes
+            .into_iter()
+            .map(|episode| Episode {
+                id: episode.id.clone(),
+                number: episode.number,
+                kind: EpisodeKind::Regular,
+                season: episode.season.map(season),
+                air_date: None,
+                duration: None,
+                title: episode.title.clone(),
+                thumbnail_resource: None,
+                description: None,
+            })
+            .collect(),
+        has_next_page,
+    }
+}
+
+fn matches(series: &CatalogSeries, query: &str, filters: &[SearchFilter]) -> bool {
+    let query = query.to_lowercase();
+    let genres_match = filters.iter().all(|filter| match &filter.value {
+        FilterValue::Selected(genres) if filter.id == GENRE_CATEGORY => {
+            genres.iter().all(|genre| series.genres.contains(genre))
+        }
+        _ => true,
+    });
+    genres_match && series.title.to_lowercase().contains(&query)
+}
+
+struct Reference;
+
+impl extractor::Guest for Reference {
+    fn filters() -> Result<Vec<FilterCategory>, ErrorCode> {
+        let (_, catalog) = fetch_catalog()?;
+        let mut genres = catalog
+            .series
+            .iter()
+            .flat_map(|series| &series.genres)
+            .collect::<Vec<_>>();
+        genres.sort();
+        genres.dedup();
+
+        Ok(vec![FilterCategory {
+            id: GENRE_CATEGORY.to_owned(),
+            display_name: "Genre".to_owned(),
+            kind: FilterKind::MultiSelect(
+                genres
+                    .into_iter()
+                    .map(|genre| Filter {
+                        id: genre.clone(),
+                        display_name: genre.clone(),
+                    })
+                    .collect(),
+            ),
+            required: false,
+        }])
+    }
+
+    fn search(
+        query: String,
+        page: Option<u16>,
+        filters: Vec<SearchFilter>,
+    ) -> Result<SeriesPage, ErrorCode> {
+        let (base_url, catalog) = fetch_catalog()?;
+        let found = catalog
+            .series
+            .iter()
+            .filter(|series| matches(series, &query, &filters));
+        let (found, has_next_page) = paginate(found, page);
+
+        Ok(SeriesPage {
+            series: found
+                .into_iter()
+                .map(|found| series(&base_url, found))
+                .collect::<Result<_, _>>()?,
+            has_next_page,
+        })
+    }
+
+    fn get_series_info(series_id: String) -> Result<Series, ErrorCode> {
+        let (base_url, catalog) = fetch_catalog()?;
+        series(&base_url, find_series(&catalog, &series_id)?)
+    }
+
+    fn get_series_episodes(
+        series_id: String,
+        page: Option<u16>,
+    ) -> Result<EpisodesPage, ErrorCode> {
+        let (_, catalog) = fetch_catalog()?;
+        let series = find_series(&catalog, &series_id)?;
+        Ok(episodes_page(series.episodes.iter().collect(), page))
+    }
+
+    fn get_series_seasons(series_id: String) -> Result<Vec<Season>, ErrorCode> {
+        let (_, catalog) = fetch_catalog()?;
+        let mut numbers = find_series(&catalog, &series_id)?
+            .episodes
+            .iter()
+            .filter_map(|episode| episode.season)
+            .collect::<Vec<_>>();
+        numbers.sort_unstable();
+        numbers.dedup();
+        Ok(numbers.into_iter().map(season).collect())
+    }
+
+    fn get_season_episodes(
+        series_id: String,
+        season_id: String,
+        page: Option<u16>,
+    ) -> Result<EpisodesPage, ErrorCode> {
+        let (_, catalog) = fetch_catalog()?;
+        let series = find_series(&catalog, &series_id)?;
+        let episodes = series
+            .episodes
+            .iter()
+            .filter(|episode| {
+                episode
+                    .season
+                    .is_some_and(|number| number.to_string() == season_id)
+            })
+            .collect();
+        Ok(episodes_page(episodes, page))
+    }
+
+    fn get_series_videos(series_id: String, episode_id: String) -> Result<Vec<Video>, ErrorCode> {
+        let (base_url, catalog) = fetch_catalog()?;
+        let episode = find_series(&catalog, &series_id)?
+            .episodes
+            .iter()
+            .find(|episode| episode.id == episode_id)
+            .ok_or_else(|| {
+                ErrorCode::InternalError(Some(format!("unknown episode `{episode_id}`")))
+            })?;
+
+        episode
+            .videos
+            .iter()
+            .map(|video| {
+                Ok(Video {
+                    media_resource: resource(&base_url, &video.source)?,
+                    server: video.server.clone(),
+                    resolution: video.resolution,
+                    subtitles: Vec::new(),
+                    audio_language: None,
+                    is_dub: false,
+                    has_hardsubs: false,
+                    audio_tracks: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl configurable::Guest for Reference {
+    fn settings() -> Vec<Setting> {
+        vec![Setting {
+            id: BASE_URL_SETTING.to_owned(),
+            display_name: "Base URL".to_owned(),
+            description: Some("The URL the catalog is served from".to_owned()),
+            kind: SettingKind::Text(TextSetting {
+                default: None,
+                placeholder: Some("http://127.0.0.1:8080".to_owned()),
+            }),
+            required: true,
+        }]
+    }
+}
+
+export!(Reference);
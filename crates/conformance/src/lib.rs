@@ -0,0 +1,419 @@
+//! A conformance suite run against any extension component, checking that
+//! its answers are well-formed and consistent with each other.
+
+mod report;
+mod stand_in;
+
+use std::{collections::HashSet, future::Future};
+
+use anyhow::Result;
+use http::uri::Scheme;
+use nero_extensions::{
+    WasmExtension,
+    types::{
+        FilterCategory, FilterKind, FilterValue, MediaResource, Page, SearchFilter, Series,
+        SortDirection, Video,
+    },
+};
+
+pub use crate::{
+    report::{CheckResult, Outcome, Report},
+    stand_in::{StandIn, StandInServer},
+};
+
+/// What the suite searches for and how far it walks the results.
+#[derive(Debug, Clone)]
+pub struct ConformanceOptions {
+    /// The query passed to `search`, which must return at least one series.
+    pub query: String,
+    /// Pages fetched before pagination is considered endless.
+    pub max_pages: u16,
+    /// Series of the search results whose info, episodes and videos are
+    /// checked.
+    pub max_series: usize,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            max_pages: 20,
+            max_series: 3,
+        }
+    }
+}
+
+/// Runs the conformance checks against a loaded extension.
+pub struct ConformanceSuite<'a> {
+    extension: &'a WasmExtension,
+    options: ConformanceOptions,
+}
+
+impl<'a> ConformanceSuite<'a> {
+    pub fn new(extension: &'a WasmExtension) -> Self {
+        Self {
+            extension,
+            options: ConformanceOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ConformanceOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Runs every check. Checks that need the results of a failed one are
+    /// skipped.
+    pub async fn run(&self) -> Report {
+        let mut report = Report::default();
+        let mut media = MediaChecks::default();
+
+        let categories = match self.extension.filters().await {
+            Ok(categories) => {
+                report.push("filters", outcome(check_filter_categories(&categories)));
+                categories
+            }
+            Err(err) => {
+                report.push("filters", Outcome::Failed(format!("{err:#}")));
+                Vec::new()
+            }
+        };
+
+        let series = match self.search(&categories).await {
+            Ok(series) => {
+                report.push("search-pagination", Outcome::Passed);
+                series
+            }
+            Err(reason) => {
+                report.push("search-pagination", Outcome::Failed(reason));
+                for name in ["series-info", "episodes", "videos"] {
+                    report.push(name, Outcome::Skipped("search failed".to_owned()));
+                }
+                report.push("media-resources", media.outcome());
+                return report;
+            }
+        };
+
+        let sampled = &series[..series.len().min(self.options.max_series)];
+        for series in sampled {
+            media.check_series(series);
+        }
+        report.push(
+            "series-info",
+            self.check_series_info(sampled, &mut media).await,
+        );
+
+        let episodes = match self.episodes(sampled, &mut media).await {
+            Ok(episodes) => {
+                report.push("episodes", Outcome::Passed);
+                episodes
+            }
+            Err(reason) => {
+                report.push("episodes", Outcome::Failed(reason));
+                Vec::new()
+            }
+        };
+
+        if episodes.is_empty() {
+            report.push(
+                "videos",
+                Outcome::Skipped("no episode to resolve".to_owned()),
+            );
+        } else {
+            report.push("videos", self.check_videos(&episodes, &mut media).await);
+        }
+
+        report.push("media-resources", media.outcome());
+        report
+    }
+
+    /// Walks every page of the search results, returning the series found.
+    async fn search(&self, categories: &[FilterCategory]) -> Result<Vec<Series>, String> {
+        let filters = required_filters(categories, &self.options.query);
+        let series = paginate(self.options.max_pages, |page| {
+            self.extension
+                .search(&self.options.query, Some(page), filters.clone())
+        })
+        .await?;
+
+        if series.is_empty() {
+            return Err(format!(
+                "no series found for the query `{}`",
+                self.options.query
+            ));
+        }
+        unique_ids(series.iter().map(|series| series.id.as_str()), "series")?;
+        Ok(series)
+    }
+
+    async fn check_series_info(&self, sampled: &[Series], media: &mut MediaChecks) -> Outcome {
+        let mut problems = Vec::new();
+        for series in sampled {
+            match self.extension.get_series_info(&series.id).await {
+                Ok(info) if info.id != series.id => problems.push(format!(
+                    "series `{}` resolved to series `{}`",
+                    series.id, info.id
+                )),
+                Ok(info) => media.check_series(&info),
+                Err(err) => problems.push(format!("series `{}`: {err:#}", series.id)),
+            }
+        }
+        outcome(problems)
+    }
+
+    /// Walks every page of the episodes of the sampled series, returning
+    /// the first episode of each as `(series_id, episode_id)`.
+    async fn episodes(
+        &self,
+        sampled: &[Series],
+        media: &mut MediaChecks,
+    ) -> Result<Vec<(String, String)>, String> {
+        let mut first_episodes = Vec::new();
+        for series in sampled {
+            let episodes = paginate(self.options.max_pages, |page| {
+                self.extension.get_series_episodes(&series.id, Some(page))
+            })
+            .await
+            .map_err(|reason| format!("series `{}`: {reason}", series.id))?;
+
+            unique_ids(
+                episodes.iter().map(|episode| episode.id.as_str()),
+                "episode",
+            )
+            .map_err(|reason| format!("series `{}`: {reason}", series.id))?;
+            for episode in &episodes {
+                if let Some(thumbnail) = &episode.thumbnail_resource {
+                    media.check(thumbnail, || {
+                        format!("thumbnail of episode `{}`", episode.id)
+                    });
+                }
+            }
+            if let Some(episode) = episodes.first() {
+                first_episodes.push((series.id.clone(), episode.id.clone()));
+            }
+        }
+        Ok(first_episodes)
+    }
+
+    async fn check_videos(
+        &self,
+        episodes: &[(String, String)],
+        media: &mut MediaChecks,
+    ) -> Outcome {
+        let mut problems = Vec::new();
+        for (series_id, episode_id) in episodes {
+            match self
+                .extension
+                .get_series_videos(series_id, episode_id)
+                .await
+            {
+                Ok(videos) if videos.is_empty() => problems.push(format!(
+                    "episode `{episode_id}` of series `{series_id}` has no videos"
+                )),
+                Ok(videos) => videos.iter().for_each(|video| media.check_video(video)),
+                Err(err) => problems.push(format!(
+                    "episode `{episode_id}` of series `{series_id}`: {err:#}"
+                )),
+            }
+        }
+        outcome(problems)
+    }
+}
+
+fn outcome(problems: Vec<String>) -> Outcome {
+    if problems.is_empty() {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(problems.join("; "))
+    }
+}
+
+/// Fetches pages from 1 until one has no next page, checking that the
+/// pagination is consistent and ends.
+async fn paginate<T, F, Fut>(max_pages: u16, mut fetch: F) -> Result<Vec<T>, String>
+where
+    F: FnMut(u16) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    let mut items = Vec::new();
+    for page in 1..=max_pages {
+        let result = fetch(page)
+            .await
+            .map_err(|err| format!("page {page}: {err:#}"))?;
+        if result.items.is_empty() && result.has_next_page {
+            return Err(format!("page {page} is empty but has a next page"));
+        }
+
+        items.extend(result.items);
+        if !result.has_next_page {
+            return Ok(items);
+        }
+    }
+
+    Err(format!("pagination did not end within {max_pages} pages"))
+}
+
+fn unique_ids<'a>(ids: impl Iterator<Item = &'a str>, kind: &str) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if id.is_empty() {
+            return Err(format!("{kind} with an empty id"));
+        }
+        if !seen.insert(id) {
+            return Err(format!("{kind} `{id}` is returned more than once"));
+        }
+    }
+    Ok(())
+}
+
+/// Returns a value for each required filter category, since `search` fails
+/// without them.
+fn required_filters(categories: &[FilterCategory], query: &str) -> Vec<SearchFilter> {
+    categories
+        .iter()
+        .filter(|category| category.required)
+        .filter_map(|category| {
+            let value = match &category.kind {
+                FilterKind::MultiSelect(filters) | FilterKind::SingleSelect(filters) => {
+                    FilterValue::Selected(vec![filters.first()?.id.clone()])
+                }
+                FilterKind::TriState(filters) => FilterValue::TriState {
+                    included: vec![filters.first()?.id.clone()],
+                    excluded: Vec::new(),
+                },
+                FilterKind::Range(bounds) => FilterValue::Range {
+                    min: Some(bounds.min),
+                    max: Some(bounds.max),
+                },
+                FilterKind::Sort(filters) => FilterValue::Sort {
+                    id: filters.first()?.id.clone(),
+                    direction: SortDirection::Descending,
+                },
+                FilterKind::Text { .. } => FilterValue::Text(query.to_owned()),
+            };
+            Some(SearchFilter {
+                id: category.id.clone(),
+                value,
+            })
+        })
+        .collect()
+}
+
+fn check_filter_categories(categories: &[FilterCategory]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Err(reason) = unique_ids(
+        categories.iter().map(|category| category.id.as_str()),
+        "filter category",
+    ) {
+        problems.push(reason);
+    }
+
+    for category in categories {
+        if category.display_name.trim().is_empty() {
+            problems.push(format!(
+                "filter category `{}` has no display name",
+                category.id
+            ));
+        }
+
+        match &category.kind {
+            FilterKind::MultiSelect(filters)
+            | FilterKind::SingleSelect(filters)
+            | FilterKind::TriState(filters)
+            | FilterKind::Sort(filters) => {
+                if filters.is_empty() {
+                    problems.push(format!("filter category `{}` has no filters", category.id));
+                }
+                if let Err(reason) =
+                    unique_ids(filters.iter().map(|filter| filter.id.as_str()), "filter")
+                {
+                    problems.push(format!("filter category `{}`: {reason}", category.id));
+                }
+                for filter in filters {
+                    if filter.display_name.trim().is_empty() {
+                        problems.push(format!(
+                            "filter `{}` of category `{}` has no display name",
+                            filter.id, category.id
+                        ));
+                    }
+                }
+            }
+            FilterKind::Range(bounds) => {
+                if !bounds.min.is_finite() || !bounds.max.is_finite() || bounds.min > bounds.max {
+                    problems.push(format!(
+                        "range of filter category `{}` is invalid: {}..{}",
+                        category.id, bounds.min, bounds.max
+                    ));
+                }
+                if bounds.step.is_some_and(|step| step.is_nan() || step <= 0.0) {
+                    problems.push(format!(
+                        "step of filter category `{}` is not positive",
+                        category.id
+                    ));
+                }
+            }
+            FilterKind::Text { .. } => {}
+        }
+    }
+
+    problems
+}
+
+/// Collects the media resources returned by the extension that the host
+/// could not fetch.
+#[derive(Default)]
+struct MediaChecks {
+    checked: usize,
+    problems: Vec<String>,
+}
+
+impl MediaChecks {
+    fn check(&mut self, resource: &MediaResource, what: impl FnOnce() -> String) {
+        self.checked += 1;
+
+        // Magnet URIs are parsed by the host when converting the results.
+        let MediaResource::HttpRequest(request) = resource else {
+            return;
+        };
+        let uri = request.uri();
+        let scheme_ok = uri
+            .scheme()
+            .is_some_and(|scheme| *scheme == Scheme::HTTP || *scheme == Scheme::HTTPS);
+        if !scheme_ok || uri.host().is_none() {
+            self.problems
+                .push(format!("{} has an invalid URL `{uri}`", what()));
+        }
+    }
+
+    fn check_series(&mut self, series: &Series) {
+        if let Some(poster) = &series.poster_resource {
+            self.check(poster, || format!("poster of series `{}`", series.id));
+        }
+        if let Some(banner) = &series.banner_resource {
+            self.check(banner, || format!("banner of series `{}`", series.id));
+        }
+    }
+
+    fn check_video(&mut self, video: &Video) {
+        self.check(&video.media_resource, || {
+            format!("video from server `{}`", video.server)
+        });
+        for subtitle in &video.subtitles {
+            self.check(&subtitle.media_resource, || {
+                format!("subtitle `{}`", subtitle.label)
+            });
+        }
+        for track in &video.audio_tracks {
+            self.check(&track.media_resource, || {
+                format!("audio track `{}`", track.label)
+            });
+        }
+    }
+
+    fn outcome(&self) -> Outcome {
+        if self.checked == 0 {
+            return Outcome::Skipped("no media resources returned".to_owned());
+        }
+        outcome(self.problems.clone())
+    }
+}
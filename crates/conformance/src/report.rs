@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// The outcome of a single conformance check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    Failed(String),
+    /// The check could not run, usually because a check it depends on
+    /// failed.
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: Outcome,
+}
+
+/// The results of a conformance run, in the order the checks ran.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub checks: Vec<CheckResult>,
+}
+
+impl Report {
+    /// Whether no check failed. Skipped checks do not count as failures.
+    pub fn is_success(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check.outcome, Outcome::Failed(_)))
+    }
+
+    pub fn outcome(&self, name: &str) -> Option<&Outcome> {
+        self.checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| &check.outcome)
+    }
+
+    pub(crate) fn push(&mut self, name: &'static str, outcome: Outcome) {
+        self.checks.push(CheckResult { name, outcome });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                Outcome::Passed => writeln!(f, "pass  {}", check.name)?,
+                Outcome::Failed(reason) => writeln!(f, "FAIL  {}: {reason}", check.name)?,
+                Outcome::Skipped(reason) => writeln!(f, "skip  {}: {reason}", check.name)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, io, net::SocketAddr, sync::Arc};
+
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use tokio::{net::TcpListener, task::JoinHandle};
+use tracing::debug;
+
+struct File {
+    content_type: String,
+    body: Bytes,
+}
+
+/// A local HTTP server standing in for the site of an extension, serving
+/// fixed files by path. Other paths answer `404 Not Found`.
+#[derive(Default)]
+pub struct StandIn {
+    files: HashMap<String, File>,
+}
+
+impl StandIn {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves `body` at `path`, e.g. `/catalog.json`. Query strings are
+    /// ignored when matching paths.
+    pub fn with_file(
+        mut self,
+        path: impl Into<String>,
+        content_type: impl Into<String>,
+        body: impl Into<Bytes>,
+    ) -> Self {
+        self.files.insert(
+            path.into(),
+            File {
+                content_type: content_type.into(),
+                body: body.into(),
+            },
+        );
+        self
+    }
+
+    /// Starts serving on a free port of the loopback interface.
+    pub async fn start(self) -> io::Result<StandInServer> {
+        let app = Router::new()
+            .fallback(serve_file)
+            .with_state(Arc::new(self.files));
+
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let local_addr = listener.local_addr()?;
+        debug!("stand-in listening on {}", local_addr);
+
+        let task = tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok(StandInServer { local_addr, task })
+    }
+}
+
+async fn serve_file(State(files): State<Arc<HashMap<String, File>>>, uri: Uri) -> Response {
+    match files.get(uri.path()) {
+        Some(file) => (
+            [(header::CONTENT_TYPE, file.content_type.clone())],
+            file.body.clone(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// A running [`StandIn`], stopped when dropped.
+pub struct StandInServer {
+    local_addr: SocketAddr,
+    task: JoinHandle<io::Result<()>>,
+}
+
+impl StandInServer {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The URL of the server root, without a trailing slash.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.local_addr)
+    }
+}
+
+impl Drop for StandInServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,27 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+/// Builds the reference extension once per test run and returns the path
+/// of its component.
+pub fn reference_extension() -> &'static Path {
+    static COMPONENT: OnceLock<PathBuf> = OnceLock::new();
+
+    COMPONENT.get_or_init(|| {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("reference-extension/Cargo.toml");
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reference-extension");
+        let status = Command::new(env!("CARGO"))
+            .arg("build")
+            .arg("--manifest-path")
+            .arg(manifest)
+            .args(["--target", "wasm32-wasip2", "--target-dir"])
+            .arg(&target_dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(status.success(), "failed to build the reference extension");
+
+        target_dir.join("wasm32-wasip2/debug/nero_reference_extension.wasm")
+    })
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::path::Path;
+
+use common::reference_extension;
+use nero_conformance::{ConformanceSuite, Outcome, StandIn, StandInServer};
+use nero_extensions::{WasmExtension, fixtures::HttpFixtures, host::WasmHost, types::SettingValue};
+
+const CATALOG: &str = include_str!("fixtures/catalog.json");
+
+async fn load(base_url: &str) -> WasmExtension {
+    let extension = WasmHost::default()
+        .load_extension_async(reference_extension())
+        .await
+        .expect("failed to load the reference extension");
+    extension
+        .set_setting("base-url", Some(SettingValue::Text(base_url.to_owned())))
+        .await
+        .expect("failed to set the base URL");
+    extension
+}
+
+async fn start_site() -> StandInServer {
+    StandIn::new()
+        .with_file("/catalog.json", "application/json", CATALOG)
+        .start()
+        .await
+        .expect("failed to start the stand-in")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reference_extension_conforms() {
+    let site = start_site().await;
+    let extension = load(&site.url()).await;
+
+    let report = ConformanceSuite::new(&extension).run().await;
+
+    assert!(
+        report
+            .checks
+            .iter()
+            .all(|check| check.outcome == Outcome::Passed),
+        "{report}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn seasons_group_episodes() {
+    let site = start_site().await;
+    let extension = load(&site.url()).await;
+
+    let seasons = extension.get_series_seasons("lighthouse").await.unwrap();
+    let ids = seasons
+        .iter()
+        .map(|season| season.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["1", "2"]);
+
+    let page = extension
+        .get_season_episodes("lighthouse", "2", None)
+        .await
+        .unwrap();
+    let ids = page
+        .items
+        .iter()
+        .map(|episode| episode.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["lighthouse-3"]);
+
+    let seasons = extension.get_series_seasons("orbit").await.unwrap();
+    assert!(seasons.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unreachable_catalog_fails() {
+    let site = StandIn::new().start().await.unwrap();
+    let extension = load(&site.url()).await;
+
+    let report = ConformanceSuite::new(&extension).run().await;
+
+    assert!(!report.is_success(), "{report}");
+    assert!(matches!(
+        report.outcome("filters"),
+        Some(Outcome::Failed(_))
+    ));
+    assert!(matches!(
+        report.outcome("videos"),
+        Some(Outcome::Skipped(_))
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recorded_traffic_replays_offline() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reference-extension.fixtures.json");
+    let site = start_site().await;
+    let base_url = site.url();
+
+    let mut extension = load(&base_url).await;
+    extension.set_http_fixtures(Some(HttpFixtures::record(&path)));
+    let recorded = ConformanceSuite::new(&extension).run().await;
+    assert!(recorded.is_success(), "{recorded}");
+    drop(site);
+
+    let mut extension = load(&base_url).await;
+    let fixtures = HttpFixtures::replay(&path).await.unwrap();
+    extension.set_http_fixtures(Some(fixtures.clone()));
+    let replayed = ConformanceSuite::new(&extension).run().await;
+    assert!(replayed.is_success(), "{replayed}");
+    assert_eq!(fixtures.unused().await, 0);
+
+    // Every request was answered, so one more has no fixture left.
+    let err = extension.filters().await.expect_err("no fixture is left");
+    assert!(err.to_string().contains("no HTTP fixture matches"), "{err}");
+}
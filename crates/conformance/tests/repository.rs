@@ -0,0 +1,299 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::reference_extension;
+use nero_conformance::{StandIn, StandInServer};
+use nero_extensions::{
+    crypto::{HashAlgorithm, hash},
+    host::WasmHost,
+    manifest::{MANIFEST_SECTION, set_custom_section},
+    repository::{ExtensionStore, RepositoryClient},
+    signing::SigningKey,
+};
+use semver::Version;
+use serde_json::json;
+use url::Url;
+
+const HOST_VERSION: Version = Version::new(1, 2, 0);
+
+fn sha256(data: &[u8]) -> String {
+    hash(HashAlgorithm::Sha256, data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The reference extension with a manifest declaring the id `id`,
+/// embedded like `nero-cli package` does.
+fn package(id: &str) -> Vec<u8> {
+    let manifest = json!({
+        "id": id,
+        "displayName": "Reference",
+        "imports": ["wasi:http/outgoing-handler", "nero:extension/settings"],
+        "networkHosts": ["127.0.0.1"],
+    })
+    .to_string();
+
+    let component = fs::read(reference_extension()).unwrap();
+    set_custom_section(&component, MANIFEST_SECTION, manifest.as_bytes()).unwrap()
+}
+
+/// An empty directory for the extensions installed by test `name`.
+fn store_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("repository-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// An index listing the reference extension with a release for this host
+/// and a newer one requiring a newer host.
+fn index(package: &[u8], signature: Option<&str>) -> serde_json::Value {
+    json!({
+        "format": 1,
+        "name": "Test repository",
+        "extensions": [{
+            "id": "reference",
+            "name": "Reference",
+            "releases": [
+                {
+                    "version": "0.1.0",
+                    "url": "packages/reference.wasm",
+                    "sha256": sha256(package),
+                    "signature": signature,
+                },
+                {
+                    "version": "0.2.0",
+                    "url": "packages/reference.wasm",
+                    "sha256": sha256(package),
+                    "signature": signature,
+                    "minHostVersion": "2.0.0",
+                },
+            ],
+        }],
+    })
+}
+
+async fn start_repository(index: &serde_json::Value, package: Vec<u8>) -> StandInServer {
+    StandIn::new()
+        .with_file("/index.json", "application/json", index.to_string())
+        .with_file("/packages/reference.wasm", "application/wasm", package)
+        .start()
+        .await
+        .expect("failed to start the stand-in")
+}
+
+fn client(server: &StandInServer) -> RepositoryClient {
+    RepositoryClient::new(Url::parse(&format!("{}/index.json", server.url())).unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn installs_latest_compatible_release() {
+    let package = package("reference");
+    let key = SigningKey::generate();
+    let server =
+        start_repository(&index(&package, Some(&key.sign(&package))), package.clone()).await;
+    let client = client(&server).with_public_key(key.public_key());
+    let store = ExtensionStore::new(store_dir("install"));
+
+    let installed = store
+        .install(
+            &client,
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(installed.version, Version::new(0, 1, 0));
+    assert_eq!(fs::read(store.package_path("reference")).unwrap(), package);
+    WasmHost::default()
+        .load_extension_async(store.package_path("reference"))
+        .await
+        .expect("the installed extension loads");
+
+    let listed = store.installed().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, "reference");
+
+    store.uninstall("reference").await.unwrap();
+    assert!(store.installed().await.unwrap().is_empty());
+    assert!(!store.package_path("reference").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_incompatible_release() {
+    let package = package("reference");
+    let server = start_repository(&index(&package, None), package).await;
+    let store = ExtensionStore::new(store_dir("incompatible"));
+
+    let err = store
+        .install(
+            &client(&server),
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            Some(&Version::new(0, 2, 0)),
+        )
+        .await
+        .expect_err("0.2.0 requires a newer host");
+
+    assert!(err.to_string().contains("does not work with"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_tampered_package() {
+    let package = package("reference");
+    let index = index(&package, None);
+    let mut tampered = package;
+    tampered.extend_from_slice(b"\0");
+    let server = start_repository(&index, tampered).await;
+    let store = ExtensionStore::new(store_dir("tampered"));
+
+    let err = store
+        .install(
+            &client(&server).with_unsigned_allowed(),
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            None,
+        )
+        .await
+        .expect_err("the hash does not match");
+
+    assert!(err.to_string().contains("has hash"), "{err}");
+    assert!(store.installed().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_package_signed_with_another_key() {
+    let package = package("reference");
+    let signature = SigningKey::generate().sign(&package);
+    let server = start_repository(&index(&package, Some(&signature)), package).await;
+    let client = client(&server).with_public_key(SigningKey::generate().public_key());
+    let store = ExtensionStore::new(store_dir("signature"));
+
+    let err = store
+        .install(
+            &client,
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            None,
+        )
+        .await
+        .expect_err("the signature does not match");
+
+    assert!(
+        format!("{err:#}").contains("signature does not match"),
+        "{err:#}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reads_file_repositories() {
+    let package = package("reference");
+    let dir = store_dir("file-repository");
+    fs::create_dir_all(dir.join("packages")).unwrap();
+    fs::write(dir.join("packages/reference.wasm"), &package).unwrap();
+    fs::write(dir.join("index.json"), index(&package, None).to_string()).unwrap();
+    let client = RepositoryClient::new(Url::from_file_path(dir.join("index.json")).unwrap())
+        .with_unsigned_allowed();
+    let store = ExtensionStore::new(dir.join("installed"));
+
+    let index = client.index().await.unwrap();
+    assert_eq!(index.extensions[0].id, "reference");
+
+    let installed = store
+        .install(
+            &client,
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(installed.sha256, sha256(&package));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_unsigned_packages_unless_allowed() {
+    let package = package("reference");
+    let server = start_repository(&index(&package, None), package).await;
+    let store = ExtensionStore::new(store_dir("unsigned"));
+    let host = WasmHost::default();
+
+    let err = store
+        .install(&client(&server), &host, &HOST_VERSION, "reference", None)
+        .await
+        .expect_err("the client has no public key");
+    assert!(err.to_string().contains("no public key"), "{err}");
+
+    let client = client(&server).with_public_key(SigningKey::generate().public_key());
+    let err = store
+        .install(&client, &host, &HOST_VERSION, "reference", None)
+        .await
+        .expect_err("the package is not signed");
+    assert!(err.to_string().contains("is not signed"), "{err}");
+    assert!(store.installed().await.unwrap().is_empty());
+
+    let client = client.with_unsigned_allowed();
+    let err = store
+        .install(&client, &host, &HOST_VERSION, "reference", None)
+        .await
+        .expect_err("a public key requires signatures");
+    assert!(err.to_string().contains("is not signed"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_packages_without_matching_manifest() {
+    let store = ExtensionStore::new(store_dir("manifest"));
+    let host = WasmHost::default();
+
+    for (package, expected) in [
+        (fs::read(reference_extension()).unwrap(), "has no manifest"),
+        (package("other"), "declares the id `other`"),
+    ] {
+        let server = start_repository(&index(&package, None), package).await;
+        let err = store
+            .install(
+                &client(&server).with_unsigned_allowed(),
+                &host,
+                &HOST_VERSION,
+                "reference",
+                None,
+            )
+            .await
+            .expect_err("the manifest does not declare `reference`");
+
+        assert!(err.to_string().contains(expected), "{err}");
+        assert!(!store.package_path("reference").exists());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn removes_package_when_record_cannot_be_written() {
+    let package = package("reference");
+    let server = start_repository(&index(&package, None), package).await;
+    let dir = store_dir("record");
+    // A directory in place of the record makes writing it fail.
+    fs::create_dir_all(dir.join("reference.json")).unwrap();
+    let store = ExtensionStore::new(&dir);
+
+    store
+        .install(
+            &client(&server).with_unsigned_allowed(),
+            &WasmHost::default(),
+            &HOST_VERSION,
+            "reference",
+            None,
+        )
+        .await
+        .expect_err("the record cannot be written");
+
+    assert!(!store.package_path("reference").exists());
+    assert!(!dir.join("reference.json.tmp").exists());
+}
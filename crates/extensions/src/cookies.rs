@@ -0,0 +1,241 @@
+use std::{
+    io::BufReader,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Result, anyhow};
+use cookie_store::{CookieStore, RawCookie};
+use http::{HeaderMap, HeaderValue, Uri, header};
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::repository::write_atomic;
+
+/// A cookie jar shared by every call into an extension.
+///
+/// Cookies received in `Set-Cookie` headers are stored in the jar and sent
+/// back on later requests to matching URLs. A jar opened with
+/// [`CookieJar::open`] is written back to its file whenever it changes,
+/// including session cookies, which are kept until the jar is cleared.
+/// Expired cookies are dropped when the jar is opened again.
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    inner: Arc<CookieJarInner>,
+}
+
+#[derive(Debug, Default)]
+struct CookieJarInner {
+    store: RwLock<CookieStore>,
+    path: Option<PathBuf>,
+    /// Held while persisting, so that concurrent writes of the jar land in
+    /// order.
+    persist_lock: Mutex<()>,
+}
+
+impl CookieJar {
+    /// Opens the jar persisted at `path`, starting empty if the file does
+    /// not exist yet.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let store = match tokio::fs::read(&path).await {
+            Ok(bytes) => cookie_store::serde::json::load(BufReader::new(bytes.as_slice()))
+                .map_err(|err| anyhow!("failed to load cookies: {err}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => CookieStore::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            inner: Arc::new(CookieJarInner {
+                store: RwLock::new(store),
+                path: Some(path),
+                persist_lock: Mutex::default(),
+            }),
+        })
+    }
+
+    /// Returns the `Cookie` header to send with a request to `uri`, if any
+    /// cookie matches it.
+    pub(crate) fn cookie_header(&self, uri: &Uri) -> Option<HeaderValue> {
+        let url = Url::parse(&uri.to_string()).ok()?;
+        let store = self.inner.store.read().unwrap();
+
+        let cookies = store
+            .get_request_values(&url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return None;
+        }
+
+        HeaderValue::from_str(&cookies.join("; ")).ok()
+    }
+
+    /// Stores the cookies set by a response to a request to `uri`.
+    pub(crate) async fn store_response_cookies(&self, uri: &Uri, headers: &HeaderMap) {
+        let Ok(url) = Url::parse(&uri.to_string()) else {
+            return;
+        };
+        let cookies = headers
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        if let Err(err) = self.store_cookies(&url, cookies).await {
+            tracing::warn!("failed to store cookies: {err}");
+        }
+    }
+
+    /// Stores cookies in `Set-Cookie` format as if they were received from
+    /// `url`.
+    pub async fn store_cookies(
+        &self,
+        url: &Url,
+        cookies: impl IntoIterator<Item = String>,
+    ) -> Result<()> {
+        let cookies = cookies
+            .into_iter()
+            .filter_map(|cookie| RawCookie::parse(cookie).ok())
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return Ok(());
+        }
+
+        self.inner
+            .store
+            .write()
+            .unwrap()
+            .store_response_cookies(cookies.into_iter(), url);
+        self.persist().await
+    }
+
+    /// Removes every cookie from the jar.
+    pub async fn clear(&self) -> Result<()> {
+        self.inner.store.write().unwrap().clear();
+        self.persist().await
+    }
+
+    async fn persist(&self) -> Result<()> {
+        let Some(path) = &self.inner.path else {
+            return Ok(());
+        };
+
+        let _guard = self.inner.persist_lock.lock().await;
+        let mut bytes = Vec::new();
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(
+            &self.inner.store.read().unwrap(),
+            &mut bytes,
+        )
+        .map_err(|err| anyhow!("failed to save cookies: {err}"))?;
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        write_atomic(path, &bytes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(uri: &str) -> Uri {
+        uri.parse().unwrap()
+    }
+
+    fn set_cookies(cookies: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for cookie in cookies {
+            headers.append(header::SET_COOKIE, HeaderValue::from_str(cookie).unwrap());
+        }
+        headers
+    }
+
+    /// The cookies sent to `uri`, sorted since the jar does not keep their
+    /// order.
+    fn cookies(jar: &CookieJar, uri: &str) -> Vec<String> {
+        let Some(header) = jar.cookie_header(&self::uri(uri)) else {
+            return Vec::new();
+        };
+        let mut cookies = header
+            .to_str()
+            .unwrap()
+            .split("; ")
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        cookies.sort();
+        cookies
+    }
+
+    /// The path of the jar file of test `name`, removed if it exists.
+    fn jar_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("nero-cookies-{}-{name}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn sends_cookies_matching_domain_and_path() {
+        let jar = CookieJar::default();
+        let headers = set_cookies(&[
+            "session=a",
+            "shared=b; Domain=example.com",
+            "admin=c; Path=/admin",
+        ]);
+        jar.store_response_cookies(&uri("https://www.example.com/login"), &headers)
+            .await;
+
+        assert_eq!(
+            cookies(&jar, "https://www.example.com/"),
+            ["session=a", "shared=b"]
+        );
+        assert_eq!(
+            cookies(&jar, "https://www.example.com/admin/users"),
+            ["admin=c", "session=a", "shared=b"]
+        );
+        assert_eq!(cookies(&jar, "https://api.example.com/"), ["shared=b"]);
+        assert!(cookies(&jar, "https://example.org/").is_empty());
+    }
+
+    #[tokio::test]
+    async fn clears_cookies() {
+        let jar = CookieJar::default();
+        let url = Url::parse("https://example.com/").unwrap();
+        jar.store_cookies(&url, ["session=a".to_owned()])
+            .await
+            .unwrap();
+
+        jar.clear().await.unwrap();
+        assert!(cookies(&jar, "https://example.com/").is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_session_cookies_across_reopens() {
+        let path = jar_path("reopen");
+        let url = Url::parse("https://example.com/").unwrap();
+
+        let jar = CookieJar::open(&path).await.unwrap();
+        jar.store_cookies(
+            &url,
+            [
+                "session=a".to_owned(),
+                "remember=b; Max-Age=3600".to_owned(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let jar = CookieJar::open(&path).await.unwrap();
+        assert_eq!(
+            cookies(&jar, "https://example.com/"),
+            ["remember=b", "session=a"]
+        );
+
+        jar.clear().await.unwrap();
+        let jar = CookieJar::open(&path).await.unwrap();
+        assert!(cookies(&jar, "https://example.com/").is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,413 @@
+use std::fmt;
+
+use aes::{
+    Aes128, Aes192, Aes256,
+    cipher::{
+        BlockCipher, BlockDecryptMut, BlockEncrypt, BlockEncryptMut, KeyInit, KeyIvInit,
+        StreamCipher, block_padding::Pkcs7,
+    },
+};
+use aes_gcm::{
+    AesGcm, Nonce,
+    aead::{
+        Aead, Payload,
+        consts::{U12, U16},
+    },
+};
+use base64::{
+    Engine,
+    alphabet::{self, Alphabet},
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use sha2::Sha256;
+
+/// Longest output of [`evp_bytes_to_key`], in bytes.
+const MAX_DERIVED_LEN: usize = 1024;
+
+/// The reasons a cryptographic operation can fail.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CryptoError {
+    InvalidKeyLength,
+    InvalidIvLength,
+    InvalidPadding,
+    AuthenticationFailed,
+    InvalidBase64(String),
+    OutputTooLong,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidKeyLength => f.write_str("invalid key length"),
+            CryptoError::InvalidIvLength => f.write_str("invalid IV length"),
+            CryptoError::InvalidPadding => f.write_str("invalid padding"),
+            CryptoError::AuthenticationFailed => f.write_str("authentication failed"),
+            CryptoError::InvalidBase64(err) => write!(f, "invalid base64: {err}"),
+            CryptoError::OutputTooLong => f.write_str("output too long"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Base64Alphabet {
+    Standard,
+    UrlSafe,
+}
+
+pub fn hash(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Md5 => Md5::digest(data).to_vec(),
+        HashAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+        HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+    }
+}
+
+pub fn hmac(algorithm: HashAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+    fn mac<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    match algorithm {
+        HashAlgorithm::Md5 => mac::<Hmac<Md5>>(key, data),
+        HashAlgorithm::Sha1 => mac::<Hmac<Sha1>>(key, data),
+        HashAlgorithm::Sha256 => mac::<Hmac<Sha256>>(key, data),
+    }
+}
+
+/// Calls `$f` with the AES variant matching the length of `$key`.
+macro_rules! with_aes {
+    ($key:expr, $f:ident($($arg:expr),*)) => {
+        match $key.len() {
+            16 => $f::<Aes128>($($arg),*),
+            24 => $f::<Aes192>($($arg),*),
+            32 => $f::<Aes256>($($arg),*),
+            _ => Err(CryptoError::InvalidKeyLength),
+        }
+    };
+}
+
+pub fn aes_cbc_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    fn encrypt<C: BlockEncryptMut + BlockCipher + KeyInit>(
+        key: &[u8],
+        iv: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let cipher = cbc::Encryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| CryptoError::InvalidIvLength)?;
+        Ok(cipher.encrypt_padded_vec_mut::<Pkcs7>(data))
+    }
+
+    with_aes!(key, encrypt(key, iv, data))
+}
+
+pub fn aes_cbc_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    fn decrypt<C: BlockDecryptMut + BlockCipher + KeyInit>(
+        key: &[u8],
+        iv: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let cipher = cbc::Decryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| CryptoError::InvalidIvLength)?;
+        cipher
+            .decrypt_padded_vec_mut::<Pkcs7>(data)
+            .map_err(|_| CryptoError::InvalidPadding)
+    }
+
+    with_aes!(key, decrypt(key, iv, data))
+}
+
+/// Applies the AES-CTR keystream to `data`, which both encrypts and decrypts.
+pub fn aes_ctr(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    fn apply<C: BlockEncrypt + BlockCipher<BlockSize = U16> + KeyInit>(
+        key: &[u8],
+        iv: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let mut cipher = ctr::Ctr128BE::<C>::new_from_slices(key, iv)
+            .map_err(|_| CryptoError::InvalidIvLength)?;
+        let mut data = data.to_vec();
+        cipher.apply_keystream(&mut data);
+        Ok(data)
+    }
+
+    with_aes!(key, apply(key, iv, data))
+}
+
+/// Encrypts `data` with AES-GCM, returning the ciphertext followed by the tag.
+pub fn aes_gcm_encrypt(
+    key: &[u8],
+    nonce: &[u8],
+    data: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    fn encrypt<C: BlockEncrypt + BlockCipher<BlockSize = U16> + KeyInit>(
+        key: &[u8],
+        nonce: &[u8],
+        data: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let cipher =
+            AesGcm::<C, U12>::new_from_slice(key).map_err(|_| CryptoError::InvalidKeyLength)?;
+        let nonce = gcm_nonce(nonce)?;
+        cipher
+            .encrypt(nonce, Payload { msg: data, aad })
+            .map_err(|_| CryptoError::OutputTooLong)
+    }
+
+    with_aes!(key, encrypt(key, nonce, data, aad))
+}
+
+/// Decrypts `data`, the ciphertext followed by the tag, with AES-GCM.
+pub fn aes_gcm_decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    data: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    fn decrypt<C: BlockEncrypt + BlockCipher<BlockSize = U16> + KeyInit>(
+        key: &[u8],
+        nonce: &[u8],
+        data: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let cipher =
+            AesGcm::<C, U12>::new_from_slice(key).map_err(|_| CryptoError::InvalidKeyLength)?;
+        let nonce = gcm_nonce(nonce)?;
+        cipher
+            .decrypt(nonce, Payload { msg: data, aad })
+            .map_err(|_| CryptoError::AuthenticationFailed)
+    }
+
+    with_aes!(key, decrypt(key, nonce, data, aad))
+}
+
+fn gcm_nonce(nonce: &[u8]) -> Result<&Nonce<U12>, CryptoError> {
+    if nonce.len() != 12 {
+        return Err(CryptoError::InvalidIvLength);
+    }
+    Ok(Nonce::from_slice(nonce))
+}
+
+fn base64_engine(alphabet: Base64Alphabet, padded: bool) -> GeneralPurpose {
+    let alphabet: &Alphabet = match alphabet {
+        Base64Alphabet::Standard => &alphabet::STANDARD,
+        Base64Alphabet::UrlSafe => &alphabet::URL_SAFE,
+    };
+    let config = GeneralPurposeConfig::new()
+        .with_encode_padding(padded)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true);
+    GeneralPurpose::new(alphabet, config)
+}
+
+pub fn base64_encode(alphabet: Base64Alphabet, padded: bool, data: &[u8]) -> String {
+    base64_engine(alphabet, padded).encode(data)
+}
+
+/// Decodes base64 `data`, accepting it with or without padding.
+pub fn base64_decode(alphabet: Base64Alphabet, data: &str) -> Result<Vec<u8>, CryptoError> {
+    base64_engine(alphabet, false)
+        .decode(data.trim())
+        .map_err(|err| CryptoError::InvalidBase64(err.to_string()))
+}
+
+/// Derives a key and an IV like OpenSSL's `EVP_BytesToKey` with a single
+/// iteration.
+pub fn evp_bytes_to_key(
+    algorithm: HashAlgorithm,
+    password: &[u8],
+    salt: Option<&[u8]>,
+    key_len: usize,
+    iv_len: usize,
+) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+    let len = key_len
+        .checked_add(iv_len)
+        .filter(|len| *len <= MAX_DERIVED_LEN)
+        .ok_or(CryptoError::OutputTooLong)?;
+
+    let mut derived = Vec::with_capacity(len);
+    let mut block = Vec::new();
+    while derived.len() < len {
+        block.extend_from_slice(password);
+        block.extend_from_slice(salt.unwrap_or_default());
+        block = hash(algorithm, &block);
+        derived.extend_from_slice(&block);
+    }
+
+    derived.truncate(len);
+    let iv = derived.split_off(key_len);
+    Ok((derived, iv))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(data: &[u8]) -> String {
+        data.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn hashes() {
+        assert_eq!(
+            hex(&hash(HashAlgorithm::Md5, b"abc")),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            hex(&hash(HashAlgorithm::Sha1, b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&hash(HashAlgorithm::Sha256, b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn hmacs() {
+        // RFC 2202 and RFC 4231, test case 2.
+        let (key, data) = (b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&hmac(HashAlgorithm::Md5, key, data)),
+            "750c783e6ab0b503eaa86e310a5db738"
+        );
+        assert_eq!(
+            hex(&hmac(HashAlgorithm::Sha1, key, data)),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(
+            hex(&hmac(HashAlgorithm::Sha256, key, data)),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn aes_cbc() {
+        // NIST SP 800-38A, F.2.1, followed by a block of PKCS#7 padding.
+        let key = unhex("2b7e151628aed2a6abf7158809cf4f3c");
+        let iv = unhex("000102030405060708090a0b0c0d0e0f");
+        let plaintext = unhex("6bc1bee22e409f96e93d7e117393172a");
+        let ciphertext = aes_cbc_encrypt(&key, &iv, &plaintext).unwrap();
+        assert_eq!(hex(&ciphertext[..16]), "7649abac8119b246cee98e9b12e9197d");
+        assert_eq!(ciphertext.len(), 32);
+        assert_eq!(aes_cbc_decrypt(&key, &iv, &ciphertext).unwrap(), plaintext);
+
+        assert_eq!(
+            aes_cbc_decrypt(&key[..15], &iv, &ciphertext),
+            Err(CryptoError::InvalidKeyLength)
+        );
+        assert_eq!(
+            aes_cbc_decrypt(&key, &iv[..8], &ciphertext),
+            Err(CryptoError::InvalidIvLength)
+        );
+        assert_eq!(
+            aes_cbc_decrypt(&key, &iv, &ciphertext[..16]),
+            Err(CryptoError::InvalidPadding)
+        );
+    }
+
+    #[test]
+    fn aes_ctr_mode() {
+        // NIST SP 800-38A, F.5.5.
+        let key = unhex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+        let iv = unhex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = unhex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let ciphertext = aes_ctr(&key, &iv, &plaintext).unwrap();
+        assert_eq!(
+            hex(&ciphertext),
+            "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5"
+        );
+        assert_eq!(aes_ctr(&key, &iv, &ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn aes_gcm() {
+        // GCM specification, test case 4.
+        let key = unhex("feffe9928665731c6d6a8f9467308308");
+        let nonce = unhex("cafebabefacedbaddecaf888");
+        let plaintext = unhex(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let ciphertext = aes_gcm_encrypt(&key, &nonce, &plaintext, &aad).unwrap();
+        assert_eq!(
+            hex(&ciphertext),
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e0915bc94fbc3221a5db94fae95ae7121a47"
+        );
+        assert_eq!(
+            aes_gcm_decrypt(&key, &nonce, &ciphertext, &aad).unwrap(),
+            plaintext
+        );
+        assert_eq!(
+            aes_gcm_decrypt(&key, &nonce, &ciphertext, b""),
+            Err(CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn base64() {
+        let data = b"\xfb\xffnero";
+        assert_eq!(
+            base64_encode(Base64Alphabet::Standard, true, data),
+            "+/9uZXJv"
+        );
+        assert_eq!(base64_encode(Base64Alphabet::UrlSafe, false, b"ne"), "bmU");
+        assert_eq!(
+            base64_decode(Base64Alphabet::UrlSafe, "-_9uZXJv").unwrap(),
+            data
+        );
+        assert_eq!(
+            base64_decode(Base64Alphabet::Standard, "bmU=").unwrap(),
+            b"ne"
+        );
+        assert_eq!(
+            base64_decode(Base64Alphabet::Standard, "bmU").unwrap(),
+            b"ne"
+        );
+        assert!(base64_decode(Base64Alphabet::Standard, "b!U").is_err());
+    }
+
+    #[test]
+    fn evp_key_derivation() {
+        // `openssl enc -aes-256-cbc -md md5 -k password -S 0102030405060708 -P`
+        let (key, iv) = evp_bytes_to_key(
+            HashAlgorithm::Md5,
+            b"password",
+            Some(&unhex("0102030405060708")),
+            32,
+            16,
+        )
+        .unwrap();
+        assert_eq!(
+            hex(&key),
+            "e7b0971e52ca5cc8d0539fb3412f6316f7ba2e6ee293d9f3457b99436b51ce02"
+        );
+        assert_eq!(hex(&iv), "8d450e2ed75a84a923d4eac9fe49226b");
+
+        assert_eq!(
+            evp_bytes_to_key(HashAlgorithm::Md5, b"", None, 4096, 16),
+            Err(CryptoError::OutputTooLong)
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use http_body_util::BodyExt;
-use wasmtime::{Engine, Store};
+use wasmtime::{Engine, Store, component::Component};
 use wasmtime_wasi_http::{
     bindings::http::types::{Method, Scheme},
     types::HostOutgoingRequest,
@@ -9,6 +9,7 @@ use wasmtime_wasi_http::{
 use crate::{WasmState, types};
 
 pub mod since_v0_1_0_draft;
+pub mod since_v0_2_0_draft;
 
 pub(super) trait AsyncTryFromWithStore<T>: Sized {
     async fn try_from_with_store(value: T, store: &mut Store<WasmState>) -> Result<Self>;
@@ -27,6 +28,15 @@ where
     }
 }
 
+/// Whether `component` imports the interface `name` (e.g.
+/// `nero:extension/html`) at any version.
+pub(super) fn imports_interface(engine: &Engine, component: &Component, name: &str) -> bool {
+    component
+        .component_type()
+        .imports(engine)
+        .any(|(import, _)| import.split('@').next() == Some(name))
+}
+
 pub(super) trait IntoHttpRequest {
     async fn into_http_request(self) -> Result<types::HttpRequest>;
 }
@@ -81,12 +91,14 @@ impl IntoHttpRequest for HostOutgoingRequest {
 #[allow(non_camel_case_types)]
 pub enum ExtensionPre {
     V0_1_0_DRAFT(since_v0_1_0_draft::ExtensionPre<WasmState>),
+    V0_2_0_DRAFT(since_v0_2_0_draft::ExtensionPre<WasmState>),
 }
 
 impl ExtensionPre {
     pub fn engine(&self) -> &Engine {
         match self {
             ExtensionPre::V0_1_0_DRAFT(extension_pre) => extension_pre.engine(),
+            ExtensionPre::V0_2_0_DRAFT(extension_pre) => extension_pre.engine(),
         }
     }
 
@@ -96,6 +108,10 @@ impl ExtensionPre {
                 let extension = pre.instantiate_async(store).await?;
                 Ok(Extension::V0_1_0_DRAFT(extension))
             }
+            ExtensionPre::V0_2_0_DRAFT(pre) => {
+                let extension = pre.instantiate_async(store).await?;
+                Ok(Extension::V0_2_0_DRAFT(extension))
+            }
         }
     }
 }
@@ -103,4 +119,53 @@ impl ExtensionPre {
 #[allow(non_camel_case_types)]
 pub enum Extension {
     V0_1_0_DRAFT(since_v0_1_0_draft::Extension),
+    V0_2_0_DRAFT(since_v0_2_0_draft::Extension),
+}
+
+#[allow(non_camel_case_types)]
+pub enum FeedsPre {
+    V0_2_0_DRAFT(since_v0_2_0_draft::FeedsPre<WasmState>),
+}
+
+impl FeedsPre {
+    pub async fn instantiate_async(&self, store: &mut Store<WasmState>) -> Result<Feeds> {
+        match self {
+            FeedsPre::V0_2_0_DRAFT(pre) => {
+                let feeds = pre.instantiate_async(store).await?;
+                Ok(Feeds::V0_2_0_DRAFT(feeds))
+            }
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub enum Feeds {
+    V0_2_0_DRAFT(since_v0_2_0_draft::Feeds),
+}
+
+#[allow(non_camel_case_types)]
+pub enum ConfigurablePre {
+    V0_2_0_DRAFT(since_v0_2_0_draft::ConfigurablePre<WasmState>),
+}
+
+impl ConfigurablePre {
+    pub fn engine(&self) -> &Engine {
+        match self {
+            ConfigurablePre::V0_2_0_DRAFT(configurable_pre) => configurable_pre.engine(),
+        }
+    }
+
+    pub async fn instantiate_async(&self, store: &mut Store<WasmState>) -> Result<Configurable> {
+        match self {
+            ConfigurablePre::V0_2_0_DRAFT(pre) => {
+                let configurable = pre.instantiate_async(store).await?;
+                Ok(Configurable::V0_2_0_DRAFT(configurable))
+            }
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub enum Configurable {
+    V0_2_0_DRAFT(since_v0_2_0_draft::Configurable),
 }
@@ -60,20 +60,31 @@ impl From<FilterCategory> for crate::types::FilterCategory {
         crate::types::FilterCategory {
             id: filter_category.id,
             display_name: filter_category.display_name,
-            filters: filter_category
-                .filters
-                .into_iter()
-                .map(Into::into)
-                .collect(),
+            kind: crate::types::FilterKind::MultiSelect(
+                filter_category
+                    .filters
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            ),
+            required: false,
         }
     }
 }
 
-impl From<crate::types::SearchFilter> for SearchFilter {
-    fn from(search_filter: crate::types::SearchFilter) -> Self {
-        SearchFilter {
-            id: search_filter.id,
-            values: search_filter.values,
+impl TryFrom<crate::types::SearchFilter> for SearchFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(search_filter: crate::types::SearchFilter) -> Result<Self> {
+        match search_filter.value {
+            crate::types::FilterValue::Selected(values) => Ok(SearchFilter {
+                id: search_filter.id,
+                values,
+            }),
+            _ => Err(anyhow::anyhow!(
+                "filter category `{}` only supports selected values",
+                search_filter.id
+            )),
         }
     }
 }
@@ -108,6 +119,16 @@ impl AsyncTryFromWithStore<Series> for crate::types::Series {
             },
             synopsis: series.synopsis,
             r#type: series.type_,
+            alternative_titles: Vec::new(),
+            genres: Vec::new(),
+            status: None,
+            start_year: None,
+            end_year: None,
+            total_episodes: None,
+            rating: None,
+            studios: Vec::new(),
+            banner_resource: None,
+            external_ids: Vec::new(),
         })
     }
 }
@@ -135,7 +156,11 @@ impl AsyncTryFromWithStore<Episode> for crate::types::Episode {
     ) -> anyhow::Result<Self> {
         Ok(crate::types::Episode {
             id: episode.id,
-            number: episode.number,
+            number: Some(f32::from(episode.number)),
+            kind: crate::types::EpisodeKind::Regular,
+            season: None,
+            air_date: None,
+            duration: None,
             title: episode.title,
             thumbnail_resource: match episode.thumbnail_resource {
                 Some(mr) => Some(mr.try_into_with_store(store).await?),
@@ -155,6 +180,11 @@ impl AsyncTryFromWithStore<Video> for crate::types::Video {
             media_resource: video.media_resource.try_into_with_store(store).await?,
             server: video.server,
             resolution: video.resolution,
+            subtitles: Vec::new(),
+            audio_language: None,
+            is_dub: false,
+            has_hardsubs: false,
+            audio_tracks: Vec::new(),
         })
     }
 }
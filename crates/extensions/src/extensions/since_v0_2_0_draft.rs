@@ -0,0 +1,812 @@
+use std::str::FromStr;
+
+use self::nero::extension::types::{
+    AudioTrack, Episode, EpisodeKind, EpisodesPage, ExternalId, Feed, FeedItem, FeedPage, Filter,
+    FilterCategory, FilterKind, FilterValue, RangeValue, SearchFilter, Season, Series, SeriesPage,
+    SeriesStatus, Setting, SettingKind, SettingValue, SortDirection, SortValue, Subtitle,
+    SubtitleFormat, TriStateValue, Video,
+};
+use self::nero::extension::{crypto, html, javascript, login, settings};
+
+use anyhow::Result;
+use magnet_uri::MagnetURI;
+use semver::Version;
+use url::Url;
+use wasmtime::{
+    Engine,
+    component::{Component, HasSelf, Linker, Resource, bindgen},
+};
+use wasmtime_wasi_http::types::HostOutgoingRequest;
+
+use crate::{
+    AsyncTryIntoWithStore, WasmState,
+    crypto::{Base64Alphabet, CryptoError, HashAlgorithm},
+    extensions::{
+        AsyncTryFromWithStore, IntoHttpRequest, imports_interface,
+        since_v0_2_0_draft::nero::extension::types::MediaResource,
+    },
+    html::{HtmlDocument, HtmlElement},
+    javascript::JsError,
+    login::LoginError,
+};
+
+pub const MIN_VER: Version = Version::new(0, 2, 0);
+
+bindgen!({
+    path: "./wit/v0.2.0-draft",
+    world: "nero:extension/extension",
+    imports: { default: async | trappable, },
+    exports: { default: async },
+    with: {
+        "wasi:http": wasmtime_wasi_http::bindings::http,
+        "wasi:logging": nero_wasi_logging::logging,
+        "nero:wasi-logging/structured": nero_wasi_logging::structured,
+        "nero:extension/persistent-cache": super::since_v0_1_0_draft::nero::extension::persistent_cache,
+        "nero:extension/html/document": crate::html::HtmlDocument,
+        "nero:extension/html/element": crate::html::HtmlElement,
+    },
+});
+
+mod feeds {
+    wasmtime::component::bindgen!({
+        path: "./wit/v0.2.0-draft",
+        world: "nero:nero-extensions/feeds",
+        exports: { default: async },
+        with: {
+            "wasi:http": wasmtime_wasi_http::bindings::http,
+            "nero:extension/types": super::nero::extension::types,
+        },
+    });
+}
+
+mod configurable {
+    wasmtime::component::bindgen!({
+        path: "./wit/v0.2.0-draft",
+        world: "nero:nero-extensions/configurable",
+        exports: { default: async },
+        with: {
+            "nero:extension/types": super::nero::extension::types,
+        },
+    });
+}
+
+pub use self::{
+    configurable::{Configurable, ConfigurablePre},
+    feeds::{Feeds, FeedsPre},
+};
+
+pub fn linker(engine: &Engine, component: &Component) -> Result<Linker<WasmState>> {
+    let mut linker = Linker::new(engine);
+    wasmtime_wasi::p2::add_to_linker_async(&mut linker).unwrap();
+    wasmtime_wasi_http::add_only_http_to_linker_async(&mut linker).unwrap();
+    nero_wasi_logging::add_to_linker(&mut linker).unwrap();
+    settings::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+    login::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+    crypto::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+    if imports_interface(engine, component, "nero:extension/html") {
+        html::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+    }
+    if imports_interface(engine, component, "nero:extension/javascript") {
+        javascript::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+    }
+    Ok(linker)
+}
+
+impl From<crypto::HashAlgorithm> for HashAlgorithm {
+    fn from(algorithm: crypto::HashAlgorithm) -> Self {
+        match algorithm {
+            crypto::HashAlgorithm::Md5 => Self::Md5,
+            crypto::HashAlgorithm::Sha1 => Self::Sha1,
+            crypto::HashAlgorithm::Sha256 => Self::Sha256,
+        }
+    }
+}
+
+impl From<crypto::Base64Alphabet> for Base64Alphabet {
+    fn from(alphabet: crypto::Base64Alphabet) -> Self {
+        match alphabet {
+            crypto::Base64Alphabet::Standard => Self::Standard,
+            crypto::Base64Alphabet::UrlSafe => Self::UrlSafe,
+        }
+    }
+}
+
+impl From<CryptoError> for crypto::Error {
+    fn from(err: CryptoError) -> Self {
+        match err {
+            CryptoError::InvalidKeyLength => Self::InvalidKeyLength,
+            CryptoError::InvalidIvLength => Self::InvalidIvLength,
+            CryptoError::InvalidPadding => Self::InvalidPadding,
+            CryptoError::AuthenticationFailed => Self::AuthenticationFailed,
+            CryptoError::InvalidBase64(err) => Self::InvalidBase64(err),
+            CryptoError::OutputTooLong => Self::OutputTooLong,
+        }
+    }
+}
+
+impl crypto::Host for WasmState {
+    async fn hash(
+        &mut self,
+        algorithm: crypto::HashAlgorithm,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<Vec<u8>> {
+        Ok(crate::crypto::hash(algorithm.into(), &data))
+    }
+
+    async fn hmac(
+        &mut self,
+        algorithm: crypto::HashAlgorithm,
+        key: Vec<u8>,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<Vec<u8>> {
+        Ok(crate::crypto::hmac(algorithm.into(), &key, &data))
+    }
+
+    async fn aes_cbc_encrypt(
+        &mut self,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::aes_cbc_encrypt(&key, &iv, &data).map_err(Into::into))
+    }
+
+    async fn aes_cbc_decrypt(
+        &mut self,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::aes_cbc_decrypt(&key, &iv, &data).map_err(Into::into))
+    }
+
+    async fn aes_ctr(
+        &mut self,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::aes_ctr(&key, &iv, &data).map_err(Into::into))
+    }
+
+    async fn aes_gcm_encrypt(
+        &mut self,
+        key: Vec<u8>,
+        nonce: Vec<u8>,
+        data: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::aes_gcm_encrypt(&key, &nonce, &data, &aad).map_err(Into::into))
+    }
+
+    async fn aes_gcm_decrypt(
+        &mut self,
+        key: Vec<u8>,
+        nonce: Vec<u8>,
+        data: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::aes_gcm_decrypt(&key, &nonce, &data, &aad).map_err(Into::into))
+    }
+
+    async fn base64_encode(
+        &mut self,
+        alphabet: crypto::Base64Alphabet,
+        padded: bool,
+        data: Vec<u8>,
+    ) -> wasmtime::Result<String> {
+        Ok(crate::crypto::base64_encode(alphabet.into(), padded, &data))
+    }
+
+    async fn base64_decode(
+        &mut self,
+        alphabet: crypto::Base64Alphabet,
+        data: String,
+    ) -> wasmtime::Result<Result<Vec<u8>, crypto::Error>> {
+        Ok(crate::crypto::base64_decode(alphabet.into(), &data).map_err(Into::into))
+    }
+
+    async fn evp_bytes_to_key(
+        &mut self,
+        algorithm: crypto::HashAlgorithm,
+        password: Vec<u8>,
+        salt: Option<Vec<u8>>,
+        key_length: u32,
+        iv_length: u32,
+    ) -> wasmtime::Result<Result<(Vec<u8>, Vec<u8>), crypto::Error>> {
+        Ok(crate::crypto::evp_bytes_to_key(
+            algorithm.into(),
+            &password,
+            salt.as_deref(),
+            key_length as usize,
+            iv_length as usize,
+        )
+        .map_err(Into::into))
+    }
+}
+
+impl From<JsError> for javascript::Error {
+    fn from(err: JsError) -> Self {
+        match err {
+            JsError::Syntax(err) => Self::Syntax(err),
+            JsError::Runtime(err) => Self::Runtime(err),
+            JsError::LimitExceeded => Self::LimitExceeded,
+        }
+    }
+}
+
+impl javascript::Host for WasmState {
+    async fn eval(&mut self, code: String) -> wasmtime::Result<Result<String, javascript::Error>> {
+        Ok(crate::javascript::eval(code).await.map_err(Into::into))
+    }
+
+    async fn unpack(
+        &mut self,
+        packed: String,
+    ) -> wasmtime::Result<Result<String, javascript::Error>> {
+        Ok(crate::javascript::unpack(packed).await.map_err(Into::into))
+    }
+}
+
+impl html::Host for WasmState {}
+
+impl html::HostDocument for WasmState {
+    async fn parse(&mut self, html: String) -> wasmtime::Result<Resource<HtmlDocument>> {
+        Ok(self.table.push(HtmlDocument::parse(&html))?)
+    }
+
+    async fn parse_fragment(&mut self, html: String) -> wasmtime::Result<Resource<HtmlDocument>> {
+        Ok(self.table.push(HtmlDocument::parse_fragment(&html))?)
+    }
+
+    async fn root(
+        &mut self,
+        document: Resource<HtmlDocument>,
+    ) -> wasmtime::Result<Resource<HtmlElement>> {
+        let root = self.table.get(&document)?.root();
+        Ok(self.table.push(root)?)
+    }
+
+    async fn select(
+        &mut self,
+        document: Resource<HtmlDocument>,
+        selector: String,
+    ) -> wasmtime::Result<Result<Vec<Resource<HtmlElement>>, html::Error>> {
+        match self.table.get(&document)?.select(&selector) {
+            Ok(elements) => Ok(Ok(self.push_elements(elements)?)),
+            Err(err) => Ok(Err(html::Error::InvalidSelector(err.to_string()))),
+        }
+    }
+
+    async fn select_first(
+        &mut self,
+        document: Resource<HtmlDocument>,
+        selector: String,
+    ) -> wasmtime::Result<Result<Option<Resource<HtmlElement>>, html::Error>> {
+        match self.table.get(&document)?.select_first(&selector) {
+            Ok(element) => Ok(Ok(element.map(|e| self.table.push(e)).transpose()?)),
+            Err(err) => Ok(Err(html::Error::InvalidSelector(err.to_string()))),
+        }
+    }
+
+    async fn drop(&mut self, document: Resource<HtmlDocument>) -> wasmtime::Result<()> {
+        self.table.delete(document)?;
+        Ok(())
+    }
+}
+
+impl html::HostElement for WasmState {
+    async fn name(&mut self, element: Resource<HtmlElement>) -> wasmtime::Result<String> {
+        Ok(self.table.get(&element)?.name())
+    }
+
+    async fn attr(
+        &mut self,
+        element: Resource<HtmlElement>,
+        name: String,
+    ) -> wasmtime::Result<Option<String>> {
+        Ok(self.table.get(&element)?.attr(&name))
+    }
+
+    async fn attrs(
+        &mut self,
+        element: Resource<HtmlElement>,
+    ) -> wasmtime::Result<Vec<(String, String)>> {
+        Ok(self.table.get(&element)?.attrs())
+    }
+
+    async fn text(&mut self, element: Resource<HtmlElement>) -> wasmtime::Result<String> {
+        Ok(self.table.get(&element)?.text())
+    }
+
+    async fn inner_html(&mut self, element: Resource<HtmlElement>) -> wasmtime::Result<String> {
+        Ok(self.table.get(&element)?.inner_html())
+    }
+
+    async fn outer_html(&mut self, element: Resource<HtmlElement>) -> wasmtime::Result<String> {
+        Ok(self.table.get(&element)?.outer_html())
+    }
+
+    async fn parent(
+        &mut self,
+        element: Resource<HtmlElement>,
+    ) -> wasmtime::Result<Option<Resource<HtmlElement>>> {
+        let parent = self.table.get(&element)?.parent();
+        Ok(parent.map(|p| self.table.push(p)).transpose()?)
+    }
+
+    async fn children(
+        &mut self,
+        element: Resource<HtmlElement>,
+    ) -> wasmtime::Result<Vec<Resource<HtmlElement>>> {
+        let children = self.table.get(&element)?.children();
+        self.push_elements(children)
+    }
+
+    async fn select(
+        &mut self,
+        element: Resource<HtmlElement>,
+        selector: String,
+    ) -> wasmtime::Result<Result<Vec<Resource<HtmlElement>>, html::Error>> {
+        match self.table.get(&element)?.select(&selector) {
+            Ok(elements) => Ok(Ok(self.push_elements(elements)?)),
+            Err(err) => Ok(Err(html::Error::InvalidSelector(err.to_string()))),
+        }
+    }
+
+    async fn select_first(
+        &mut self,
+        element: Resource<HtmlElement>,
+        selector: String,
+    ) -> wasmtime::Result<Result<Option<Resource<HtmlElement>>, html::Error>> {
+        match self.table.get(&element)?.select_first(&selector) {
+            Ok(element) => Ok(Ok(element.map(|e| self.table.push(e)).transpose()?)),
+            Err(err) => Ok(Err(html::Error::InvalidSelector(err.to_string()))),
+        }
+    }
+
+    async fn drop(&mut self, element: Resource<HtmlElement>) -> wasmtime::Result<()> {
+        self.table.delete(element)?;
+        Ok(())
+    }
+}
+
+impl WasmState {
+    fn push_elements(
+        &mut self,
+        elements: Vec<HtmlElement>,
+    ) -> wasmtime::Result<Vec<Resource<HtmlElement>>> {
+        elements
+            .into_iter()
+            .map(|element| Ok(self.table.push(element)?))
+            .collect()
+    }
+}
+
+impl settings::Host for WasmState {
+    async fn get(&mut self, id: String) -> wasmtime::Result<Option<SettingValue>> {
+        Ok(self.settings.get(&id).cloned().map(Into::into))
+    }
+}
+
+impl login::Host for WasmState {
+    async fn open(
+        &mut self,
+        url: String,
+        target_url: String,
+    ) -> wasmtime::Result<Result<(), login::Error>> {
+        let Some(handler) = self.login_handler.clone() else {
+            return Ok(Err(login::Error::Unsupported));
+        };
+        let (url, target_url) = match (Url::parse(&url), Url::parse(&target_url)) {
+            (Ok(url), Ok(target_url)) => (url, target_url),
+            (Err(err), _) | (_, Err(err)) => return Ok(Err(login::Error::Other(err.to_string()))),
+        };
+
+        let cookies = match handler.login(url, target_url.clone()).await {
+            Ok(cookies) => cookies,
+            Err(LoginError::Unsupported) => return Ok(Err(login::Error::Unsupported)),
+            Err(LoginError::Cancelled) => return Ok(Err(login::Error::Cancelled)),
+            Err(LoginError::Other(err)) => return Ok(Err(login::Error::Other(err))),
+        };
+
+        Ok(self
+            .cookie_jar
+            .store_cookies(&target_url, cookies)
+            .await
+            .map_err(|err| login::Error::Other(err.to_string())))
+    }
+}
+
+impl From<Setting> for crate::types::Setting {
+    fn from(setting: Setting) -> Self {
+        crate::types::Setting {
+            id: setting.id,
+            display_name: setting.display_name,
+            description: setting.description,
+            kind: setting.kind.into(),
+            required: setting.required,
+        }
+    }
+}
+
+impl From<SettingKind> for crate::types::SettingKind {
+    fn from(kind: SettingKind) -> Self {
+        match kind {
+            SettingKind::Text(text) => crate::types::SettingKind::Text {
+                default: text.default,
+                placeholder: text.placeholder,
+            },
+            SettingKind::Secret => crate::types::SettingKind::Secret,
+            SettingKind::Toggle(default) => crate::types::SettingKind::Toggle { default },
+            SettingKind::Choice(choice) => crate::types::SettingKind::Choice {
+                options: choice
+                    .options
+                    .into_iter()
+                    .map(|option| crate::types::SettingOption {
+                        id: option.id,
+                        display_name: option.display_name,
+                    })
+                    .collect(),
+                default: choice.default,
+            },
+            SettingKind::Number(number) => crate::types::SettingKind::Number {
+                min: number.min,
+                max: number.max,
+                default: number.default,
+            },
+        }
+    }
+}
+
+impl From<crate::types::SettingValue> for SettingValue {
+    fn from(value: crate::types::SettingValue) -> Self {
+        match value {
+            crate::types::SettingValue::Text(text) => SettingValue::Text(text),
+            crate::types::SettingValue::Secret(secret) => SettingValue::Secret(secret),
+            crate::types::SettingValue::Toggle(toggle) => SettingValue::Toggle(toggle),
+            crate::types::SettingValue::Choice(choice) => SettingValue::Choice(choice),
+            crate::types::SettingValue::Number(number) => SettingValue::Number(number),
+        }
+    }
+}
+
+impl From<Filter> for crate::types::Filter {
+    fn from(filter: Filter) -> Self {
+        crate::types::Filter {
+            id: filter.id,
+            display_name: filter.display_name,
+        }
+    }
+}
+
+impl From<FilterCategory> for crate::types::FilterCategory {
+    fn from(filter_category: FilterCategory) -> Self {
+        crate::types::FilterCategory {
+            id: filter_category.id,
+            display_name: filter_category.display_name,
+            kind: filter_category.kind.into(),
+            required: filter_category.required,
+        }
+    }
+}
+
+impl From<FilterKind> for crate::types::FilterKind {
+    fn from(kind: FilterKind) -> Self {
+        let filters = |filters: Vec<Filter>| filters.into_iter().map(Into::into).collect();
+
+        match kind {
+            FilterKind::MultiSelect(items) => crate::types::FilterKind::MultiSelect(filters(items)),
+            FilterKind::SingleSelect(items) => {
+                crate::types::FilterKind::SingleSelect(filters(items))
+            }
+            FilterKind::TriState(items) => crate::types::FilterKind::TriState(filters(items)),
+            FilterKind::Range(bounds) => {
+                crate::types::FilterKind::Range(crate::types::RangeBounds {
+                    min: bounds.min,
+                    max: bounds.max,
+                    step: bounds.step,
+                })
+            }
+            FilterKind::Sort(items) => crate::types::FilterKind::Sort(filters(items)),
+            FilterKind::Text(placeholder) => crate::types::FilterKind::Text { placeholder },
+        }
+    }
+}
+
+impl From<crate::types::SortDirection> for SortDirection {
+    fn from(direction: crate::types::SortDirection) -> Self {
+        match direction {
+            crate::types::SortDirection::Ascending => SortDirection::Ascending,
+            crate::types::SortDirection::Descending => SortDirection::Descending,
+        }
+    }
+}
+
+impl From<crate::types::FilterValue> for FilterValue {
+    fn from(value: crate::types::FilterValue) -> Self {
+        match value {
+            crate::types::FilterValue::Selected(values) => FilterValue::Selected(values),
+            crate::types::FilterValue::TriState { included, excluded } => {
+                FilterValue::TriState(TriStateValue { included, excluded })
+            }
+            crate::types::FilterValue::Range { min, max } => {
+                FilterValue::Range(RangeValue { min, max })
+            }
+            crate::types::FilterValue::Sort { id, direction } => FilterValue::Sort(SortValue {
+                id,
+                direction: direction.into(),
+            }),
+            crate::types::FilterValue::Text(text) => FilterValue::Text(text),
+        }
+    }
+}
+
+impl From<crate::types::SearchFilter> for SearchFilter {
+    fn from(search_filter: crate::types::SearchFilter) -> Self {
+        SearchFilter {
+            id: search_filter.id,
+            value: search_filter.value.into(),
+        }
+    }
+}
+
+impl AsyncTryFromWithStore<SeriesPage> for crate::types::SeriesPage {
+    async fn try_from_with_store(
+        page: SeriesPage,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        let mut items = Vec::new();
+        for series in page.series {
+            items.push(series.try_into_with_store(store).await?);
+        }
+        Ok(crate::types::SeriesPage {
+            items,
+            has_next_page: page.has_next_page,
+        })
+    }
+}
+
+impl AsyncTryFromWithStore<Series> for crate::types::Series {
+    async fn try_from_with_store(
+        series: Series,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        Ok(crate::types::Series {
+            id: series.id,
+            title: series.title,
+            poster_resource: match series.poster_resource {
+                Some(mr) => Some(mr.try_into_with_store(store).await?),
+                None => None,
+            },
+            synopsis: series.synopsis,
+            r#type: series.type_,
+            alternative_titles: series.alternative_titles,
+            genres: series.genres,
+            status: series.status.map(Into::into),
+            start_year: series.start_year,
+            end_year: series.end_year,
+            total_episodes: series.total_episodes,
+            rating: series.rating,
+            studios: series.studios,
+            banner_resource: match series.banner_resource {
+                Some(mr) => Some(mr.try_into_with_store(store).await?),
+                None => None,
+            },
+            external_ids: series.external_ids.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl From<SeriesStatus> for crate::types::SeriesStatus {
+    fn from(status: SeriesStatus) -> Self {
+        match status {
+            SeriesStatus::Upcoming => crate::types::SeriesStatus::Upcoming,
+            SeriesStatus::Airing => crate::types::SeriesStatus::Airing,
+            SeriesStatus::Finished => crate::types::SeriesStatus::Finished,
+            SeriesStatus::Hiatus => crate::types::SeriesStatus::Hiatus,
+            SeriesStatus::Cancelled => crate::types::SeriesStatus::Cancelled,
+        }
+    }
+}
+
+impl From<ExternalId> for crate::types::ExternalId {
+    fn from(external_id: ExternalId) -> Self {
+        match external_id {
+            ExternalId::Anilist(id) => crate::types::ExternalId::Anilist(id),
+            ExternalId::MyAnimeList(id) => crate::types::ExternalId::MyAnimeList(id),
+            ExternalId::Tmdb(id) => crate::types::ExternalId::Tmdb(id),
+        }
+    }
+}
+
+impl AsyncTryFromWithStore<EpisodesPage> for crate::types::EpisodesPage {
+    async fn try_from_with_store(
+        page: EpisodesPage,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        let mut items = Vec::new();
+        for episode in page.episodes {
+            items.push(episode.try_into_with_store(store).await?);
+        }
+        Ok(crate::types::EpisodesPage {
+            items,
+            has_next_page: page.has_next_page,
+        })
+    }
+}
+
+impl AsyncTryFromWithStore<Episode> for crate::types::Episode {
+    async fn try_from_with_store(
+        episode: Episode,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        Ok(crate::types::Episode {
+            id: episode.id,
+            number: episode.number,
+            kind: episode.kind.into(),
+            season: episode.season.map(Into::into),
+            air_date: episode.air_date,
+            duration: episode.duration,
+            title: episode.title,
+            thumbnail_resource: match episode.thumbnail_resource {
+                Some(mr) => Some(mr.try_into_with_store(store).await?),
+                None => None,
+            },
+            description: episode.description,
+        })
+    }
+}
+
+impl From<EpisodeKind> for crate::types::EpisodeKind {
+    fn from(kind: EpisodeKind) -> Self {
+        match kind {
+            EpisodeKind::Regular => crate::types::EpisodeKind::Regular,
+            EpisodeKind::Special => crate::types::EpisodeKind::Special,
+            EpisodeKind::Recap => crate::types::EpisodeKind::Recap,
+            EpisodeKind::Ova => crate::types::EpisodeKind::Ova,
+            EpisodeKind::Movie => crate::types::EpisodeKind::Movie,
+        }
+    }
+}
+
+impl From<Season> for crate::types::Season {
+    fn from(season: Season) -> Self {
+        crate::types::Season {
+            id: season.id,
+            number: season.number,
+            title: season.title,
+        }
+    }
+}
+
+impl From<Feed> for crate::types::Feed {
+    fn from(feed: Feed) -> Self {
+        crate::types::Feed {
+            id: feed.id,
+            display_name: feed.display_name,
+        }
+    }
+}
+
+impl AsyncTryFromWithStore<FeedPage> for crate::types::FeedPage {
+    async fn try_from_with_store(
+        page: FeedPage,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        let mut items = Vec::new();
+        for item in page.items {
+            items.push(item.try_into_with_store(store).await?);
+        }
+        Ok(crate::types::FeedPage {
+            items,
+            has_next_page: page.has_next_page,
+        })
+    }
+}
+
+impl AsyncTryFromWithStore<FeedItem> for crate::types::FeedItem {
+    async fn try_from_with_store(
+        item: FeedItem,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        match item {
+            FeedItem::Series(series) => Ok(crate::types::FeedItem::Series(
+                series.try_into_with_store(store).await?,
+            )),
+            FeedItem::Episode((series, episode)) => Ok(crate::types::FeedItem::Episode {
+                series: series.try_into_with_store(store).await?,
+                episode: episode.try_into_with_store(store).await?,
+            }),
+        }
+    }
+}
+
+impl AsyncTryFromWithStore<Video> for crate::types::Video {
+    async fn try_from_with_store(
+        video: Video,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        let mut subtitles = Vec::with_capacity(video.subtitles.len());
+        for subtitle in video.subtitles {
+            subtitles.push(subtitle.try_into_with_store(store).await?);
+        }
+
+        let mut audio_tracks = Vec::with_capacity(video.audio_tracks.len());
+        for audio_track in video.audio_tracks {
+            audio_tracks.push(audio_track.try_into_with_store(store).await?);
+        }
+
+        Ok(crate::types::Video {
+            media_resource: video.media_resource.try_into_with_store(store).await?,
+            server: video.server,
+            resolution: video.resolution,
+            subtitles,
+            audio_language: video.audio_language,
+            is_dub: video.is_dub,
+            has_hardsubs: video.has_hardsubs,
+            audio_tracks,
+        })
+    }
+}
+
+impl AsyncTryFromWithStore<AudioTrack> for crate::types::AudioTrack {
+    async fn try_from_with_store(
+        audio_track: AudioTrack,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        Ok(crate::types::AudioTrack {
+            language: audio_track.language,
+            label: audio_track.label,
+            is_dub: audio_track.is_dub,
+            media_resource: audio_track
+                .media_resource
+                .try_into_with_store(store)
+                .await?,
+        })
+    }
+}
+
+impl From<SubtitleFormat> for crate::types::SubtitleFormat {
+    fn from(format: SubtitleFormat) -> Self {
+        match format {
+            SubtitleFormat::Vtt => crate::types::SubtitleFormat::Vtt,
+            SubtitleFormat::Srt => crate::types::SubtitleFormat::Srt,
+            SubtitleFormat::Ass => crate::types::SubtitleFormat::Ass,
+        }
+    }
+}
+
+impl AsyncTryFromWithStore<Subtitle> for crate::types::Subtitle {
+    async fn try_from_with_store(
+        subtitle: Subtitle,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> anyhow::Result<Self> {
+        Ok(crate::types::Subtitle {
+            language: subtitle.language,
+            label: subtitle.label,
+            format: subtitle.format.into(),
+            media_resource: subtitle.media_resource.try_into_with_store(store).await?,
+        })
+    }
+}
+
+impl AsyncTryFromWithStore<MediaResource> for crate::types::MediaResource {
+    async fn try_from_with_store(
+        media_resource: MediaResource,
+        store: &mut wasmtime::Store<WasmState>,
+    ) -> Result<Self> {
+        match media_resource {
+            MediaResource::HttpRequest(resource) => {
+                let outgoing: HostOutgoingRequest = resource.try_into_with_store(store).await?;
+                let request = outgoing.into_http_request().await?;
+                Ok(crate::types::MediaResource::HttpRequest(Box::new(request)))
+            }
+            MediaResource::MagnetUri(uri) => {
+                MagnetURI::from_str(&uri)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse magnet URI: {:?}", e))?;
+                Ok(crate::types::MediaResource::MagnetUri(uri))
+            }
+        }
+    }
+}
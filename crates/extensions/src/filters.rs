@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+
+use crate::types::{Filter, FilterCategory, FilterKind, FilterValue, SearchFilter};
+
+/// Checks `filters` against the categories declared by an extension before
+/// they are handed to its `search` export.
+pub fn validate_search_filters(
+    categories: &[FilterCategory],
+    filters: &[SearchFilter],
+) -> Result<()> {
+    let mut seen = HashSet::new();
+
+    for filter in filters {
+        if !seen.insert(filter.id.as_str()) {
+            bail!("filter category `{}` is set more than once", filter.id);
+        }
+
+        let Some(category) = categories.iter().find(|c| c.id == filter.id) else {
+            bail!("unknown filter category `{}`", filter.id);
+        };
+
+        validate_value(category, &filter.value)?;
+    }
+
+    for category in categories {
+        if category.required && !seen.contains(category.id.as_str()) {
+            bail!("filter category `{}` is required", category.id);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_value(category: &FilterCategory, value: &FilterValue) -> Result<()> {
+    match (&category.kind, value) {
+        (FilterKind::MultiSelect(filters), FilterValue::Selected(ids)) => {
+            validate_ids(category, filters, ids)
+        }
+        (FilterKind::SingleSelect(filters), FilterValue::Selected(ids)) => {
+            if ids.len() > 1 {
+                bail!("filter category `{}` accepts a single value", category.id);
+            }
+            validate_ids(category, filters, ids)
+        }
+        (FilterKind::TriState(filters), FilterValue::TriState { included, excluded }) => {
+            validate_ids(category, filters, included)?;
+            validate_ids(category, filters, excluded)?;
+            if let Some(id) = included.iter().find(|id| excluded.contains(id)) {
+                bail!(
+                    "filter `{id}` of category `{}` is both included and excluded",
+                    category.id
+                );
+            }
+            Ok(())
+        }
+        (FilterKind::Range(bounds), FilterValue::Range { min, max }) => {
+            for value in [min, max].into_iter().flatten() {
+                if !(bounds.min..=bounds.max).contains(value) {
+                    bail!(
+                        "value {value} of filter category `{}` is outside [{}, {}]",
+                        category.id,
+                        bounds.min,
+                        bounds.max
+                    );
+                }
+            }
+            if let (Some(min), Some(max)) = (min, max)
+                && min > max
+            {
+                bail!("range of filter category `{}` is empty", category.id);
+            }
+            Ok(())
+        }
+        (FilterKind::Sort(filters), FilterValue::Sort { id, .. }) => {
+            validate_ids(category, filters, std::slice::from_ref(id))
+        }
+        (FilterKind::Text { .. }, FilterValue::Text(_)) => Ok(()),
+        _ => bail!(
+            "value does not match the kind of filter category `{}`",
+            category.id
+        ),
+    }
+}
+
+fn validate_ids(category: &FilterCategory, filters: &[Filter], ids: &[String]) -> Result<()> {
+    for id in ids {
+        if !filters.iter().any(|filter| &filter.id == id) {
+            bail!("unknown filter `{id}` in category `{}`", category.id);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RangeBounds, SortDirection};
+
+    fn genres(required: bool) -> FilterCategory {
+        FilterCategory {
+            id: "genre".to_owned(),
+            display_name: "Genre".to_owned(),
+            kind: FilterKind::SingleSelect(vec![Filter {
+                id: "drama".to_owned(),
+                display_name: "Drama".to_owned(),
+            }]),
+            required,
+        }
+    }
+
+    fn category(id: &str, kind: FilterKind) -> FilterCategory {
+        FilterCategory {
+            id: id.to_owned(),
+            display_name: id.to_owned(),
+            kind,
+            required: false,
+        }
+    }
+
+    fn filters(ids: &[&str]) -> Vec<Filter> {
+        ids.iter()
+            .map(|id| Filter {
+                id: id.to_string(),
+                display_name: id.to_string(),
+            })
+            .collect()
+    }
+
+    fn strings(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn genre(ids: &[&str]) -> SearchFilter {
+        SearchFilter {
+            id: "genre".to_owned(),
+            value: FilterValue::Selected(ids.iter().map(|id| id.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn requires_required_category_without_filters() {
+        let err = validate_search_filters(&[genres(true)], &[]).unwrap_err();
+        assert_eq!(err.to_string(), "filter category `genre` is required");
+    }
+
+    #[test]
+    fn accepts_no_filters_without_required_category() {
+        assert!(validate_search_filters(&[genres(false)], &[]).is_ok());
+    }
+
+    #[test]
+    fn accepts_required_category() {
+        assert!(validate_search_filters(&[genres(true)], &[genre(&["drama"])]).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        let categories = [genres(false)];
+        assert!(validate_search_filters(&categories, &[genre(&["drama"]), genre(&[])]).is_err());
+        assert!(validate_search_filters(&categories, &[genre(&["drama", "drama"])]).is_err());
+        assert!(validate_search_filters(&categories, &[genre(&["comedy"])]).is_err());
+    }
+
+    #[test]
+    fn validates_tri_state_values() {
+        let tags = category("tags", FilterKind::TriState(filters(&["gore", "romance"])));
+
+        let value = FilterValue::TriState {
+            included: strings(&["romance"]),
+            excluded: strings(&["gore"]),
+        };
+        assert!(validate_value(&tags, &value).is_ok());
+
+        let value = FilterValue::TriState {
+            included: strings(&["gore"]),
+            excluded: strings(&["romance", "gore"]),
+        };
+        let err = validate_value(&tags, &value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "filter `gore` of category `tags` is both included and excluded"
+        );
+    }
+
+    #[test]
+    fn validates_range_values() {
+        let year = category(
+            "year",
+            FilterKind::Range(RangeBounds {
+                min: 1990.0,
+                max: 2020.0,
+                step: Some(1.0),
+            }),
+        );
+        let range = |min, max| FilterValue::Range { min, max };
+
+        assert!(validate_value(&year, &range(Some(2000.0), None)).is_ok());
+        assert!(validate_value(&year, &range(Some(2000.0), Some(2000.0))).is_ok());
+
+        let err = validate_value(&year, &range(None, Some(2021.0))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value 2021 of filter category `year` is outside [1990, 2020]"
+        );
+
+        let err = validate_value(&year, &range(Some(2010.0), Some(2000.0))).unwrap_err();
+        assert_eq!(err.to_string(), "range of filter category `year` is empty");
+    }
+
+    #[test]
+    fn validates_sort_ids() {
+        let sort = category("sort", FilterKind::Sort(filters(&["title"])));
+        let by = |id: &str| FilterValue::Sort {
+            id: id.to_owned(),
+            direction: SortDirection::Descending,
+        };
+
+        assert!(validate_value(&sort, &by("title")).is_ok());
+        let err = validate_value(&sort, &by("rating")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown filter `rating` in category `sort`"
+        );
+    }
+
+    #[test]
+    fn accepts_any_text() {
+        let query = category("query", FilterKind::Text { placeholder: None });
+        assert!(validate_value(&query, &FilterValue::Text(String::new())).is_ok());
+        assert!(validate_value(&query, &FilterValue::Text("a b".to_owned())).is_ok());
+    }
+
+    #[test]
+    fn rejects_value_of_another_kind() {
+        let query = category("query", FilterKind::Text { placeholder: None });
+        let err = validate_value(&query, &FilterValue::Selected(strings(&["a"]))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value does not match the kind of filter category `query`"
+        );
+
+        let value = FilterValue::Range {
+            min: None,
+            max: None,
+        };
+        assert!(validate_value(&genres(false), &value).is_err());
+    }
+}
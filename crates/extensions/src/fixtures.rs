@@ -0,0 +1,338 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method};
+use http_body_util::{BodyExt, Full};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use wasmtime_wasi_http::{
+    bindings::http::types::ErrorCode,
+    body::HyperOutgoingBody,
+    types::{IncomingResponse, OutgoingRequestConfig, default_send_request_handler},
+};
+
+use crate::inspector::{REDACTED, redact};
+
+/// Whether [`HttpFixtures`] capture real traffic or serve it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Requests are sent to the network and every exchange is written to the
+    /// fixture file.
+    Record,
+    /// Requests are answered from the fixture file without touching the
+    /// network.
+    Replay,
+}
+
+/// Recorded HTTP exchanges of an extension, used to run it without the
+/// real site.
+///
+/// In replay mode each recorded exchange answers a single request with the
+/// same method, URL and body, in recording order. Requests left without a
+/// matching exchange fail with an `internal-error`.
+#[derive(Clone)]
+pub struct HttpFixtures {
+    inner: Arc<HttpFixturesInner>,
+}
+
+struct HttpFixturesInner {
+    mode: FixtureMode,
+    path: PathBuf,
+    fixture: Mutex<Fixture>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    exchanges: Vec<FixtureExchange>,
+    /// Whether each exchange already answered a request while replaying.
+    #[serde(skip)]
+    used: Vec<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FixtureExchange {
+    request: FixtureRequest,
+    response: FixtureResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FixtureRequest {
+    method: String,
+    url: String,
+    /// Informative only, with secrets redacted; requests are matched
+    /// without them.
+    headers: Vec<(String, String)>,
+    body: FixtureBody,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FixtureResponse {
+    status: u16,
+    /// With secrets redacted, which are left out when replaying.
+    headers: Vec<(String, String)>,
+    body: FixtureBody,
+}
+
+impl FixtureRequest {
+    fn new(parts: &http::request::Parts, body: &[u8]) -> Self {
+        Self {
+            method: parts.method.to_string(),
+            url: parts.uri.to_string(),
+            headers: redact(&parts.headers),
+            body: FixtureBody::new(body),
+        }
+    }
+}
+
+impl FixtureResponse {
+    fn new(parts: &http::response::Parts, body: &[u8]) -> Self {
+        Self {
+            status: parts.status.as_u16(),
+            headers: redact(&parts.headers),
+            body: FixtureBody::new(body),
+        }
+    }
+}
+
+/// A body stored as text when it is UTF-8, base64-encoded otherwise.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum FixtureBody {
+    Text(String),
+    Base64 { base64: String },
+}
+
+impl FixtureBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_owned()),
+            Err(_) => Self::Base64 {
+                base64: STANDARD.encode(bytes),
+            },
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Bytes> {
+        match self {
+            Self::Text(text) => Ok(Bytes::copy_from_slice(text.as_bytes())),
+            Self::Base64 { base64 } => Ok(STANDARD.decode(base64)?.into()),
+        }
+    }
+}
+
+impl HttpFixtures {
+    /// Records the exchanges to `path`, replacing the file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(FixtureMode::Record, path.into(), Fixture::default())
+    }
+
+    /// Replays the exchanges recorded in `path`.
+    pub async fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let bytes = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("failed to read HTTP fixtures {}", path.display()))?;
+        let mut fixture = serde_json::from_slice::<Fixture>(&bytes)
+            .with_context(|| format!("invalid HTTP fixtures {}", path.display()))?;
+        fixture.used = vec![false; fixture.exchanges.len()];
+
+        Ok(Self::new(FixtureMode::Replay, path, fixture))
+    }
+
+    fn new(mode: FixtureMode, path: PathBuf, fixture: Fixture) -> Self {
+        Self {
+            inner: Arc::new(HttpFixturesInner {
+                mode,
+                path,
+                fixture: Mutex::new(fixture),
+            }),
+        }
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.inner.mode
+    }
+
+    /// Returns the number of recorded exchanges that have not answered a
+    /// request yet while replaying.
+    pub async fn unused(&self) -> usize {
+        let fixture = self.inner.fixture.lock().await;
+        fixture.used.iter().filter(|used| !**used).count()
+    }
+
+    pub(crate) async fn send_request(
+        &self,
+        request: http::Request<HyperOutgoingBody>,
+        config: OutgoingRequestConfig,
+    ) -> Result<IncomingResponse, ErrorCode> {
+        match self.inner.mode {
+            FixtureMode::Record => self.record_request(request, config).await,
+            FixtureMode::Replay => self.replay_request(request, config).await,
+        }
+    }
+
+    async fn record_request(
+        &self,
+        request: http::Request<HyperOutgoingBody>,
+        config: OutgoingRequestConfig,
+    ) -> Result<IncomingResponse, ErrorCode> {
+        let (parts, body) = request.into_parts();
+        let request_body = body.collect().await?.to_bytes();
+        let fixture_request = FixtureRequest::new(&parts, &request_body);
+
+        let request = http::Request::from_parts(parts, full(request_body));
+        let response = default_send_request_handler(request, config).await?;
+        let between_bytes_timeout = response.between_bytes_timeout;
+        let (parts, body) = response.resp.into_parts();
+        let response_body = body.collect().await?.to_bytes();
+
+        let mut fixture = self.inner.fixture.lock().await;
+        fixture.exchanges.push(FixtureExchange {
+            request: fixture_request,
+            response: FixtureResponse::new(&parts, &response_body),
+        });
+        if let Err(err) = self.persist(&fixture).await {
+            tracing::warn!("failed to save HTTP fixtures: {err:#}");
+        }
+
+        Ok(IncomingResponse {
+            resp: http::Response::from_parts(parts, full(response_body)),
+            worker: None,
+            between_bytes_timeout,
+        })
+    }
+
+    async fn replay_request(
+        &self,
+        request: http::Request<HyperOutgoingBody>,
+        config: OutgoingRequestConfig,
+    ) -> Result<IncomingResponse, ErrorCode> {
+        let (parts, body) = request.into_parts();
+        let body = body.collect().await?.to_bytes();
+        let url = parts.uri.to_string();
+
+        let mut fixture = self.inner.fixture.lock().await;
+        let Fixture { exchanges, used } = &mut *fixture;
+        let Some((exchange, used)) = exchanges.iter().zip(used).find(|(exchange, used)| {
+            !**used
+                && exchange.request.url == url
+                && exchange.request.method.parse::<Method>().ok() == Some(parts.method.clone())
+                && exchange
+                    .request
+                    .body
+                    .to_bytes()
+                    .is_ok_and(|bytes| bytes == body)
+        }) else {
+            return Err(ErrorCode::InternalError(Some(format!(
+                "no HTTP fixture matches {} {url}",
+                parts.method
+            ))));
+        };
+        *used = true;
+
+        let response = &exchange.response;
+        let mut resp = http::Response::builder().status(response.status);
+        let headers = resp.headers_mut().expect("the builder has no error yet");
+        *headers = response_headers(&response.headers)?;
+        let body = response
+            .body
+            .to_bytes()
+            .map_err(|err| ErrorCode::InternalError(Some(err.to_string())))?;
+        let resp = resp
+            .body(full(body))
+            .map_err(|err| ErrorCode::InternalError(Some(err.to_string())))?;
+
+        Ok(IncomingResponse {
+            resp,
+            worker: None,
+            between_bytes_timeout: config.between_bytes_timeout,
+        })
+    }
+
+    async fn persist(&self, fixture: &Fixture) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(fixture)?;
+        if let Some(dir) = self.inner.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&self.inner.path, bytes).await?;
+        Ok(())
+    }
+}
+
+fn full(body: Bytes) -> HyperOutgoingBody {
+    Full::new(body).map_err(|never| match never {}).boxed()
+}
+
+fn response_headers(headers: &[(String, String)]) -> Result<HeaderMap, ErrorCode> {
+    headers
+        .iter()
+        .filter(|(_, value)| value != REDACTED)
+        .map(|(name, value)| {
+            match (
+                HeaderName::try_from(name.as_str()),
+                HeaderValue::try_from(value.as_str()),
+            ) {
+                (Ok(name), Ok(value)) => Ok((name, value)),
+                _ => Err(ErrorCode::InternalError(Some(format!(
+                    "invalid header `{name}` in HTTP fixture"
+                )))),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_recorded_headers() {
+        let (request, _) = http::Request::post("https://example.com/login?page=1")
+            .header(http::header::COOKIE, "session=secret")
+            .header(http::header::ACCEPT, "application/json")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let (response, _) = http::Response::builder()
+            .header(http::header::SET_COOKIE, "session=secret; HttpOnly")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let exchange = FixtureExchange {
+            request: FixtureRequest::new(&request, b"user=me"),
+            response: FixtureResponse::new(&response, b"{}"),
+        };
+        let json = serde_json::to_string(&exchange).unwrap();
+
+        assert!(!json.contains("secret"), "{json}");
+        assert!(
+            exchange
+                .response
+                .headers
+                .contains(&("set-cookie".to_owned(), REDACTED.to_owned()))
+        );
+        assert!(
+            exchange
+                .request
+                .headers
+                .contains(&("cookie".to_owned(), REDACTED.to_owned()))
+        );
+        assert_eq!(exchange.request.url, "https://example.com/login?page=1");
+    }
+
+    #[test]
+    fn leaves_redacted_headers_out_when_replaying() {
+        let headers = response_headers(&[
+            ("set-cookie".to_owned(), REDACTED.to_owned()),
+            ("content-type".to_owned(), "application/json".to_owned()),
+        ])
+        .unwrap();
+
+        assert!(!headers.contains_key(http::header::SET_COOKIE));
+        assert_eq!(headers[http::header::CONTENT_TYPE], "application/json");
+    }
+}
@@ -1,14 +1,24 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use nero_wasi_logging::LogSink;
 use semver::Version;
 use wasm_metadata::Payload;
 use wasmtime::{Engine, component::Component};
 
-use crate::WasmExtension;
+use crate::{WasmExtension, inspector::HttpInspector, login::LoginHandler, manifest::Manifest};
+
+/// The services the embedder provides to every extension.
+#[derive(Clone, Default)]
+pub(crate) struct HostHooks {
+    pub(crate) login_handler: Option<Arc<dyn LoginHandler>>,
+    pub(crate) log_sinks: Vec<Arc<dyn LogSink>>,
+    pub(crate) http_inspector: Option<HttpInspector>,
+}
 
 pub struct WasmHost {
     engine: Engine,
+    hooks: HostHooks,
 }
 
 impl Default for WasmHost {
@@ -20,11 +30,32 @@ impl Default for WasmHost {
                 config.wasm_component_model(true);
                 wasmtime::Engine::new(&config).unwrap()
             },
+            hooks: HostHooks::default(),
         }
     }
 }
 
 impl WasmHost {
+    /// Sets the handler used to answer login requests of extensions.
+    pub fn with_login_handler(mut self, handler: impl LoginHandler + 'static) -> Self {
+        self.hooks.login_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Adds a sink receiving the messages logged by extensions, tagged with
+    /// the id of the extension and the operation it was running.
+    pub fn with_log_sink(mut self, sink: Arc<dyn LogSink>) -> Self {
+        self.hooks.log_sinks.push(sink);
+        self
+    }
+
+    /// Sets the inspector recording the HTTP traffic of extensions while it
+    /// is enabled.
+    pub fn with_http_inspector(mut self, inspector: HttpInspector) -> Self {
+        self.hooks.http_inspector = Some(inspector);
+        self
+    }
+
     pub async fn load_extension_async<P: AsRef<Path>>(
         &self,
         path: P,
@@ -32,22 +63,85 @@ impl WasmHost {
         let path = path.as_ref();
 
         let wasm_bytes = std::fs::read(path)?;
-        let version = Self::get_extension_version(&wasm_bytes)?;
-        let component = Component::from_file(&self.engine, path)?;
+        let component = Component::from_binary(&self.engine, &wasm_bytes)?;
+        let version = Self::get_extension_version(&self.engine, &component)?;
+        let manifest = Self::get_manifest(&self.engine, &wasm_bytes, &component)?;
         let metadata = match Payload::from_binary(&wasm_bytes)? {
             Payload::Component { metadata, .. } => metadata,
             Payload::Module(..) => unreachable!(),
         };
+        let id = match (&manifest, &metadata.name, path.file_stem()) {
+            (Some(manifest), ..) => manifest.id.as_str().into(),
+            (None, Some(name), _) => name.as_str().into(),
+            (None, None, Some(stem)) => stem.to_string_lossy().into(),
+            (None, None, None) => "unknown".into(),
+        };
 
-        let extension =
-            WasmExtension::instantiate_async(&self.engine, version, &component, metadata).await?;
+        let extension = WasmExtension::instantiate_async(
+            &self.engine,
+            version,
+            &component,
+            id,
+            metadata,
+            manifest,
+            self.hooks.clone(),
+        )
+        .await?;
 
         Ok(extension)
     }
 
-    // TODO:
-    #[allow(unused_variables)]
-    fn get_extension_version(wasm_bytes: &[u8]) -> Result<Version> {
-        Ok(Version::new(0, 1, 0))
+    /// Checks that `wasm_bytes` is an extension this host can load: a
+    /// component exporting a supported version of the `nero:extension`
+    /// interfaces and importing only what the host provides.
+    ///
+    /// Its manifest, if any, must declare what it imports. Returns the
+    /// version of the interfaces, like [`WasmExtension::version`].
+    pub fn validate_component(&self, wasm_bytes: &[u8]) -> wasmtime::Result<Version> {
+        let component = Component::from_binary(&self.engine, wasm_bytes)?;
+        let version = Self::get_extension_version(&self.engine, &component)?;
+        Self::get_manifest(&self.engine, wasm_bytes, &component)?;
+        WasmExtension::prepare(&self.engine, &version, &component)?;
+        Ok(version)
+    }
+
+    /// Reads the manifest of an extension without loading it, so that the
+    /// capabilities it needs can be reviewed first.
+    pub fn read_manifest(&self, wasm_bytes: &[u8]) -> wasmtime::Result<Option<Manifest>> {
+        let component = Component::from_binary(&self.engine, wasm_bytes)?;
+        Self::get_manifest(&self.engine, wasm_bytes, &component)
+    }
+
+    /// Reads the manifest of a component and checks it against the
+    /// imports of the component.
+    fn get_manifest(
+        engine: &Engine,
+        wasm_bytes: &[u8],
+        component: &Component,
+    ) -> Result<Option<Manifest>> {
+        let Some(manifest) = Manifest::from_wasm(wasm_bytes).context("invalid manifest")? else {
+            return Ok(None);
+        };
+
+        let component_type = component.component_type();
+        manifest
+            .validate(component_type.imports(engine).map(|(name, _)| name))
+            .context("invalid manifest")?;
+        Ok(Some(manifest))
+    }
+
+    /// Reads the WIT version from the `nero:extension/extractor` export.
+    ///
+    /// Pre-release tags (e.g. `-draft`) are dropped so versions compare
+    /// against the `MIN_VER` of each `since_*` module.
+    fn get_extension_version(engine: &Engine, component: &Component) -> Result<Version> {
+        for (name, _) in component.component_type().exports(engine) {
+            if let Some(version) = name.strip_prefix("nero:extension/extractor@") {
+                let version = Version::parse(version)?;
+                return Ok(Version::new(version.major, version.minor, version.patch));
+            }
+        }
+
+        bail!("component does not export nero:extension/extractor")
     }
 }
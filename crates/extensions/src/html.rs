@@ -0,0 +1,273 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Selector};
+
+/// A CSS selector that could not be parsed.
+#[derive(Debug)]
+pub struct InvalidSelector(String);
+
+impl fmt::Display for InvalidSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidSelector {}
+
+fn parse_selector(selector: &str) -> Result<Selector, InvalidSelector> {
+    Selector::parse(selector).map_err(|err| InvalidSelector(format!("`{selector}`: {err}")))
+}
+
+/// A parsed HTML document, backing the `document` resource of the
+/// `nero:extension/html` import.
+#[derive(Clone)]
+pub struct HtmlDocument {
+    html: Arc<Mutex<Html>>,
+}
+
+impl HtmlDocument {
+    pub fn parse(html: &str) -> Self {
+        Self::new(Html::parse_document(html))
+    }
+
+    pub fn parse_fragment(html: &str) -> Self {
+        Self::new(Html::parse_fragment(html))
+    }
+
+    fn new(html: Html) -> Self {
+        Self {
+            html: Arc::new(Mutex::new(html)),
+        }
+    }
+
+    fn element(&self, id: NodeId) -> HtmlElement {
+        HtmlElement {
+            html: self.html.clone(),
+            id,
+        }
+    }
+
+    pub fn root(&self) -> HtmlElement {
+        let id = self.html.lock().unwrap().root_element().id();
+        self.element(id)
+    }
+
+    /// Returns the elements of the document matching `selector`, in
+    /// document order.
+    pub fn select(&self, selector: &str) -> Result<Vec<HtmlElement>, InvalidSelector> {
+        let selector = parse_selector(selector)?;
+        let ids = self
+            .html
+            .lock()
+            .unwrap()
+            .select(&selector)
+            .map(|element| element.id())
+            .collect::<Vec<_>>();
+
+        Ok(ids.into_iter().map(|id| self.element(id)).collect())
+    }
+
+    pub fn select_first(&self, selector: &str) -> Result<Option<HtmlElement>, InvalidSelector> {
+        let selector = parse_selector(selector)?;
+        let id = self
+            .html
+            .lock()
+            .unwrap()
+            .select(&selector)
+            .next()
+            .map(|element| element.id());
+
+        Ok(id.map(|id| self.element(id)))
+    }
+}
+
+/// An element of an [`HtmlDocument`], backing the `element` resource of the
+/// `nero:extension/html` import.
+#[derive(Clone)]
+pub struct HtmlElement {
+    html: Arc<Mutex<Html>>,
+    id: NodeId,
+}
+
+impl HtmlElement {
+    fn with<T>(&self, f: impl FnOnce(ElementRef<'_>) -> T) -> T {
+        let html = self.html.lock().unwrap();
+        let element = html
+            .tree
+            .get(self.id)
+            .and_then(ElementRef::wrap)
+            .expect("element ids always refer to elements of their document");
+        f(element)
+    }
+
+    fn element(&self, id: NodeId) -> HtmlElement {
+        HtmlElement {
+            html: self.html.clone(),
+            id,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.with(|element| element.value().name().to_owned())
+    }
+
+    pub fn attr(&self, name: &str) -> Option<String> {
+        self.with(|element| element.attr(name).map(str::to_owned))
+    }
+
+    pub fn attrs(&self) -> Vec<(String, String)> {
+        self.with(|element| {
+            element
+                .value()
+                .attrs()
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .collect()
+        })
+    }
+
+    /// Returns the text of the element and its descendants.
+    pub fn text(&self) -> String {
+        self.with(|element| element.text().collect())
+    }
+
+    pub fn inner_html(&self) -> String {
+        self.with(|element| element.inner_html())
+    }
+
+    pub fn outer_html(&self) -> String {
+        self.with(|element| element.html())
+    }
+
+    pub fn parent(&self) -> Option<HtmlElement> {
+        let id = self.with(|element| {
+            element
+                .parent()
+                .and_then(ElementRef::wrap)
+                .map(|parent| parent.id())
+        });
+        id.map(|id| self.element(id))
+    }
+
+    pub fn children(&self) -> Vec<HtmlElement> {
+        let ids = self.with(|element| {
+            element
+                .child_elements()
+                .map(|child| child.id())
+                .collect::<Vec<_>>()
+        });
+        ids.into_iter().map(|id| self.element(id)).collect()
+    }
+
+    /// Returns the descendants of the element matching `selector`, in
+    /// document order.
+    pub fn select(&self, selector: &str) -> Result<Vec<HtmlElement>, InvalidSelector> {
+        let selector = parse_selector(selector)?;
+        let ids = self.with(|element| {
+            element
+                .select(&selector)
+                .map(|element| element.id())
+                .collect::<Vec<_>>()
+        });
+        Ok(ids.into_iter().map(|id| self.element(id)).collect())
+    }
+
+    pub fn select_first(&self, selector: &str) -> Result<Option<HtmlElement>, InvalidSelector> {
+        let selector = parse_selector(selector)?;
+        let id = self.with(|element| element.select(&selector).next().map(|element| element.id()));
+        Ok(id.map(|id| self.element(id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = r#"<!DOCTYPE html>
+<html>
+  <body>
+    <ul id="series">
+      <li class="item" data-id="1"><a href="/series/1">First <b>show</b></a></li>
+      <li class="item" data-id="2"><a href="/series/2">Second show</a></li>
+    </ul>
+  </body>
+</html>"#;
+
+    #[test]
+    fn selects_in_document_order() {
+        let document = HtmlDocument::parse(LISTING);
+
+        let items = document.select("li.item").unwrap();
+        let ids = items
+            .iter()
+            .map(|item| item.attr("data-id").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["1", "2"]);
+        assert!(document.select("li.missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn selects_first() {
+        let document = HtmlDocument::parse(LISTING);
+
+        let link = document.select_first("li a").unwrap().unwrap();
+        assert_eq!(link.attr("href").as_deref(), Some("/series/1"));
+        assert!(document.select_first("table").unwrap().is_none());
+    }
+
+    #[test]
+    fn selects_within_elements() {
+        let document = HtmlDocument::parse(LISTING);
+        let second = document.select("li").unwrap().remove(1);
+
+        let links = second.select("a").unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].attr("href").as_deref(), Some("/series/2"));
+        assert!(second.select_first("b").unwrap().is_none());
+    }
+
+    #[test]
+    fn navigates_parents_and_children() {
+        let document = HtmlDocument::parse(LISTING);
+        let list = document.select_first("#series").unwrap().unwrap();
+
+        let children = list.children();
+        assert_eq!(children.len(), 2);
+        assert!(children.iter().all(|child| child.name() == "li"));
+
+        let parent = children[0].parent().unwrap();
+        assert_eq!(parent.attr("id").as_deref(), Some("series"));
+        assert_eq!(document.root().name(), "html");
+        assert!(document.root().parent().is_none());
+    }
+
+    #[test]
+    fn reads_text_and_markup() {
+        let document = HtmlDocument::parse_fragment(r#"<p class="note">Hello <em>there</em></p>"#);
+        let paragraph = document.select_first("p").unwrap().unwrap();
+
+        assert_eq!(paragraph.text(), "Hello there");
+        assert_eq!(paragraph.inner_html(), "Hello <em>there</em>");
+        assert_eq!(
+            paragraph.outer_html(),
+            r#"<p class="note">Hello <em>there</em></p>"#
+        );
+        assert_eq!(paragraph.attrs(), [("class".to_owned(), "note".to_owned())]);
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        let document = HtmlDocument::parse(LISTING);
+
+        let err = document
+            .select("li[")
+            .err()
+            .expect("the selector is invalid");
+        assert!(err.to_string().starts_with("`li[`"), "{err}");
+        assert!(document.select_first("::").is_err());
+        assert!(document.root().select(">").is_err());
+    }
+}
@@ -0,0 +1,587 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use http::{HeaderMap, header};
+use http_body_util::{BodyExt, combinators::BoxBody};
+use serde_json::{Value, json};
+use wasmtime_wasi_http::bindings::http::types::ErrorCode;
+
+use crate::Call;
+
+/// Headers whose values are replaced by [`REDACTED`] in recorded exchanges.
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+    "x-csrf-token",
+];
+/// Query parameters whose values are replaced by [`REDACTED`] in recorded
+/// URLs, compared case-insensitively.
+const SECRET_QUERY_PARAMS: &[&str] = &[
+    "access_token",
+    "api_key",
+    "apikey",
+    "auth",
+    "key",
+    "password",
+    "secret",
+    "sig",
+    "signature",
+    "token",
+];
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// A captured request or response body, cut after a size limit.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedBody {
+    /// The first bytes of the body.
+    pub data: Vec<u8>,
+    /// The size of the whole body seen so far.
+    pub size: u64,
+}
+
+impl RecordedBody {
+    pub fn is_truncated(&self) -> bool {
+        self.size > self.data.len() as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: RecordedBody,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: RecordedBody,
+}
+
+/// An outgoing request of an extension and its outcome.
+#[derive(Debug, Clone)]
+pub struct HttpExchange {
+    pub extension: Arc<str>,
+    pub operation: &'static str,
+    /// Identifies the call into the extension that sent the request.
+    pub call_id: u64,
+    pub started: SystemTime,
+    /// Time until the response headers were received.
+    pub duration: Option<Duration>,
+    pub request: RecordedRequest,
+    pub response: Option<RecordedResponse>,
+    pub error: Option<String>,
+}
+
+type SharedBody = Arc<Mutex<RecordedBody>>;
+
+struct Entry {
+    exchange: Mutex<HttpExchange>,
+    request_body: SharedBody,
+    response_body: SharedBody,
+}
+
+impl Entry {
+    fn snapshot(&self) -> HttpExchange {
+        let mut exchange = self.exchange.lock().unwrap().clone();
+        exchange.request.body = self.request_body.lock().unwrap().clone();
+        if let Some(response) = &mut exchange.response {
+            response.body = self.response_body.lock().unwrap().clone();
+        }
+        exchange
+    }
+}
+
+struct Inner {
+    enabled: AtomicBool,
+    max_body_len: usize,
+    capacity: usize,
+    entries: Mutex<VecDeque<Arc<Entry>>>,
+}
+
+/// Records the HTTP traffic of extensions while enabled.
+///
+/// Secret headers and query parameters are redacted and bodies are cut
+/// after a size limit; secrets elsewhere, e.g. in bodies, are kept. Only
+/// the latest exchanges are kept.
+#[derive(Clone)]
+pub struct HttpInspector {
+    inner: Arc<Inner>,
+}
+
+impl Default for HttpInspector {
+    fn default() -> Self {
+        Self::new(500, 64 * 1024)
+    }
+}
+
+impl HttpInspector {
+    /// Creates a disabled inspector keeping up to `capacity` exchanges, with
+    /// bodies cut after `max_body_len` bytes.
+    pub fn new(capacity: usize, max_body_len: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                enabled: AtomicBool::new(false),
+                max_body_len,
+                capacity,
+                entries: Mutex::default(),
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns the recorded exchanges, oldest first.
+    pub fn exchanges(&self) -> Vec<HttpExchange> {
+        self.inner
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.snapshot())
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.inner.entries.lock().unwrap().clear();
+    }
+
+    /// Records `request`, wrapping its body to capture it as it is sent.
+    pub(crate) fn record_request(
+        &self,
+        call: &Call,
+        request: &mut http::Request<BoxBody<Bytes, ErrorCode>>,
+    ) -> Recording {
+        let request_body = SharedBody::default();
+        let body = std::mem::take(request.body_mut());
+        *request.body_mut() = capture(body, request_body.clone(), self.inner.max_body_len);
+
+        let entry = Arc::new(Entry {
+            exchange: Mutex::new(HttpExchange {
+                extension: call.extension.clone(),
+                operation: call.operation,
+                call_id: call.id,
+                started: SystemTime::now(),
+                duration: None,
+                request: RecordedRequest {
+                    method: request.method().to_string(),
+                    url: redact_url(request.uri()),
+                    headers: redact(request.headers()),
+                    body: RecordedBody::default(),
+                },
+                response: None,
+                error: None,
+            }),
+            request_body,
+            response_body: SharedBody::default(),
+        });
+
+        let mut entries = self.inner.entries.lock().unwrap();
+        if entries.len() >= self.inner.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+
+        Recording {
+            entry,
+            start: Instant::now(),
+            max_body_len: self.inner.max_body_len,
+        }
+    }
+
+    /// Exports the recorded exchanges as an HTTP Archive (HAR 1.2).
+    pub fn to_har(&self) -> Value {
+        let entries = self.exchanges().iter().map(har_entry).collect::<Vec<_>>();
+
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": entries,
+            }
+        })
+    }
+}
+
+/// An exchange being recorded, completed once the response arrives.
+pub(crate) struct Recording {
+    entry: Arc<Entry>,
+    start: Instant,
+    max_body_len: usize,
+}
+
+impl Recording {
+    /// Records the response head, wrapping its body to capture it as the
+    /// extension reads it.
+    pub(crate) fn response(self, response: &mut http::Response<BoxBody<Bytes, ErrorCode>>) {
+        let body = std::mem::take(response.body_mut());
+        *response.body_mut() = capture(body, self.entry.response_body.clone(), self.max_body_len);
+
+        let mut exchange = self.entry.exchange.lock().unwrap();
+        exchange.duration = Some(self.start.elapsed());
+        exchange.response = Some(RecordedResponse {
+            status: response.status().as_u16(),
+            headers: redact(response.headers()),
+            body: RecordedBody::default(),
+        });
+    }
+
+    pub(crate) fn error(self, error: &ErrorCode) {
+        let mut exchange = self.entry.exchange.lock().unwrap();
+        exchange.duration = Some(self.start.elapsed());
+        exchange.error = Some(error.to_string());
+    }
+}
+
+fn capture(
+    body: BoxBody<Bytes, ErrorCode>,
+    recorded: SharedBody,
+    max_len: usize,
+) -> BoxBody<Bytes, ErrorCode> {
+    body.map_frame(move |frame| {
+        if let Some(data) = frame.data_ref() {
+            let mut recorded = recorded.lock().unwrap();
+            let len = data.len().min(max_len.saturating_sub(recorded.data.len()));
+            recorded.data.extend_from_slice(&data[..len]);
+            recorded.size += data.len() as u64;
+        }
+        frame
+    })
+    .boxed()
+}
+
+pub(crate) fn redact(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Returns `uri` with the values of secret query parameters redacted,
+/// keeping the rest of it as sent.
+fn redact_url(uri: &http::Uri) -> String {
+    let url = uri.to_string();
+    let Some((base, query)) = uri.query().and_then(|query| {
+        let base = url.strip_suffix(query)?.strip_suffix('?')?;
+        Some((base, query))
+    }) else {
+        return url;
+    };
+
+    let query = query
+        .split('&')
+        .map(|pair| {
+            let name = pair.split_once('=').map_or(pair, |(name, _)| name);
+            let decoded = url::form_urlencoded::parse(name.as_bytes())
+                .next()
+                .map(|(name, _)| name.to_ascii_lowercase())
+                .unwrap_or_default();
+            if SECRET_QUERY_PARAMS.contains(&decoded.as_str()) {
+                format!("{name}={REDACTED}")
+            } else {
+                pair.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{base}?{query}")
+}
+
+fn har_headers(headers: &[(String, String)]) -> Value {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+fn mime_type(headers: &[(String, String)]) -> &str {
+    headers
+        .iter()
+        .find(|(name, _)| name == header::CONTENT_TYPE.as_str())
+        .map_or("", |(_, value)| value)
+}
+
+/// Returns the body as HAR `text`, base64-encoded if it is not UTF-8.
+fn har_text(body: &RecordedBody) -> (String, Option<&'static str>) {
+    use base64::Engine;
+
+    match std::str::from_utf8(&body.data) {
+        Ok(text) => (text.to_owned(), None),
+        Err(_) => (
+            base64::engine::general_purpose::STANDARD.encode(&body.data),
+            Some("base64"),
+        ),
+    }
+}
+
+fn har_entry(exchange: &HttpExchange) -> Value {
+    let started: DateTime<Utc> = exchange.started.into();
+    let time = exchange
+        .duration
+        .map_or(-1.0, |duration| duration.as_secs_f64() * 1000.0);
+    let request = &exchange.request;
+    let query = url::Url::parse(&request.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut har_request = json!({
+        "method": request.method,
+        "url": request.url,
+        "httpVersion": "HTTP/1.1",
+        "cookies": [],
+        "headers": har_headers(&request.headers),
+        "queryString": query,
+        "headersSize": -1,
+        "bodySize": request.body.size,
+    });
+    if request.body.size > 0 {
+        let (text, _) = har_text(&request.body);
+        har_request["postData"] = json!({
+            "mimeType": mime_type(&request.headers),
+            "text": text,
+        });
+    }
+
+    let har_response = match &exchange.response {
+        Some(response) => {
+            let (text, encoding) = har_text(&response.body);
+            let mut content = json!({
+                "size": response.body.size,
+                "mimeType": mime_type(&response.headers),
+                "text": text,
+            });
+            if let Some(encoding) = encoding {
+                content["encoding"] = json!(encoding);
+            }
+            if response.body.is_truncated() {
+                content["comment"] = json!("truncated");
+            }
+            json!({
+                "status": response.status,
+                "statusText": http::StatusCode::from_u16(response.status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or_default(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": har_headers(&response.headers),
+                "content": content,
+                "redirectURL": "",
+                "headersSize": -1,
+                "bodySize": response.body.size,
+            })
+        }
+        // HAR has no notion of failed requests; status 0 is what browsers
+        // export for them.
+        None => json!({
+            "status": 0,
+            "statusText": exchange.error.clone().unwrap_or_default(),
+            "httpVersion": "",
+            "cookies": [],
+            "headers": [],
+            "content": { "size": 0, "mimeType": "" },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": -1,
+        }),
+    };
+
+    json!({
+        "startedDateTime": started.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "time": time,
+        "request": har_request,
+        "response": har_response,
+        "cache": {},
+        "timings": { "send": 0, "wait": time, "receive": 0 },
+        "comment": format!("{} {} #{}", exchange.extension, exchange.operation, exchange.call_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::Full;
+
+    use super::*;
+
+    fn redacted_url(url: &str) -> String {
+        redact_url(&url.parse().unwrap())
+    }
+
+    fn body(data: &'static [u8]) -> BoxBody<Bytes, ErrorCode> {
+        Full::new(Bytes::from_static(data))
+            .map_err(|never| match never {})
+            .boxed()
+    }
+
+    fn call() -> Call {
+        Call {
+            extension: "test".into(),
+            operation: "search",
+            id: 1,
+        }
+    }
+
+    /// Records a request to `url` and sends its body.
+    async fn send(inspector: &HttpInspector, url: &str, data: &'static [u8]) -> Recording {
+        let mut request = http::Request::post(url).body(body(data)).unwrap();
+        let recording = inspector.record_request(&call(), &mut request);
+        request.into_body().collect().await.unwrap();
+        recording
+    }
+
+    #[test]
+    fn redacts_secret_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        headers.insert(header::SET_COOKIE, "session=secret".parse().unwrap());
+        headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+
+        let headers = redact(&headers);
+        assert!(headers.contains(&("authorization".to_owned(), REDACTED.to_owned())));
+        assert!(headers.contains(&("set-cookie".to_owned(), REDACTED.to_owned())));
+        assert!(headers.contains(&("accept".to_owned(), "text/html".to_owned())));
+    }
+
+    #[test]
+    fn redacts_secret_query_parameters() {
+        assert_eq!(
+            redacted_url("https://example.com/search?q=naruto&token=abc&API_KEY=def&page=2"),
+            "https://example.com/search?q=naruto&token=[redacted]&API_KEY=[redacted]&page=2"
+        );
+        assert_eq!(
+            redacted_url("https://example.com/video?access%5Ftoken=abc&sig"),
+            "https://example.com/video?access%5Ftoken=[redacted]&sig=[redacted]"
+        );
+    }
+
+    #[test]
+    fn keeps_urls_without_secrets() {
+        for url in [
+            "https://example.com/",
+            "https://example.com/search?q=token&keyword=a",
+            "https://example.com/search?",
+        ] {
+            assert_eq!(redacted_url(url), url);
+        }
+    }
+
+    #[tokio::test]
+    async fn truncates_bodies_but_counts_their_full_size() {
+        let inspector = HttpInspector::new(10, 5);
+        let recording = send(&inspector, "https://example.com/", b"hello world").await;
+
+        let mut response = http::Response::new(body(b"abc"));
+        recording.response(&mut response);
+        response.into_body().collect().await.unwrap();
+
+        let exchange = inspector.exchanges().remove(0);
+        assert_eq!(exchange.request.body.data, b"hello");
+        assert_eq!(exchange.request.body.size, 11);
+        assert!(exchange.request.body.is_truncated());
+
+        let response = exchange.response.unwrap();
+        assert_eq!(response.body.data, b"abc");
+        assert_eq!(response.body.size, 3);
+        assert!(!response.body.is_truncated());
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_exchanges() {
+        let inspector = HttpInspector::new(2, 16);
+        for page in 1..=3 {
+            send(&inspector, &format!("https://example.com/{page}"), b"").await;
+        }
+
+        let urls = inspector
+            .exchanges()
+            .into_iter()
+            .map(|exchange| exchange.request.url)
+            .collect::<Vec<_>>();
+        assert_eq!(urls, ["https://example.com/2", "https://example.com/3"]);
+    }
+
+    #[tokio::test]
+    async fn exports_har() {
+        let inspector = HttpInspector::new(10, 16);
+
+        let recording = send(&inspector, "https://example.com/image?size=2", b"").await;
+        let mut response = http::Response::builder()
+            .header(header::CONTENT_TYPE, "image/png")
+            .body(body(&[0xff, 0xfe]))
+            .unwrap();
+        recording.response(&mut response);
+        response.into_body().collect().await.unwrap();
+
+        send(&inspector, "https://example.org/", b"q=a")
+            .await
+            .error(&ErrorCode::ConnectionRefused);
+
+        let har = inspector.to_har();
+        assert_eq!(har["log"]["version"], "1.2");
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let image = &entries[0];
+        assert_eq!(image["request"]["method"], "POST");
+        assert_eq!(
+            image["request"]["queryString"],
+            json!([{ "name": "size", "value": "2" }])
+        );
+        assert_eq!(image["response"]["status"], 200);
+        assert_eq!(image["response"]["statusText"], "OK");
+        assert_eq!(
+            image["response"]["content"],
+            json!({
+                "size": 2,
+                "mimeType": "image/png",
+                "text": "//4=",
+                "encoding": "base64",
+            })
+        );
+        assert_eq!(image["comment"], "test search #1");
+
+        let failed = &entries[1];
+        assert_eq!(failed["request"]["postData"]["text"], "q=a");
+        assert_eq!(failed["request"]["bodySize"], 3);
+        assert_eq!(failed["response"]["status"], 0);
+        assert_eq!(
+            failed["response"]["statusText"],
+            ErrorCode::ConnectionRefused.to_string()
+        );
+        assert_eq!(failed["response"]["bodySize"], -1);
+    }
+}
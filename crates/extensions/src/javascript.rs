@@ -0,0 +1,226 @@
+use std::{
+    fmt,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use regex::{Captures, Regex};
+use rquickjs::{
+    CatchResultExt, CaughtError, Context, Runtime, context::EvalOptions, convert::Coerced,
+};
+
+/// Longest snippet accepted for evaluation, in bytes.
+const MAX_SOURCE_LEN: usize = 1024 * 1024;
+/// Longest result returned to the extension, in bytes.
+const MAX_RESULT_LEN: usize = 4 * 1024 * 1024;
+/// Memory the engine may allocate for a single evaluation.
+const MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+const MAX_STACK_SIZE: usize = 512 * 1024;
+/// Time a single evaluation may run for.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The reasons an evaluation can fail.
+#[derive(Debug)]
+pub enum JsError {
+    /// The snippet is not valid JavaScript.
+    Syntax(String),
+    /// The snippet threw an exception.
+    Runtime(String),
+    /// The snippet ran out of time, memory or stack, or its source or
+    /// result was too long.
+    LimitExceeded,
+}
+
+impl fmt::Display for JsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsError::Syntax(err) => write!(f, "syntax error: {err}"),
+            JsError::Runtime(err) => write!(f, "runtime error: {err}"),
+            JsError::LimitExceeded => f.write_str("limit exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for JsError {}
+
+/// Evaluates `code` and returns its completion value converted to a string.
+///
+/// Each evaluation runs on a fresh engine exposing only the ECMAScript
+/// built-ins, so snippets have no access to the network, the file system or
+/// the host. Run time, memory, stack, and source and result sizes are
+/// bounded.
+pub async fn eval(code: String) -> Result<String, JsError> {
+    if code.len() > MAX_SOURCE_LEN {
+        return Err(JsError::LimitExceeded);
+    }
+
+    tokio::task::spawn_blocking(move || eval_blocking(code))
+        .await
+        .map_err(|err| JsError::Runtime(err.to_string()))?
+}
+
+fn eval_blocking(code: String) -> Result<String, JsError> {
+    let runtime = Runtime::new().map_err(|err| JsError::Runtime(err.to_string()))?;
+    runtime.set_memory_limit(MEMORY_LIMIT);
+    runtime.set_max_stack_size(MAX_STACK_SIZE);
+    let deadline = Instant::now() + TIMEOUT;
+    runtime.set_interrupt_handler(Some(Box::new(move || Instant::now() > deadline)));
+
+    let context = Context::full(&runtime).map_err(|err| JsError::Runtime(err.to_string()))?;
+    let result = context.with(|ctx| {
+        // Sloppy mode, as scripts scraped from pages expect.
+        let mut options = EvalOptions::default();
+        options.strict = false;
+        ctx.eval_with_options::<Coerced<String>, _>(code, options)
+            .catch(&ctx)
+            .map(|Coerced(result)| result)
+            .map_err(|err| classify(&err))
+    })?;
+
+    if result.len() > MAX_RESULT_LEN {
+        return Err(JsError::LimitExceeded);
+    }
+    Ok(result)
+}
+
+fn classify(err: &CaughtError<'_>) -> JsError {
+    let exception = match err {
+        CaughtError::Exception(exception) => exception,
+        // QuickJS throws `null` when it runs out of memory before it can
+        // allocate an error.
+        CaughtError::Value(value) if value.is_null() => return JsError::LimitExceeded,
+        err => return JsError::Runtime(err.to_string()),
+    };
+
+    let name = exception.get::<_, String>("name").unwrap_or_default();
+    let message = exception.message().unwrap_or_default();
+    match (name.as_str(), message.as_str()) {
+        ("SyntaxError", _) => JsError::Syntax(message),
+        ("InternalError", "interrupted" | "out of memory" | "stack overflow")
+        | ("RangeError", "Maximum call stack size exceeded") => JsError::LimitExceeded,
+        _ => JsError::Runtime(format!("{name}: {message}")),
+    }
+}
+
+static PACKER_ARGS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)\}\s*\(\s*'(.*)'\s*,\s*(\d+|\[\])\s*,\s*(\d+)\s*,\s*'(.*?)'\.split\(\s*'\|'\s*\)"#,
+    )
+    .unwrap()
+});
+static WORD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\w+\b").unwrap());
+
+const BASE62_DIGITS: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Unpacks scripts packed with Dean Edwards' p.a.c.k.e.r
+/// (`eval(function(p,a,c,k,e,d){...}(...))`) without running them.
+///
+/// Returns `None` if `packed` is not in a format this unpacker supports.
+pub fn unpack_packer(packed: &str) -> Option<String> {
+    let captures = PACKER_ARGS.captures(packed)?;
+    let payload = captures[1].replace("\\\\", "\\").replace("\\'", "'");
+    let radix = match &captures[2] {
+        "[]" => 62,
+        radix => radix.parse::<u32>().ok()?,
+    };
+    let count = captures[3].parse::<usize>().ok()?;
+    let keywords = captures[4].split('|').collect::<Vec<_>>();
+
+    if !(2..=62).contains(&radix) || keywords.len() != count {
+        return None;
+    }
+
+    let unpacked = WORD.replace_all(&payload, |word: &Captures| {
+        let word = &word[0];
+        decode_base(word, radix)
+            .and_then(|index| keywords.get(index))
+            .filter(|keyword| !keyword.is_empty())
+            .map_or_else(|| word.to_owned(), |keyword| (*keyword).to_owned())
+    });
+
+    Some(unpacked.into_owned())
+}
+
+fn decode_base(word: &str, radix: u32) -> Option<usize> {
+    word.chars().try_fold(0usize, |acc, c| {
+        let digit = BASE62_DIGITS.find(c)? as u32;
+        if digit >= radix {
+            return None;
+        }
+        acc.checked_mul(radix as usize)?.checked_add(digit as usize)
+    })
+}
+
+/// Unpacks a p.a.c.k.e.r script, falling back to evaluating it with `eval`
+/// captured when the native unpacker does not support its format.
+pub async fn unpack(packed: String) -> Result<String, JsError> {
+    if let Some(unpacked) = unpack_packer(&packed) {
+        return Ok(unpacked);
+    }
+
+    let code = format!(
+        "var __unpacked = ''; eval = function (s) {{ __unpacked = s; }};\n{packed}\n;__unpacked"
+    );
+    eval(code).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `alert('hello world');var hello=1;` packed with base 10 and 62.
+    const PACKED_10: &str = r"eval(function(p,a,c,k,e,d){e=function(c){return c};if(!''.replace(/^/,String)){while(c--)d[c]=k[c]||c;k=[function(e){return d[e]}];e=function(){return'\\w+'};c=1};while(c--)if(k[c])p=p.replace(new RegExp('\\b'+e(c)+'\\b','g'),k[c]);return p}('0(\'1 2\');3 1=4;',10,5,'alert|hello|world|var|'.split('|'),0,{}))";
+    const PACKED_62: &str = r"eval(function(p,a,c,k,e,d){e=function(c){return(c<a?'':e(parseInt(c/a)))+((c=c%a)>35?String.fromCharCode(c+29):c.toString(36))};if(!''.replace(/^/,String)){while(c--)d[e(c)]=k[c]||e(c);k=[function(e){return d[e]}];e=function(){return'\\w+'};c=1};while(c--)if(k[c])p=p.replace(new RegExp('\\b'+e(c)+'\\b','g'),k[c]);return p}('0(\'1 2\');3 1=4;',62,5,'alert|hello|world|var|'.split('|'),0,{}))";
+    const UNPACKED: &str = "alert('hello world');var hello=4;";
+
+    #[test]
+    fn unpacks_known_samples() {
+        assert_eq!(unpack_packer(PACKED_10).as_deref(), Some(UNPACKED));
+        assert_eq!(unpack_packer(PACKED_62).as_deref(), Some(UNPACKED));
+    }
+
+    #[test]
+    fn unpacks_like_the_packer_itself() {
+        // The packed scripts unpack themselves when run, which is what
+        // `unpack` falls back to.
+        let code = format!(
+            "var __unpacked = ''; eval = function (s) {{ __unpacked = s; }};\n{PACKED_62}\n;__unpacked"
+        );
+        assert_eq!(eval_blocking(code).unwrap(), UNPACKED);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for packed in [
+            "",
+            "alert('hello')",
+            // Fewer keywords than declared.
+            r"}('0 1',10,3,'a|b'.split('|'),0,{}))",
+            // Unsupported radix.
+            r"}('0 1',99,2,'a|b'.split('|'),0,{}))",
+            // Count too large to parse.
+            r"}('0 1',10,99999999999999999999999,'a|b'.split('|'),0,{}))",
+        ] {
+            assert_eq!(unpack_packer(packed), None, "{packed}");
+        }
+    }
+
+    #[test]
+    fn ignores_words_out_of_range() {
+        // Words too long to decode, or past the keywords, are kept as is.
+        let packed = r"}('0 zzzzzzzzzzzzzzzzzzzz 9',36,1,'a'.split('|'),0,{}))";
+        assert_eq!(
+            unpack_packer(packed).as_deref(),
+            Some("a zzzzzzzzzzzzzzzzzzzz 9")
+        );
+    }
+
+    #[test]
+    fn fails_to_unpack_malformed_input() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(unpack(r"eval(function(p,a,c,k,e,d){}('0 1',".to_owned()));
+        assert!(matches!(result, Err(JsError::Syntax(_))), "{result:?}");
+    }
+}
@@ -1,23 +1,84 @@
+pub mod cookies;
+pub mod crypto;
 mod extensions;
+pub mod filters;
+pub mod fixtures;
 pub mod host;
+pub mod html;
+pub mod inspector;
+pub mod javascript;
+pub mod login;
+pub mod manifest;
+pub mod repository;
+pub mod settings;
+pub mod signing;
 pub mod types;
 
-use anyhow::{Result, anyhow};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, RwLock as StdRwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use anyhow::{Result, anyhow, bail};
+use http::header;
+use nero_wasi_logging::{LogFilter, RateLimiter, WasiLoggingCtx, WasiLoggingView};
 use semver::Version;
+use tokio::sync::{OnceCell, RwLock};
 use wasm_metadata::Metadata;
 use wasmtime::{Engine, Store, component::Component};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxView, WasiView};
-use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
+use wasmtime_wasi_http::{
+    HttpResult, WasiHttpCtx, WasiHttpView,
+    bindings::http::types::ErrorCode,
+    body::HyperOutgoingBody,
+    types::{HostFutureIncomingResponse, OutgoingRequestConfig, default_send_request_handler},
+};
 
 use crate::{
-    extensions::{AsyncTryIntoWithStore, Extension, ExtensionPre, since_v0_1_0_draft},
-    types::{EpisodesPage, FilterCategory, SearchFilter, Series, SeriesPage, Video},
+    cookies::CookieJar,
+    extensions::{
+        AsyncTryIntoWithStore, Configurable, ConfigurablePre, Extension, ExtensionPre, Feeds,
+        FeedsPre, since_v0_1_0_draft, since_v0_2_0_draft,
+    },
+    filters::validate_search_filters,
+    fixtures::HttpFixtures,
+    host::HostHooks,
+    inspector::HttpInspector,
+    login::LoginHandler,
+    manifest::Manifest,
+    settings::{resolve_setting_values, validate_setting_value},
+    types::{
+        EpisodesPage, Feed, FeedPage, FilterCategory, SearchFilter, Season, Series, SeriesPage,
+        Setting, SettingValue, Video,
+    },
 };
 
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a call into an extension.
+#[derive(Clone, Default)]
+pub(crate) struct Call {
+    pub(crate) extension: Arc<str>,
+    /// The operation the host is running, e.g. `search`.
+    pub(crate) operation: &'static str,
+    pub(crate) id: u64,
+}
+
 struct WasmState {
     table: ResourceTable,
     ctx: WasiCtx,
     http_ctx: WasiHttpCtx,
+    settings: HashMap<String, SettingValue>,
+    cookie_jar: CookieJar,
+    login_handler: Option<Arc<dyn LoginHandler>>,
+    logging: WasiLoggingCtx,
+    http_inspector: Option<HttpInspector>,
+    http_fixtures: Option<HttpFixtures>,
+    manifest: Option<Arc<Manifest>>,
+    call: Call,
 }
 
 impl WasiView for WasmState {
@@ -29,6 +90,12 @@ impl WasiView for WasmState {
     }
 }
 
+impl WasiLoggingView for WasmState {
+    fn logging(&mut self) -> &mut WasiLoggingCtx {
+        &mut self.logging
+    }
+}
+
 impl WasiHttpView for WasmState {
     fn ctx(&mut self) -> &mut WasiHttpCtx {
         &mut self.http_ctx
@@ -37,6 +104,64 @@ impl WasiHttpView for WasmState {
     fn table(&mut self) -> &mut ResourceTable {
         &mut self.table
     }
+
+    /// Sends the request with the cookies of the extension, storing the
+    /// ones set by the response.
+    ///
+    /// Requests that already carry a `Cookie` header are left untouched.
+    /// With HTTP fixtures set, requests are recorded to or answered from
+    /// them instead.
+    fn send_request(
+        &mut self,
+        mut request: http::Request<HyperOutgoingBody>,
+        config: OutgoingRequestConfig,
+    ) -> HttpResult<HostFutureIncomingResponse> {
+        let uri = request.uri().clone();
+        if let Some(manifest) = &self.manifest
+            && !uri.host().is_some_and(|host| manifest.allows_host(host))
+        {
+            tracing::warn!(
+                "{} sent a request to `{uri}`, which is not a declared network host",
+                self.call.extension
+            );
+            return Ok(HostFutureIncomingResponse::ready(Ok(Err(
+                ErrorCode::HttpRequestDenied,
+            ))));
+        }
+        if !request.headers().contains_key(header::COOKIE)
+            && let Some(cookies) = self.cookie_jar.cookie_header(&uri)
+        {
+            request.headers_mut().insert(header::COOKIE, cookies);
+        }
+
+        let recording = self
+            .http_inspector
+            .as_ref()
+            .filter(|inspector| inspector.is_enabled())
+            .map(|inspector| inspector.record_request(&self.call, &mut request));
+
+        let cookie_jar = self.cookie_jar.clone();
+        let fixtures = self.http_fixtures.clone();
+        let handle = wasmtime_wasi::runtime::spawn(async move {
+            let mut response = match fixtures {
+                Some(fixtures) => fixtures.send_request(request, config).await,
+                None => default_send_request_handler(request, config).await,
+            };
+            if let Ok(response) = &response {
+                cookie_jar
+                    .store_response_cookies(&uri, response.resp.headers())
+                    .await;
+            }
+            match (&mut response, recording) {
+                (Ok(response), Some(recording)) => recording.response(&mut response.resp),
+                (Err(err), Some(recording)) => recording.error(err),
+                (_, None) => {}
+            }
+            Ok(response)
+        });
+
+        Ok(HostFutureIncomingResponse::pending(handle))
+    }
 }
 
 impl Default for WasmState {
@@ -45,45 +170,278 @@ impl Default for WasmState {
             table: ResourceTable::new(),
             ctx: WasiCtx::builder().build(),
             http_ctx: WasiHttpCtx::new(),
+            settings: HashMap::new(),
+            cookie_jar: CookieJar::default(),
+            login_handler: None,
+            logging: WasiLoggingCtx::default(),
+            http_inspector: None,
+            http_fixtures: None,
+            manifest: None,
+            call: Call::default(),
         }
     }
 }
 
+/// The bound exports of a component, before it is instantiated.
+pub(crate) struct Prepared {
+    extension: ExtensionPre,
+    feeds: Option<FeedsPre>,
+    configurable: Option<ConfigurablePre>,
+}
+
 pub struct WasmExtension {
+    id: Arc<str>,
+    version: Version,
     extension_pre: ExtensionPre,
+    feeds_pre: Option<FeedsPre>,
     metadata: Metadata,
+    configurable_pre: Option<ConfigurablePre>,
+    filter_categories: OnceCell<Vec<FilterCategory>>,
+    settings: OnceCell<Vec<Setting>>,
+    setting_values: RwLock<HashMap<String, SettingValue>>,
+    cookie_jar: CookieJar,
+    http_fixtures: Option<HttpFixtures>,
+    manifest: Option<Arc<Manifest>>,
+    hooks: HostHooks,
+    log_filter: StdRwLock<Arc<LogFilter>>,
+    log_rate_limiter: Arc<RateLimiter>,
 }
 
 impl WasmExtension {
-    async fn instantiate_async(
+    /// Binds the host imports and the exports of `component` for
+    /// `version`, failing if it imports an interface the host does not
+    /// provide or lacks a required export.
+    pub(crate) fn prepare(
         engine: &Engine,
-        version: Version,
+        version: &Version,
         component: &Component,
-        metadata: Metadata,
-    ) -> Result<Self> {
-        let extension_pre = match version {
-            v if v >= since_v0_1_0_draft::MIN_VER => {
+    ) -> Result<Prepared> {
+        match version {
+            v if *v >= since_v0_2_0_draft::MIN_VER => {
+                let linker = since_v0_2_0_draft::linker(engine, component)?;
+                let pre = linker.instantiate_pre(component)?;
+                // `feeds` is an optional export, so failing to bind it only
+                // means the extension does not provide it.
+                let feeds = since_v0_2_0_draft::FeedsPre::new(pre.clone())
+                    .ok()
+                    .map(FeedsPre::V0_2_0_DRAFT);
+                let configurable = since_v0_2_0_draft::ConfigurablePre::new(pre.clone())
+                    .ok()
+                    .map(ConfigurablePre::V0_2_0_DRAFT);
+                Ok(Prepared {
+                    extension: ExtensionPre::V0_2_0_DRAFT(since_v0_2_0_draft::ExtensionPre::new(
+                        pre,
+                    )?),
+                    feeds,
+                    configurable,
+                })
+            }
+            v if *v >= since_v0_1_0_draft::MIN_VER => {
                 let linker = since_v0_1_0_draft::linker(engine)?;
                 let pre = linker.instantiate_pre(component)?;
-                Ok(ExtensionPre::V0_1_0_DRAFT(
-                    since_v0_1_0_draft::ExtensionPre::new(pre)?,
-                ))
+                Ok(Prepared {
+                    extension: ExtensionPre::V0_1_0_DRAFT(since_v0_1_0_draft::ExtensionPre::new(
+                        pre,
+                    )?),
+                    feeds: None,
+                    configurable: None,
+                })
             }
             _ => Err(anyhow!("unsupported extension version")),
-        }?;
+        }
+    }
+
+    async fn instantiate_async(
+        engine: &Engine,
+        version: Version,
+        component: &Component,
+        id: Arc<str>,
+        metadata: Metadata,
+        manifest: Option<Manifest>,
+        hooks: HostHooks,
+    ) -> Result<Self> {
+        let Prepared {
+            extension: extension_pre,
+            feeds: feeds_pre,
+            configurable: configurable_pre,
+        } = Self::prepare(engine, &version, component)?;
 
         Ok(Self {
+            id,
+            version,
             extension_pre,
+            feeds_pre,
             metadata,
+            configurable_pre,
+            filter_categories: OnceCell::new(),
+            settings: OnceCell::new(),
+            setting_values: RwLock::default(),
+            cookie_jar: CookieJar::default(),
+            http_fixtures: None,
+            manifest: manifest.map(Arc::new),
+            hooks,
+            log_filter: StdRwLock::default(),
+            log_rate_limiter: Arc::default(),
         })
     }
 
+    /// The id the extension is known by in logs: the id of its manifest,
+    /// its name, or the file name it was loaded from if it has neither.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
 
+    /// The capabilities the extension declared, if it has a manifest.
+    ///
+    /// Requests to hosts it did not declare are denied.
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_deref()
+    }
+
+    /// The version of the `nero:extension` interfaces the extension was
+    /// built against, without its pre-release tag.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Whether the extension exports the optional `feeds` interface.
+    pub fn has_feeds(&self) -> bool {
+        self.feeds_pre.is_some()
+    }
+
+    pub fn cookie_jar(&self) -> &CookieJar {
+        &self.cookie_jar
+    }
+
+    /// Replaces the in-memory cookie jar the extension starts with, e.g. by
+    /// one persisted with [`CookieJar::open`].
+    pub fn set_cookie_jar(&mut self, cookie_jar: CookieJar) {
+        self.cookie_jar = cookie_jar;
+    }
+
+    /// Records the HTTP traffic of the following calls to `fixtures`, or
+    /// answers it from them, depending on their mode. `None` sends requests
+    /// to the network again.
+    pub fn set_http_fixtures(&mut self, fixtures: Option<HttpFixtures>) {
+        self.http_fixtures = fixtures;
+    }
+
+    /// Whether the extension exports the optional `configurable` interface.
+    pub fn has_settings(&self) -> bool {
+        self.configurable_pre.is_some()
+    }
+
+    /// Returns the settings declared by the extension, or none if it does
+    /// not export the `configurable` interface.
+    pub async fn settings(&self) -> Result<&[Setting]> {
+        let settings = self
+            .settings
+            .get_or_try_init(|| async {
+                let Some(configurable_pre) = &self.configurable_pre else {
+                    return Ok(Vec::new());
+                };
+                let state = self.call_state("settings");
+                let mut store = Store::new(configurable_pre.engine(), state);
+
+                match configurable_pre.instantiate_async(&mut store).await? {
+                    Configurable::V0_2_0_DRAFT(configurable) => {
+                        let res = configurable
+                            .nero_extension_configurable()
+                            .call_settings(&mut store)
+                            .await?;
+
+                        Ok::<_, anyhow::Error>(res.into_iter().map(Into::into).collect())
+                    }
+                }
+            })
+            .await?;
+
+        Ok(settings)
+    }
+
+    /// Returns the values set by the user, without declared defaults.
+    pub async fn setting_values(&self) -> HashMap<String, SettingValue> {
+        self.setting_values.read().await.clone()
+    }
+
+    /// Sets the value of the setting `id`, or resets it to its default when
+    /// `value` is `None`.
+    pub async fn set_setting(&self, id: &str, value: Option<SettingValue>) -> Result<()> {
+        let setting = self
+            .settings()
+            .await?
+            .iter()
+            .find(|setting| setting.id == id)
+            .ok_or_else(|| anyhow!("unknown setting `{id}`"))?;
+
+        let mut values = self.setting_values.write().await;
+        match value {
+            Some(value) => {
+                validate_setting_value(setting, &value)?;
+                values.insert(setting.id.clone(), value);
+            }
+            None => {
+                values.remove(id);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn log_filter(&self) -> Arc<LogFilter> {
+        self.log_filter.read().unwrap().clone()
+    }
+
+    /// Replaces the filter deciding which messages logged by the extension
+    /// are kept, e.g. to turn up verbosity while debugging it.
+    pub fn set_log_filter(&self, filter: LogFilter) {
+        *self.log_filter.write().unwrap() = Arc::new(filter);
+    }
+
+    /// Creates the state of a call into the extension running `operation`.
+    fn call_state(&self, operation: &'static str) -> WasmState {
+        let logging = WasiLoggingCtx::new(self.id.clone())
+            .with_operation(operation)
+            .with_sinks(self.hooks.log_sinks.iter().cloned())
+            .with_filter(self.log_filter())
+            .with_rate_limiter(self.log_rate_limiter.clone());
+
+        WasmState {
+            cookie_jar: self.cookie_jar.clone(),
+            login_handler: self.hooks.login_handler.clone(),
+            logging,
+            http_inspector: self.hooks.http_inspector.clone(),
+            http_fixtures: self.http_fixtures.clone(),
+            manifest: self.manifest.clone(),
+            call: Call {
+                extension: self.id.clone(),
+                operation,
+                id: NEXT_CALL_ID.fetch_add(1, Ordering::Relaxed),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Creates the store for a call into the extension, exposing the current
+    /// setting values through the `settings` import. Messages logged and
+    /// requests sent by the extension are tagged with `operation`.
+    async fn new_store(&self, operation: &'static str) -> Result<Store<WasmState>> {
+        let values = self.setting_values.read().await;
+        let settings = resolve_setting_values(self.settings().await?, &values)?;
+
+        let state = WasmState {
+            settings,
+            ..self.call_state(operation)
+        };
+        Ok(Store::new(self.extension_pre.engine(), state))
+    }
+
     pub async fn filters(&self) -> Result<Vec<FilterCategory>> {
-        let mut store = Store::new(self.extension_pre.engine(), WasmState::default());
+        let mut store = self.new_store("filters").await?;
 
         match self.extension_pre.instantiate_async(&mut store).await? {
             Extension::V0_1_0_DRAFT(extension) => {
@@ -95,6 +453,15 @@ impl WasmExtension {
 
                 Ok(res.into_iter().map(Into::into).collect())
             }
+            Extension::V0_2_0_DRAFT(extension) => {
+                let res = extension
+                    .nero_extension_extractor()
+                    .call_filters(&mut store)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(res.into_iter().map(Into::into).collect())
+            }
         }
     }
 
@@ -104,10 +471,30 @@ impl WasmExtension {
         page: Option<u16>,
         filters: Vec<SearchFilter>,
     ) -> Result<SeriesPage> {
-        let mut store = Store::new(self.extension_pre.engine(), WasmState::default());
+        // Checked even without filters, since categories may be required.
+        let categories = self
+            .filter_categories
+            .get_or_try_init(|| self.filters())
+            .await?;
+        validate_search_filters(categories, &filters)?;
+
+        let mut store = self.new_store("search").await?;
 
         match self.extension_pre.instantiate_async(&mut store).await? {
             Extension::V0_1_0_DRAFT(extension) => {
+                let filters = filters
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<_>>>()?;
+                let res = extension
+                    .nero_extension_extractor()
+                    .call_search(&mut store, query, page, &filters)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                res.try_into_with_store(&mut store).await
+            }
+            Extension::V0_2_0_DRAFT(extension) => {
                 let filters = filters.into_iter().map(Into::into).collect::<Vec<_>>();
                 let res = extension
                     .nero_extension_extractor()
@@ -121,7 +508,7 @@ impl WasmExtension {
     }
 
     pub async fn get_series_info(&self, series_id: &str) -> Result<Series> {
-        let mut store = Store::new(self.extension_pre.engine(), WasmState::default());
+        let mut store = self.new_store("get-series-info").await?;
 
         match self.extension_pre.instantiate_async(&mut store).await? {
             Extension::V0_1_0_DRAFT(extension) => {
@@ -133,6 +520,15 @@ impl WasmExtension {
 
                 res.try_into_with_store(&mut store).await
             }
+            Extension::V0_2_0_DRAFT(extension) => {
+                let res = extension
+                    .nero_extension_extractor()
+                    .call_get_series_info(&mut store, series_id)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                res.try_into_with_store(&mut store).await
+            }
         }
     }
 
@@ -141,7 +537,7 @@ impl WasmExtension {
         series_id: &str,
         page: Option<u16>,
     ) -> Result<EpisodesPage> {
-        let mut store = Store::new(self.extension_pre.engine(), WasmState::default());
+        let mut store = self.new_store("get-series-episodes").await?;
 
         match self.extension_pre.instantiate_async(&mut store).await? {
             Extension::V0_1_0_DRAFT(extension) => {
@@ -153,11 +549,67 @@ impl WasmExtension {
 
                 res.try_into_with_store(&mut store).await
             }
+            Extension::V0_2_0_DRAFT(extension) => {
+                let res = extension
+                    .nero_extension_extractor()
+                    .call_get_series_episodes(&mut store, series_id, page)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                res.try_into_with_store(&mut store).await
+            }
         }
     }
 
+    /// Returns the seasons of a series, empty if it is not split into
+    /// seasons or the extension predates them.
+    pub async fn get_series_seasons(&self, series_id: &str) -> Result<Vec<Season>> {
+        if let ExtensionPre::V0_1_0_DRAFT(_) = self.extension_pre {
+            return Ok(Vec::new());
+        }
+        let mut store = self.new_store("get-series-seasons").await?;
+
+        let Extension::V0_2_0_DRAFT(extension) =
+            self.extension_pre.instantiate_async(&mut store).await?
+        else {
+            unreachable!("the extension was checked to be v0.2.0-draft");
+        };
+        let res = extension
+            .nero_extension_extractor()
+            .call_get_series_seasons(&mut store, series_id)
+            .await?
+            .map_err(|err| anyhow!("{err}"))?;
+
+        Ok(res.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_season_episodes(
+        &self,
+        series_id: &str,
+        season_id: &str,
+        page: Option<u16>,
+    ) -> Result<EpisodesPage> {
+        if let ExtensionPre::V0_1_0_DRAFT(_) = self.extension_pre {
+            bail!("extension does not split series into seasons");
+        }
+        let mut store = self.new_store("get-season-episodes").await?;
+
+        let Extension::V0_2_0_DRAFT(extension) =
+            self.extension_pre.instantiate_async(&mut store).await?
+        else {
+            unreachable!("the extension was checked to be v0.2.0-draft");
+        };
+        let res = extension
+            .nero_extension_extractor()
+            .call_get_season_episodes(&mut store, series_id, season_id, page)
+            .await?
+            .map_err(|err| anyhow!("{err}"))?;
+
+        res.try_into_with_store(&mut store).await
+    }
+
     pub async fn get_series_videos(&self, series_id: &str, episode_id: &str) -> Result<Vec<Video>> {
-        let mut store = Store::new(self.extension_pre.engine(), WasmState::default());
+        let mut store = self.new_store("get-series-videos").await?;
 
         match self.extension_pre.instantiate_async(&mut store).await? {
             Extension::V0_1_0_DRAFT(extension) => {
@@ -173,6 +625,59 @@ impl WasmExtension {
                 }
                 Ok(items)
             }
+            Extension::V0_2_0_DRAFT(extension) => {
+                let res = extension
+                    .nero_extension_extractor()
+                    .call_get_series_videos(&mut store, series_id, episode_id)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                let mut items = Vec::new();
+                for video in res {
+                    items.push(video.try_into_with_store(&mut store).await?);
+                }
+                Ok(items)
+            }
+        }
+    }
+
+    pub async fn feeds(&self) -> Result<Vec<Feed>> {
+        let feeds_pre = self
+            .feeds_pre
+            .as_ref()
+            .ok_or_else(|| anyhow!("extension does not provide feeds"))?;
+        let mut store = self.new_store("feeds").await?;
+
+        match feeds_pre.instantiate_async(&mut store).await? {
+            Feeds::V0_2_0_DRAFT(feeds) => {
+                let res = feeds
+                    .nero_extension_feeds()
+                    .call_feeds(&mut store)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(res.into_iter().map(Into::into).collect())
+            }
+        }
+    }
+
+    pub async fn get_feed(&self, feed_id: &str, page: Option<u16>) -> Result<FeedPage> {
+        let feeds_pre = self
+            .feeds_pre
+            .as_ref()
+            .ok_or_else(|| anyhow!("extension does not provide feeds"))?;
+        let mut store = self.new_store("get-feed").await?;
+
+        match feeds_pre.instantiate_async(&mut store).await? {
+            Feeds::V0_2_0_DRAFT(feeds) => {
+                let res = feeds
+                    .nero_extension_feeds()
+                    .call_get_feed(&mut store, feed_id, page)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                res.try_into_with_store(&mut store).await
+            }
         }
     }
 }
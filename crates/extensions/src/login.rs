@@ -0,0 +1,21 @@
+use std::{future::Future, pin::Pin};
+
+use url::Url;
+
+/// The reasons a login requested by an extension can fail.
+#[derive(Debug)]
+pub enum LoginError {
+    Unsupported,
+    Cancelled,
+    Other(String),
+}
+
+pub type LoginFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<String>, LoginError>> + Send + 'a>>;
+
+/// Shows login pages to the user on behalf of extensions.
+pub trait LoginHandler: Send + Sync {
+    /// Opens `url` and resolves, once the user reaches a URL starting with
+    /// `target_url`, with the cookies held for it in `Set-Cookie` format.
+    fn login(&self, url: Url, target_url: Url) -> LoginFuture<'_>;
+}
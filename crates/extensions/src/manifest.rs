@@ -0,0 +1,372 @@
+//! The manifest an extension declares itself and the capabilities it needs
+//! with, embedded as JSON in the `nero:manifest` custom section of its
+//! component.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use wasm_encoder::{ComponentSection, CustomSection, RawSection};
+use wasmparser::{Parser, Payload};
+
+/// The custom section holding the manifest.
+pub const MANIFEST_SECTION: &str = "nero:manifest";
+
+/// Imports every extension may use without declaring them: the WASI
+/// interfaces the Rust standard library and logging rely on, and the
+/// interfaces only defining types.
+const IMPLICIT_IMPORTS: &[&str] = &[
+    "wasi:cli/",
+    "wasi:clocks/",
+    "wasi:filesystem/",
+    "wasi:io/",
+    "wasi:random/",
+    "wasi:sockets/",
+    "wasi:logging/",
+    "nero:wasi-logging/",
+    "wasi:http/types",
+    "nero:extension/types",
+];
+
+/// The import sending HTTP requests, which requires network hosts.
+const OUTGOING_HANDLER: &str = "wasi:http/outgoing-handler";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// A unique identifier of the extension, e.g. `my-site`.
+    pub id: String,
+    pub display_name: String,
+    /// The URL of the icon of the extension, usually a `data:` URI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// The languages of the content, as BCP 47 tags such as `en` or `ja`.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// The kinds of content offered, e.g. `anime` or `movies`.
+    #[serde(default)]
+    pub content_types: Vec<String>,
+    /// The host interfaces the extension imports, without their version,
+    /// e.g. `wasi:http/outgoing-handler` or `nero:extension/login`.
+    #[serde(default)]
+    pub imports: Vec<String>,
+    /// The hosts the extension sends requests to, e.g. `example.com`, or
+    /// `*.example.com` for any of its subdomains.
+    #[serde(default)]
+    pub network_hosts: Vec<String>,
+}
+
+/// Replaces the top-level custom section `name` of the component `wasm`
+/// with `data`, e.g. to embed a manifest in [`MANIFEST_SECTION`].
+pub fn set_custom_section(wasm: &[u8], name: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload?;
+        match &payload {
+            Payload::Version { range, .. } => {
+                if depth == 0 {
+                    output.extend_from_slice(&wasm[range.clone()]);
+                }
+                depth += 1;
+                continue;
+            }
+            Payload::End(_) => {
+                depth -= 1;
+                continue;
+            }
+            Payload::CustomSection(section) if depth == 1 && section.name() == name => continue,
+            _ => {}
+        }
+
+        // Nested modules and components are copied whole with their
+        // section, so only the top-level sections are written.
+        if depth == 1
+            && let Some((id, range)) = payload.as_section()
+        {
+            RawSection {
+                id,
+                data: &wasm[range],
+            }
+            .append_to_component(&mut output);
+        }
+    }
+
+    CustomSection {
+        name: name.into(),
+        data: data.into(),
+    }
+    .append_to_component(&mut output);
+    Ok(output)
+}
+
+/// Whether `id` is made of lowercase letters, digits, `-` and `.`. Ids also
+/// name the files of installed extensions, so they cannot start with `.`.
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+}
+
+fn check_host_pattern(pattern: &str) -> Result<()> {
+    let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+    let valid = !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if !valid {
+        bail!("`{pattern}` is not a host such as `example.com` or `*.example.com`");
+    }
+    Ok(())
+}
+
+impl Manifest {
+    /// Reads the manifest from the custom section of a component, if it
+    /// has one.
+    pub fn from_wasm(wasm_bytes: &[u8]) -> Result<Option<Self>> {
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(wasm_bytes) {
+            match payload? {
+                Payload::Version { .. } => depth += 1,
+                Payload::End(_) => depth -= 1,
+                Payload::CustomSection(section)
+                    if depth == 1 && section.name() == MANIFEST_SECTION =>
+                {
+                    return Ok(Some(serde_json::from_slice(section.data())?));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks the declared values, and that the component declares every
+    /// capability it imports.
+    ///
+    /// `imports` are the names of the imports of the component, with or
+    /// without their version.
+    pub fn validate<'a>(&self, imports: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        if !is_valid_id(&self.id) {
+            bail!(
+                "`{}` must be lowercase letters, digits, `-` and `.`, not starting with `.`",
+                self.id
+            );
+        }
+        if self.display_name.trim().is_empty() {
+            bail!("the display name is empty");
+        }
+        for pattern in &self.network_hosts {
+            check_host_pattern(pattern)?;
+        }
+
+        for import in imports {
+            let name = import.split_once('@').map_or(import, |(name, _)| name);
+            if IMPLICIT_IMPORTS
+                .iter()
+                .any(|implicit| name.starts_with(implicit))
+            {
+                continue;
+            }
+            if !self.imports.iter().any(|declared| declared == name) {
+                bail!("the component imports `{name}` without declaring it");
+            }
+            if name == OUTGOING_HANDLER && self.network_hosts.is_empty() {
+                bail!("the component sends HTTP requests without declaring network hosts");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the extension declared `host` as one it sends requests to.
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.network_hosts.iter().any(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+                None => host == pattern,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest {
+            id: "my-site".to_owned(),
+            display_name: "My Site".to_owned(),
+            icon: None,
+            languages: vec!["en".to_owned()],
+            content_types: vec!["anime".to_owned()],
+            imports: vec![OUTGOING_HANDLER.to_owned()],
+            network_hosts: vec!["example.com".to_owned(), "*.cdn.example.com".to_owned()],
+        }
+    }
+
+    #[test]
+    fn accepts_declared_and_implicit_imports() {
+        manifest()
+            .validate([
+                "wasi:http/outgoing-handler@0.2.6",
+                "wasi:http/types@0.2.6",
+                "wasi:io/streams@0.2.6",
+                "nero:extension/types@0.2.0-draft",
+            ])
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_undeclared_imports() {
+        let err = manifest()
+            .validate(["nero:extension/login@0.2.0-draft"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the component imports `nero:extension/login` without declaring it"
+        );
+    }
+
+    #[test]
+    fn rejects_http_without_network_hosts() {
+        let manifest = Manifest {
+            network_hosts: Vec::new(),
+            ..manifest()
+        };
+        let err = manifest
+            .validate(["wasi:http/outgoing-handler@0.2.6"])
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("without declaring network hosts"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for manifest in [
+            Manifest {
+                display_name: String::new(),
+                ..manifest()
+            },
+            Manifest {
+                display_name: " ".to_owned(),
+                ..manifest()
+            },
+            Manifest {
+                id: "My Site".to_owned(),
+                ..manifest()
+            },
+            Manifest {
+                id: ".my-site".to_owned(),
+                ..manifest()
+            },
+            Manifest {
+                network_hosts: vec!["*.".to_owned()],
+                ..manifest()
+            },
+            Manifest {
+                network_hosts: vec!["example.com/path".to_owned()],
+                ..manifest()
+            },
+        ] {
+            assert!(manifest.validate([]).is_err(), "{manifest:?}");
+        }
+    }
+
+    #[test]
+    fn checks_ids() {
+        for id in ["my-site", "com.example.site", "site2"] {
+            assert!(is_valid_id(id), "{id}");
+        }
+        for id in ["", ".", ".site", "../site", "a/b", "Site", "my_site"] {
+            assert!(!is_valid_id(id), "{id}");
+        }
+    }
+
+    #[test]
+    fn matches_network_hosts() {
+        let manifest = manifest();
+
+        assert!(manifest.allows_host("example.com"));
+        assert!(manifest.allows_host("EXAMPLE.com"));
+        assert!(manifest.allows_host("video.cdn.example.com"));
+        assert!(manifest.allows_host("a.video.cdn.example.com"));
+
+        assert!(!manifest.allows_host("www.example.com"));
+        assert!(!manifest.allows_host("cdn.example.com"));
+        assert!(!manifest.allows_host("evilcdn.example.com"));
+        assert!(!manifest.allows_host("video.cdn.example.com.evil"));
+        assert!(!manifest.allows_host("example.com.evil"));
+    }
+
+    #[test]
+    fn matches_wildcards_only_on_label_boundaries() {
+        let manifest = Manifest {
+            network_hosts: vec!["*.example.com".to_owned()],
+            ..manifest()
+        };
+
+        assert!(manifest.allows_host("www.example.com"));
+        assert!(!manifest.allows_host("example.com"));
+        assert!(!manifest.allows_host("evilexample.com"));
+        assert!(!manifest.allows_host("example.com.evil"));
+        assert!(!manifest.allows_host(".example.com"));
+    }
+
+    /// A component with a nested module, both holding a manifest section.
+    fn component(manifest: &[u8], nested_manifest: &[u8]) -> Vec<u8> {
+        let mut module = wasm_encoder::Module::new();
+        module.section(&CustomSection {
+            name: MANIFEST_SECTION.into(),
+            data: nested_manifest.into(),
+        });
+
+        let mut component = wasm_encoder::Component::new();
+        component.section(&wasm_encoder::ModuleSection(&module));
+        component.section(&CustomSection {
+            name: MANIFEST_SECTION.into(),
+            data: manifest.into(),
+        });
+        component.finish()
+    }
+
+    /// The manifest sections of `wasm` with their nesting depth.
+    fn manifest_sections(wasm: &[u8]) -> Vec<(usize, Vec<u8>)> {
+        let mut sections = Vec::new();
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload.unwrap() {
+                Payload::Version { .. } => depth += 1,
+                Payload::End(_) => depth -= 1,
+                Payload::CustomSection(section) if section.name() == MANIFEST_SECTION => {
+                    sections.push((depth, section.data().to_vec()));
+                }
+                _ => {}
+            }
+        }
+        sections
+    }
+
+    #[test]
+    fn replaces_only_the_top_level_section() {
+        let wasm = component(b"old", b"nested");
+
+        let wasm = set_custom_section(&wasm, MANIFEST_SECTION, b"new").unwrap();
+        assert_eq!(
+            manifest_sections(&wasm),
+            [(2, b"nested".to_vec()), (1, b"new".to_vec())]
+        );
+        wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all())
+            .validate_all(&wasm)
+            .unwrap();
+
+        let manifest = serde_json::to_vec(&manifest()).unwrap();
+        let wasm = set_custom_section(&wasm, MANIFEST_SECTION, &manifest).unwrap();
+        assert_eq!(Manifest::from_wasm(&wasm).unwrap().unwrap().id, "my-site");
+    }
+}
@@ -0,0 +1,364 @@
+//! Repositories distributing extensions, and their installation.
+//!
+//! A repository is a JSON [`RepositoryIndex`] listing the releases of
+//! each extension, with the SHA-256 hash and signature of each package.
+//! Indexes and packages are fetched over HTTP or from `file://` URLs, and
+//! package URLs may be relative to the index.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    crypto::{HashAlgorithm, hash},
+    host::WasmHost,
+    manifest::is_valid_id,
+    signing::PublicKey,
+};
+
+/// The version of the index format this client reads.
+pub const INDEX_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryIndex {
+    /// The version of the index format, currently [`INDEX_FORMAT`].
+    pub format: u32,
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexEntry {
+    /// The id of the extension, which its manifest must declare too.
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Release {
+    pub version: Version,
+    /// The URL of the package, absolute or relative to the index.
+    pub url: String,
+    /// The SHA-256 hash of the package, as lowercase hex.
+    pub sha256: String,
+    /// The signature of the package, as base64, see
+    /// [`signing`](crate::signing).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The oldest version of the app the release works with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_host_version: Option<Version>,
+}
+
+impl IndexEntry {
+    /// The newest release working with `host_version`.
+    pub fn latest_release(&self, host_version: &Version) -> Option<&Release> {
+        self.releases
+            .iter()
+            .filter(|release| release.is_compatible(host_version))
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    pub fn release(&self, version: &Version) -> Option<&Release> {
+        self.releases
+            .iter()
+            .find(|release| &release.version == version)
+    }
+}
+
+impl Release {
+    pub fn is_compatible(&self, host_version: &Version) -> bool {
+        self.min_host_version
+            .as_ref()
+            .is_none_or(|min| min <= host_version)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Fetches indexes and packages of a repository.
+pub struct RepositoryClient {
+    http: reqwest::Client,
+    index_url: Url,
+    public_key: Option<PublicKey>,
+    allow_unsigned: bool,
+}
+
+impl RepositoryClient {
+    /// Creates a client for the repository whose index is at `index_url`.
+    ///
+    /// Packages are only downloaded once the client has a public key to
+    /// verify them with, or is allowed to install unsigned packages.
+    pub fn new(index_url: Url) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            index_url,
+            public_key: None,
+            allow_unsigned: false,
+        }
+    }
+
+    /// Requires every package to be signed with the secret key of
+    /// `public_key`.
+    pub fn with_public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Accepts packages without checking their signature when the client
+    /// has no public key, relying on their hash alone.
+    pub fn with_unsigned_allowed(mut self) -> Self {
+        self.allow_unsigned = true;
+        self
+    }
+
+    async fn fetch(&self, url: &Url) -> Result<Vec<u8>> {
+        match url.scheme() {
+            "file" => {
+                let path = url
+                    .to_file_path()
+                    .map_err(|()| anyhow::anyhow!("`{url}` is not a file path"))?;
+                tokio::fs::read(&path)
+                    .await
+                    .with_context(|| format!("failed to read `{}`", path.display()))
+            }
+            "http" | "https" => {
+                let response = self
+                    .http
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .with_context(|| format!("failed to fetch `{url}`"))?;
+                Ok(response.bytes().await?.to_vec())
+            }
+            scheme => bail!("unsupported URL scheme `{scheme}`"),
+        }
+    }
+
+    pub async fn index(&self) -> Result<RepositoryIndex> {
+        let bytes = self.fetch(&self.index_url).await?;
+        let index: RepositoryIndex = serde_json::from_slice(&bytes)
+            .with_context(|| format!("`{}` is not a repository index", self.index_url))?;
+        if index.format != INDEX_FORMAT {
+            bail!(
+                "unsupported index format {}, expected {INDEX_FORMAT}",
+                index.format
+            );
+        }
+        Ok(index)
+    }
+
+    /// Downloads the package of `release` and checks its hash, and its
+    /// signature unless unsigned packages are allowed and the client has no
+    /// public key.
+    pub async fn download(&self, release: &Release) -> Result<Vec<u8>> {
+        let url = self
+            .index_url
+            .join(&release.url)
+            .with_context(|| format!("`{}` is not a URL", release.url))?;
+        if self.public_key.is_none() && !self.allow_unsigned {
+            bail!("no public key to verify the package at `{url}` with");
+        }
+        let bytes = self.fetch(&url).await?;
+
+        let sha256 = to_hex(&hash(HashAlgorithm::Sha256, &bytes));
+        if !sha256.eq_ignore_ascii_case(&release.sha256) {
+            bail!(
+                "the package at `{url}` has hash {sha256}, expected {}",
+                release.sha256
+            );
+        }
+
+        if let Some(public_key) = &self.public_key {
+            let signature = release
+                .signature
+                .as_deref()
+                .with_context(|| format!("the package at `{url}` is not signed"))?;
+            public_key
+                .verify(&bytes, signature)
+                .with_context(|| format!("failed to verify the package at `{url}`"))?;
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// An extension installed from a repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledExtension {
+    pub id: String,
+    pub name: String,
+    pub version: Version,
+    pub sha256: String,
+    /// The URL of the index it was installed from.
+    pub repository: Url,
+}
+
+/// The directory extensions are installed into, holding the package of
+/// each extension as `<id>.wasm` next to its record as `<id>.json`.
+pub struct ExtensionStore {
+    dir: PathBuf,
+}
+
+impl ExtensionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The path of the package of the extension `id`.
+    pub fn package_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.wasm"))
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    /// Ids are used as file names, so they are checked like in manifests.
+    fn check_id(id: &str) -> Result<()> {
+        if !is_valid_id(id) {
+            bail!("`{id}` is not an extension id");
+        }
+        Ok(())
+    }
+
+    pub async fn installed(&self) -> Result<Vec<InstalledExtension>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut installed = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let record = tokio::fs::read(&path).await?;
+            match serde_json::from_slice(&record) {
+                Ok(extension) => installed.push(extension),
+                Err(err) => tracing::warn!("ignoring `{}`: {err}", path.display()),
+            }
+        }
+        installed.sort_by(|a: &InstalledExtension, b| a.id.cmp(&b.id));
+        Ok(installed)
+    }
+
+    /// Downloads and installs the release `version` of the extension `id`,
+    /// or its latest release working with `host_version`, replacing any
+    /// installed version.
+    ///
+    /// The package must be an extension `host` can load, with a manifest
+    /// declaring the id listed in the index. Its record is written last, so
+    /// that the extension is only listed once installed.
+    pub async fn install(
+        &self,
+        client: &RepositoryClient,
+        host: &WasmHost,
+        host_version: &Version,
+        id: &str,
+        version: Option<&Version>,
+    ) -> Result<InstalledExtension> {
+        Self::check_id(id)?;
+        let index = client.index().await?;
+        let entry = index
+            .extensions
+            .iter()
+            .find(|entry| entry.id == id)
+            .with_context(|| format!("the repository has no extension `{id}`"))?;
+        let release = match version {
+            Some(version) => entry
+                .release(version)
+                .with_context(|| format!("`{id}` has no release {version}"))?,
+            None => entry
+                .latest_release(host_version)
+                .with_context(|| format!("no release of `{id}` works with {host_version}"))?,
+        };
+        if !release.is_compatible(host_version) {
+            bail!(
+                "`{id}` {} does not work with {host_version}",
+                release.version
+            );
+        }
+
+        let bytes = client.download(release).await?;
+        host.validate_component(&bytes)
+            .with_context(|| format!("`{id}` {} cannot be loaded", release.version))?;
+        let manifest = host
+            .read_manifest(&bytes)?
+            .with_context(|| format!("`{id}` {} has no manifest", release.version))?;
+        if manifest.id != id {
+            bail!("`{id}` declares the id `{}` in its manifest", manifest.id);
+        }
+
+        let installed = InstalledExtension {
+            id: id.to_owned(),
+            name: entry.name.clone(),
+            version: release.version.clone(),
+            sha256: release.sha256.to_ascii_lowercase(),
+            repository: client.index_url.clone(),
+        };
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("failed to create `{}`", self.dir.display()))?;
+        let record = serde_json::to_vec_pretty(&installed)?;
+        write_atomic(&self.package_path(id), &bytes).await?;
+        if let Err(err) = write_atomic(&self.record_path(id), &record).await {
+            // A package replacing another one no longer matches its record
+            // either, so both are removed.
+            let _ = tokio::fs::remove_file(self.package_path(id)).await;
+            let _ = tokio::fs::remove_file(self.record_path(id)).await;
+            return Err(err);
+        }
+
+        Ok(installed)
+    }
+
+    pub async fn uninstall(&self, id: &str) -> Result<()> {
+        Self::check_id(id)?;
+        let record_path = self.record_path(id);
+        if !tokio::fs::try_exists(&record_path).await? {
+            bail!("`{id}` is not installed");
+        }
+        tokio::fs::remove_file(&record_path).await?;
+        match tokio::fs::remove_file(self.package_path(id)).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes `data` next to `path` first, so that `path` is never left
+/// partially written.
+pub(crate) async fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    tokio::fs::write(&temp_path, data)
+        .await
+        .with_context(|| format!("failed to write `{}`", path.display()))?;
+    if let Err(err) = tokio::fs::rename(&temp_path, path).await {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(err).with_context(|| format!("failed to write `{}`", path.display()));
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+
+use crate::types::{Setting, SettingKind, SettingValue};
+
+/// Checks that `value` can be stored for `setting`.
+pub fn validate_setting_value(setting: &Setting, value: &SettingValue) -> Result<()> {
+    match (&setting.kind, value) {
+        (SettingKind::Text { .. }, SettingValue::Text(_))
+        | (SettingKind::Secret, SettingValue::Secret(_))
+        | (SettingKind::Toggle { .. }, SettingValue::Toggle(_)) => Ok(()),
+        (SettingKind::Choice { options, .. }, SettingValue::Choice(id)) => {
+            if !options.iter().any(|option| &option.id == id) {
+                bail!("unknown option `{id}` for setting `{}`", setting.id);
+            }
+            Ok(())
+        }
+        (SettingKind::Number { min, max, .. }, SettingValue::Number(value)) => {
+            if min.is_some_and(|min| *value < min) || max.is_some_and(|max| *value > max) {
+                bail!("value {value} is out of range for setting `{}`", setting.id);
+            }
+            Ok(())
+        }
+        _ => bail!("value does not match the kind of setting `{}`", setting.id),
+    }
+}
+
+/// Resolves the value passed to the extension for each of its `settings`,
+/// falling back to their defaults when no value is stored.
+pub(crate) fn resolve_setting_values(
+    settings: &[Setting],
+    values: &HashMap<String, SettingValue>,
+) -> Result<HashMap<String, SettingValue>> {
+    let mut resolved = HashMap::new();
+
+    for setting in settings {
+        match values
+            .get(&setting.id)
+            .cloned()
+            .or_else(|| setting.kind.default_value())
+        {
+            Some(value) => {
+                resolved.insert(setting.id.clone(), value);
+            }
+            None if setting.required => bail!("setting `{}` is required", setting.id),
+            None => {}
+        }
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SettingOption;
+
+    fn setting(id: &str, kind: SettingKind, required: bool) -> Setting {
+        Setting {
+            id: id.to_owned(),
+            display_name: id.to_owned(),
+            description: None,
+            kind,
+            required,
+        }
+    }
+
+    fn number(min: Option<f64>, max: Option<f64>) -> Setting {
+        let kind = SettingKind::Number {
+            min,
+            max,
+            default: None,
+        };
+        setting("quality", kind, false)
+    }
+
+    #[test]
+    fn checks_number_bounds() {
+        let quality = number(Some(1.0), Some(10.0));
+        assert!(validate_setting_value(&quality, &SettingValue::Number(1.0)).is_ok());
+        assert!(validate_setting_value(&quality, &SettingValue::Number(10.0)).is_ok());
+
+        let err = validate_setting_value(&quality, &SettingValue::Number(10.5)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value 10.5 is out of range for setting `quality`"
+        );
+        assert!(validate_setting_value(&quality, &SettingValue::Number(0.0)).is_err());
+
+        let unbounded = number(None, Some(10.0));
+        assert!(validate_setting_value(&unbounded, &SettingValue::Number(-1e9)).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_choice() {
+        let kind = SettingKind::Choice {
+            options: vec![SettingOption {
+                id: "sub".to_owned(),
+                display_name: "Subtitled".to_owned(),
+            }],
+            default: None,
+        };
+        let language = setting("language", kind, false);
+
+        assert!(validate_setting_value(&language, &SettingValue::Choice("sub".to_owned())).is_ok());
+        let err =
+            validate_setting_value(&language, &SettingValue::Choice("dub".to_owned())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown option `dub` for setting `language`"
+        );
+    }
+
+    #[test]
+    fn rejects_value_of_another_kind() {
+        let token = setting("token", SettingKind::Secret, false);
+        let err =
+            validate_setting_value(&token, &SettingValue::Text("abc".to_owned())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value does not match the kind of setting `token`"
+        );
+        assert!(validate_setting_value(&number(None, None), &SettingValue::Toggle(true)).is_err());
+    }
+
+    #[test]
+    fn resolves_stored_and_default_values() {
+        let settings = [
+            setting("adult", SettingKind::Toggle { default: false }, true),
+            setting("token", SettingKind::Secret, false),
+            number(None, None),
+        ];
+        let values = HashMap::from([("quality".to_owned(), SettingValue::Number(3.0))]);
+
+        let resolved = resolve_setting_values(&settings, &values).unwrap();
+        assert_eq!(
+            resolved,
+            HashMap::from([
+                ("adult".to_owned(), SettingValue::Toggle(false)),
+                ("quality".to_owned(), SettingValue::Number(3.0)),
+            ])
+        );
+    }
+
+    #[test]
+    fn requires_unset_required_setting() {
+        let settings = [setting("token", SettingKind::Secret, true)];
+
+        let err = resolve_setting_values(&settings, &HashMap::new()).unwrap_err();
+        assert_eq!(err.to_string(), "setting `token` is required");
+
+        let values = HashMap::from([("token".to_owned(), SettingValue::Secret("abc".to_owned()))]);
+        assert!(resolve_setting_values(&settings, &values).is_ok());
+    }
+}
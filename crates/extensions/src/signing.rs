@@ -0,0 +1,172 @@
+//! Ed25519 signatures of extension packages.
+//!
+//! Keys and signatures are exchanged as base64 text. A package is signed
+//! as a whole and its signature is distributed next to it, so signing
+//! does not change the component.
+
+use std::{fmt, str::FromStr};
+
+use ed25519_dalek::{Signature, Signer, Verifier};
+
+use crate::crypto::{Base64Alphabet, base64_decode, base64_encode};
+
+/// The reasons a key or a signature is rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    InvalidKey,
+    InvalidSignature,
+    /// The signature is well-formed but was not made with the key over
+    /// the data.
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::InvalidKey => f.write_str("invalid key"),
+            SignatureError::InvalidSignature => f.write_str("invalid signature"),
+            SignatureError::Mismatch => f.write_str("signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+fn decode<const N: usize>(text: &str, err: SignatureError) -> Result<[u8; N], SignatureError> {
+    base64_decode(Base64Alphabet::Standard, text)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(err)
+}
+
+/// The secret key packages are signed with.
+pub struct SigningKey(ed25519_dalek::SigningKey);
+
+impl SigningKey {
+    pub fn generate() -> Self {
+        let mut seed = [0; 32];
+        getrandom::fill(&mut seed).expect("the OS provides randomness");
+        Self(ed25519_dalek::SigningKey::from_bytes(&seed))
+    }
+
+    pub fn from_base64(text: &str) -> Result<Self, SignatureError> {
+        let seed = decode(text, SignatureError::InvalidKey)?;
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64_encode(Base64Alphabet::Standard, true, self.0.as_bytes())
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.0.verifying_key())
+    }
+
+    /// Signs `data`, returning the signature as base64.
+    pub fn sign(&self, data: &[u8]) -> String {
+        base64_encode(
+            Base64Alphabet::Standard,
+            true,
+            &self.0.sign(data).to_bytes(),
+        )
+    }
+}
+
+/// The public key signatures are verified with, written as base64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey(ed25519_dalek::VerifyingKey);
+
+impl PublicKey {
+    /// Checks that `signature`, as base64, was made over `data` with the
+    /// secret key of this public key.
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<(), SignatureError> {
+        let signature =
+            Signature::from_bytes(&decode(signature, SignatureError::InvalidSignature)?);
+        self.0
+            .verify(data, &signature)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = SignatureError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bytes = decode(text, SignatureError::InvalidKey)?;
+        ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+            .map(Self)
+            .map_err(|_| SignatureError::InvalidKey)
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&base64_encode(
+            Base64Alphabet::Standard,
+            true,
+            self.0.as_bytes(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE: &[u8] = b"\0asm package";
+
+    #[test]
+    fn verifies_signed_packages() {
+        let key = SigningKey::generate();
+        let signature = key.sign(PACKAGE);
+
+        assert_eq!(key.public_key().verify(PACKAGE, &signature), Ok(()));
+    }
+
+    #[test]
+    fn rejects_tampered_packages() {
+        let key = SigningKey::generate();
+        let signature = key.sign(PACKAGE);
+        let mut tampered = PACKAGE.to_vec();
+        tampered[0] ^= 1;
+
+        assert_eq!(
+            key.public_key().verify(&tampered, &signature),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            SigningKey::generate()
+                .public_key()
+                .verify(PACKAGE, &signature),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        let public_key = SigningKey::generate().public_key();
+
+        assert_eq!(
+            public_key.verify(PACKAGE, "not base64!"),
+            Err(SignatureError::InvalidSignature)
+        );
+        assert_eq!(
+            public_key.verify(PACKAGE, "c2hvcnQ="),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn round_trips_keys_as_base64() {
+        let key = SigningKey::generate();
+        let restored = SigningKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(restored.public_key(), key.public_key());
+
+        let public_key = key.public_key();
+        assert_eq!(public_key.to_string().parse(), Ok(public_key));
+        assert_eq!(
+            "c2hvcnQ=".parse::<PublicKey>(),
+            Err(SignatureError::InvalidKey)
+        );
+    }
+}
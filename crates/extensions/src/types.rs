@@ -14,6 +14,7 @@ pub struct Page<T> {
 
 pub type SeriesPage = Page<Series>;
 pub type EpisodesPage = Page<Episode>;
+pub type FeedPage = Page<FeedItem>;
 
 pub struct Series {
     pub id: String,
@@ -21,36 +22,277 @@ pub struct Series {
     pub poster_resource: Option<MediaResource>,
     pub synopsis: Option<String>,
     pub r#type: Option<String>,
+    pub alternative_titles: Vec<String>,
+    pub genres: Vec<String>,
+    pub status: Option<SeriesStatus>,
+    pub start_year: Option<u16>,
+    pub end_year: Option<u16>,
+    pub total_episodes: Option<u16>,
+    pub rating: Option<f32>,
+    pub studios: Vec<String>,
+    pub banner_resource: Option<MediaResource>,
+    pub external_ids: Vec<ExternalId>,
+}
+
+pub enum SeriesStatus {
+    Upcoming,
+    Airing,
+    Finished,
+    Hiatus,
+    Cancelled,
+}
+
+pub enum ExternalId {
+    Anilist(u32),
+    MyAnimeList(u32),
+    Tmdb(u32),
 }
 
 pub struct Episode {
     pub id: String,
-    pub number: u16,
+    pub number: Option<f32>,
+    pub kind: EpisodeKind,
+    pub season: Option<Season>,
+    pub air_date: Option<String>,
+    pub duration: Option<u32>,
     pub title: Option<String>,
     pub thumbnail_resource: Option<MediaResource>,
     pub description: Option<String>,
 }
 
+pub enum EpisodeKind {
+    Regular,
+    Special,
+    Recap,
+    Ova,
+    Movie,
+}
+
+pub struct Season {
+    pub id: String,
+    pub number: Option<u16>,
+    pub title: Option<String>,
+}
+
 type Resolution = (u16, u16);
 
 pub struct Video {
     pub media_resource: MediaResource,
     pub server: String,
     pub resolution: Resolution,
+    pub subtitles: Vec<Subtitle>,
+    pub audio_language: Option<String>,
+    pub is_dub: bool,
+    pub has_hardsubs: bool,
+    pub audio_tracks: Vec<AudioTrack>,
+}
+
+impl Video {
+    /// Whether `other` can stand in for this video when it fails to load,
+    /// i.e. it has the same audio language, dub and hardsubs and resolution.
+    pub fn is_alternative_of(&self, other: &Video) -> bool {
+        self.audio_language == other.audio_language
+            && self.is_dub == other.is_dub
+            && self.has_hardsubs == other.has_hardsubs
+            && self.resolution == other.resolution
+    }
+}
+
+/// Groups the indices of `videos` that are alternatives of each other,
+/// keeping their order.
+pub fn alternative_groups(videos: &[Video]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, video) in videos.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|group| videos[group[0]].is_alternative_of(video))
+        {
+            Some(group) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+    groups
 }
 
+pub struct AudioTrack {
+    pub language: Option<String>,
+    pub label: String,
+    pub is_dub: bool,
+    pub media_resource: MediaResource,
+}
+
+pub enum SubtitleFormat {
+    Vtt,
+    Srt,
+    Ass,
+}
+
+pub struct Subtitle {
+    pub language: Option<String>,
+    pub label: String,
+    pub format: SubtitleFormat,
+    pub media_resource: MediaResource,
+}
+
+#[derive(Debug, Clone)]
 pub struct Filter {
     pub id: String,
     pub display_name: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct RangeBounds {
+    pub min: f64,
+    pub max: f64,
+    pub step: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterKind {
+    MultiSelect(Vec<Filter>),
+    SingleSelect(Vec<Filter>),
+    TriState(Vec<Filter>),
+    Range(RangeBounds),
+    Sort(Vec<Filter>),
+    Text { placeholder: Option<String> },
+}
+
+#[derive(Debug, Clone)]
 pub struct FilterCategory {
     pub id: String,
     pub display_name: String,
-    pub filters: Vec<Filter>,
+    pub kind: FilterKind,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
 }
 
+#[derive(Debug, Clone)]
+pub enum FilterValue {
+    Selected(Vec<String>),
+    TriState {
+        included: Vec<String>,
+        excluded: Vec<String>,
+    },
+    Range {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Sort {
+        id: String,
+        direction: SortDirection,
+    },
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
 pub struct SearchFilter {
     pub id: String,
-    pub values: Vec<String>,
+    pub value: FilterValue,
+}
+
+pub struct Feed {
+    pub id: String,
+    pub display_name: String,
+}
+
+pub enum FeedItem {
+    Series(Series),
+    Episode { series: Series, episode: Episode },
+}
+
+#[derive(Debug, Clone)]
+pub struct SettingOption {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingKind {
+    Text {
+        default: Option<String>,
+        placeholder: Option<String>,
+    },
+    Secret,
+    Toggle {
+        default: bool,
+    },
+    Choice {
+        options: Vec<SettingOption>,
+        default: Option<String>,
+    },
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        default: Option<f64>,
+    },
+}
+
+impl SettingKind {
+    pub fn default_value(&self) -> Option<SettingValue> {
+        match self {
+            SettingKind::Text { default, .. } => default.clone().map(SettingValue::Text),
+            SettingKind::Secret => None,
+            SettingKind::Toggle { default } => Some(SettingValue::Toggle(*default)),
+            SettingKind::Choice { default, .. } => default.clone().map(SettingValue::Choice),
+            SettingKind::Number { default, .. } => default.map(SettingValue::Number),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting {
+    pub id: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub kind: SettingKind,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Text(String),
+    Secret(String),
+    Toggle(bool),
+    Choice(String),
+    Number(f64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(audio_language: Option<&str>, is_dub: bool, resolution: Resolution) -> Video {
+        Video {
+            media_resource: MediaResource::MagnetUri("magnet:?xt=urn:btih:0".to_owned()),
+            server: "server".to_owned(),
+            resolution,
+            subtitles: Vec::new(),
+            audio_language: audio_language.map(str::to_owned),
+            is_dub,
+            has_hardsubs: false,
+            audio_tracks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn groups_only_videos_of_the_same_variant() {
+        let videos = [
+            video(Some("ja"), false, (1920, 1080)),
+            video(Some("en"), true, (1920, 1080)),
+            video(Some("ja"), false, (1920, 1080)),
+            video(Some("ja"), false, (1280, 720)),
+            video(Some("en"), true, (1920, 1080)),
+            video(None, false, (1920, 1080)),
+        ];
+
+        assert_eq!(
+            alternative_groups(&videos),
+            vec![vec![0, 2], vec![1, 4], vec![3], vec![5]]
+        );
+    }
 }
@@ -1,11 +1,33 @@
 const COMMANDS: &[&str] = &[
     "get_extension_metadata",
+    "get_extension_manifest",
     "load_extension",
     "get_filters",
     "search",
     "get_series_info",
     "get_series_episodes",
+    "get_series_seasons",
+    "get_season_episodes",
     "get_series_videos",
+    "get_feeds",
+    "get_feed",
+    "get_settings",
+    "set_setting",
+    "clear_session",
+    "get_logs",
+    "clear_logs",
+    "stream_logs",
+    "stop_log_stream",
+    "get_log_filter",
+    "set_log_filter",
+    "set_http_inspector_enabled",
+    "get_http_exchanges",
+    "clear_http_exchanges",
+    "export_har",
+    "browse_repository",
+    "get_installed_extensions",
+    "install_extension",
+    "uninstall_extension",
 ];
 
 fn main() {
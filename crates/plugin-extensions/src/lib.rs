@@ -1,19 +1,38 @@
+mod login;
+mod settings;
 mod types;
 mod utils;
 
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
-use nero_extensions::{WasmExtension, host::WasmHost};
-use nero_processor::Processor;
+use nero_extensions::{
+    WasmExtension,
+    cookies::CookieJar,
+    host::WasmHost,
+    inspector::HttpInspector,
+    manifest::Manifest,
+    repository::{ExtensionStore, RepositoryClient, RepositoryIndex},
+    types::alternative_groups,
+};
+use nero_processor::{Processor, ProcessorConfig, ProcessorHandle};
+use nero_wasi_logging::{LogBuffer, LogFiles, LogFilter, LogSubscription};
+use semver::Version;
 use tauri::{
-    Manager, Result, Runtime, State,
+    Manager, Result, RunEvent, Runtime, State,
+    ipc::Channel,
     plugin::{self, TauriPlugin},
 };
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use url::Url;
 use wasm_metadata::{Metadata, Payload};
 
 use crate::{
-    types::{EpisodesPage, FilterCategory, SearchFilter, Series, SeriesPage, Video},
+    login::WebviewLogin,
+    settings::SettingsStore,
+    types::{
+        EpisodesPage, Feed, FeedPage, FilterCategory, HttpExchange, InstalledExtension, LogEntry,
+        SearchFilter, Season, Series, SeriesPage, Setting, SettingValue, Video,
+    },
     utils::AyncTryIntoWithState,
 };
 
@@ -21,8 +40,22 @@ struct PluginState {
     host: WasmHost,
     extension: RwLock<Option<WasmExtension>>,
     processor: Arc<Processor>,
+    processor_handle: Mutex<Option<ProcessorHandle>>,
+    settings_store: SettingsStore,
+    cookies_dir: PathBuf,
+    logs: Arc<LogBuffer>,
+    /// The log streams of the webview, by the id of their channel.
+    log_streams: Mutex<HashMap<u32, LogSubscription>>,
+    http_inspector: HttpInspector,
+    extension_store: ExtensionStore,
+    /// The version of the app, which releases in repositories may require.
+    host_version: Version,
 }
 
+const PROCESSOR_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of messages kept in memory for each extension.
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
 #[tauri::command]
 #[tracing::instrument]
 async fn get_extension_metadata(file_path: String) -> Result<Metadata> {
@@ -34,10 +67,37 @@ async fn get_extension_metadata(file_path: String) -> Result<Metadata> {
     }
 }
 
+/// Reads the capabilities an extension declares, so they can be reviewed
+/// before loading it.
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_extension_manifest(
+    state: State<'_, PluginState>,
+    file_path: String,
+) -> Result<Option<Manifest>> {
+    let bytes = tokio::fs::read(file_path).await?;
+    Ok(state.host.read_manifest(&bytes)?)
+}
+
 #[tauri::command]
 #[tracing::instrument(skip(state))]
 async fn load_extension(state: State<'_, PluginState>, file_path: String) -> Result<()> {
-    let extension = state.host.load_extension_async(file_path).await?;
+    let mut extension = state.host.load_extension_async(file_path).await?;
+
+    let key = SettingsStore::extension_key(&extension);
+    let jar = CookieJar::open(state.cookies_dir.join(format!("{key}.json"))).await?;
+    extension.set_cookie_jar(jar);
+
+    if extension.has_settings() {
+        let settings = extension.settings().await?;
+        let values = state.settings_store.load(&key, settings).await?;
+        for (id, value) in values {
+            if let Err(err) = extension.set_setting(&id, Some(value)).await {
+                tracing::warn!("ignoring stored value of setting `{id}`: {err}");
+            }
+        }
+    }
+
     state.extension.write().await.replace(extension);
 
     Ok(())
@@ -101,6 +161,37 @@ async fn get_series_episodes(
     Ok(page.async_try_into_with_state(&state).await?)
 }
 
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_series_seasons(state: State<'_, PluginState>, series_id: &str) -> Result<Vec<Season>> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let seasons = extension.get_series_seasons(series_id).await?;
+    Ok(seasons.into_iter().map(Into::into).collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_season_episodes(
+    state: State<'_, PluginState>,
+    series_id: &str,
+    season_id: &str,
+    page: Option<u16>,
+) -> Result<EpisodesPage> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let page = extension
+        .get_season_episodes(series_id, season_id, page)
+        .await?;
+    Ok(page.async_try_into_with_state(&state).await?)
+}
+
 #[tauri::command]
 #[tracing::instrument(skip(state))]
 async fn get_series_videos(
@@ -114,50 +205,416 @@ async fn get_series_videos(
         .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
 
     let extension_videos = extension.get_series_videos(series_id, episode_id).await?;
+    let groups = alternative_groups(&extension_videos);
 
     let mut videos = Vec::with_capacity(extension_videos.len());
     for video in extension_videos {
         videos.push(video.async_try_into_with_state(&state).await?);
     }
+
+    // Only videos of the same language and variant fall back to each other,
+    // so that playback never silently switches from a sub to a dub.
+    for group in groups {
+        let urls = group
+            .into_iter()
+            .map(|i| videos[i].url().clone())
+            .collect::<Vec<_>>();
+        state.processor.link_video_alternatives(&urls).await;
+    }
+
     Ok(videos)
 }
 
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_feeds(state: State<'_, PluginState>) -> Result<Vec<Feed>> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    if !extension.has_feeds() {
+        return Ok(Vec::new());
+    }
+
+    let feeds = extension.feeds().await?;
+    Ok(feeds.into_iter().map(Into::into).collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_feed(
+    state: State<'_, PluginState>,
+    feed_id: &str,
+    page: Option<u16>,
+) -> Result<FeedPage> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let page = extension.get_feed(feed_id, page).await?;
+    Ok(page.async_try_into_with_state(&state).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_settings(state: State<'_, PluginState>) -> Result<Vec<Setting>> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let mut values = extension.setting_values().await;
+    let settings = extension.settings().await?;
+    Ok(settings
+        .iter()
+        .map(|setting| Setting::new(setting.clone(), values.remove(&setting.id)))
+        .collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state, value))]
+async fn set_setting(
+    state: State<'_, PluginState>,
+    id: &str,
+    value: Option<SettingValue>,
+) -> Result<()> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let value = value.map(Into::into);
+    extension.set_setting(id, value.clone()).await?;
+
+    let key = SettingsStore::extension_key(extension);
+    let setting = extension
+        .settings()
+        .await?
+        .iter()
+        .find(|setting| setting.id == id)
+        .ok_or_else(|| anyhow::anyhow!("unknown setting `{id}`"))?;
+    state.settings_store.save(&key, setting, value).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn clear_session(state: State<'_, PluginState>) -> Result<()> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    extension.cookie_jar().clear().await?;
+    Ok(())
+}
+
+/// Returns `extension`, or the id of the loaded extension if it is `None`.
+async fn log_extension_id(state: &PluginState, extension: Option<String>) -> Result<String> {
+    if let Some(extension) = extension {
+        return Ok(extension);
+    }
+
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+    Ok(extension.id().to_owned())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_logs(
+    state: State<'_, PluginState>,
+    extension: Option<String>,
+) -> Result<Vec<LogEntry>> {
+    let extension = log_extension_id(&state, extension).await?;
+    Ok(state
+        .logs
+        .records(&extension)
+        .iter()
+        .map(Into::into)
+        .collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn clear_logs(state: State<'_, PluginState>, extension: Option<String>) -> Result<()> {
+    let extension = log_extension_id(&state, extension).await?;
+    state.logs.clear(&extension);
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_log_filter(state: State<'_, PluginState>) -> Result<String> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    Ok(extension.log_filter().to_string())
+}
+
+/// Sets the log filter of the loaded extension, written like
+/// `warn,http=debug`.
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn set_log_filter(state: State<'_, PluginState>, filter: String) -> Result<()> {
+    let guard = state.extension.read().await;
+    let extension = guard
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("extension not loaded"))?;
+
+    let filter = filter.parse::<LogFilter>().map_err(anyhow::Error::from)?;
+    extension.set_log_filter(filter);
+    Ok(())
+}
+
+/// Sends every message logged from now on by any extension to `channel`,
+/// until [`stop_log_stream`] is called with its id or sending fails.
+#[tauri::command]
+#[tracing::instrument(skip(state, channel))]
+async fn stream_logs(state: State<'_, PluginState>, channel: Channel<LogEntry>) -> Result<()> {
+    let id = channel.id();
+    let subscription = state
+        .logs
+        .subscribe(move |record| channel.send(record.into()).is_ok());
+    state.log_streams.lock().await.insert(id, subscription);
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn stop_log_stream(state: State<'_, PluginState>, channel_id: u32) -> Result<()> {
+    state.log_streams.lock().await.remove(&channel_id);
+    Ok(())
+}
+
+/// Starts or stops recording the HTTP traffic of extensions.
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn set_http_inspector_enabled(state: State<'_, PluginState>, enabled: bool) -> Result<()> {
+    state.http_inspector.set_enabled(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_http_exchanges(state: State<'_, PluginState>) -> Result<Vec<HttpExchange>> {
+    Ok(state
+        .http_inspector
+        .exchanges()
+        .iter()
+        .map(Into::into)
+        .collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn clear_http_exchanges(state: State<'_, PluginState>) -> Result<()> {
+    state.http_inspector.clear();
+    Ok(())
+}
+
+/// Writes the recorded HTTP traffic to `file_path` as a HAR file.
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn export_har(state: State<'_, PluginState>, file_path: String) -> Result<()> {
+    let har = serde_json::to_vec_pretty(&state.http_inspector.to_har())?;
+    tokio::fs::write(file_path, har).await?;
+    Ok(())
+}
+
+fn repository_client(
+    url: &str,
+    public_key: Option<&str>,
+    allow_unsigned: bool,
+) -> anyhow::Result<RepositoryClient> {
+    let mut client = RepositoryClient::new(Url::parse(url)?);
+    if let Some(public_key) = public_key {
+        client = client.with_public_key(public_key.parse()?);
+    }
+    if allow_unsigned {
+        client = client.with_unsigned_allowed();
+    }
+    Ok(client)
+}
+
+/// Fetches the index of the repository at `url`.
+#[tauri::command]
+#[tracing::instrument]
+async fn browse_repository(url: String) -> Result<RepositoryIndex> {
+    Ok(repository_client(&url, None, false)?.index().await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn get_installed_extensions(
+    state: State<'_, PluginState>,
+) -> Result<Vec<InstalledExtension>> {
+    let installed = state.extension_store.installed().await?;
+    Ok(installed
+        .into_iter()
+        .map(|extension| InstalledExtension::new(extension, &state.extension_store))
+        .collect())
+}
+
+/// Installs the release `version` of the extension `id` from the
+/// repository at `url`, or its latest release working with this app.
+///
+/// The package must be signed with the secret key of `public_key`, unless
+/// `allow_unsigned` is set and no public key is given.
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn install_extension(
+    state: State<'_, PluginState>,
+    url: String,
+    public_key: Option<String>,
+    allow_unsigned: Option<bool>,
+    id: String,
+    version: Option<String>,
+) -> Result<InstalledExtension> {
+    let client = repository_client(
+        &url,
+        public_key.as_deref(),
+        allow_unsigned.unwrap_or_default(),
+    )?;
+    let version = version
+        .map(|version| version.parse::<Version>())
+        .transpose()
+        .map_err(anyhow::Error::from)?;
+    let installed = state
+        .extension_store
+        .install(
+            &client,
+            &state.host,
+            &state.host_version,
+            &id,
+            version.as_ref(),
+        )
+        .await?;
+    Ok(InstalledExtension::new(installed, &state.extension_store))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state))]
+async fn uninstall_extension(state: State<'_, PluginState>, id: String) -> Result<()> {
+    state.extension_store.uninstall(&id).await?;
+    Ok(())
+}
+
 pub struct Builder {
     processor_addr: SocketAddr,
+    processor_config: ProcessorConfig,
+    log_files: bool,
 }
 
 impl Builder {
     pub fn new(processor_addr: SocketAddr) -> Self {
-        Self { processor_addr }
+        Self {
+            processor_addr,
+            processor_config: ProcessorConfig::default(),
+            log_files: false,
+        }
     }
 
-    pub fn build<R: Runtime>(self) -> TauriPlugin<R> {
-        let state = PluginState {
-            host: WasmHost::default(),
-            extension: RwLock::new(None),
-            processor: Arc::new(Processor::new(self.processor_addr)),
-        };
+    pub fn processor_config(mut self, config: ProcessorConfig) -> Self {
+        self.processor_config = config;
+        self
+    }
+
+    /// Also writes the messages logged by extensions to rotating files in
+    /// the `extensions` directory of the app log directory.
+    pub fn log_files(mut self, enabled: bool) -> Self {
+        self.log_files = enabled;
+        self
+    }
 
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R> {
         plugin::Builder::new("nero-extensions")
-            .setup(|app, _| {
-                let processor = state.processor.clone();
-                tauri::async_runtime::spawn(async move {
-                    processor
-                        .run()
-                        .await
-                        .expect("Unable to spawn internal extension processor server")
-                });
+            .setup(move |app, _| {
+                let processor = Processor::with_config(self.processor_addr, self.processor_config)?;
+                let settings_dir = app.path().app_config_dir()?.join("extension-settings");
+                let cookies_dir = app.path().app_data_dir()?.join("extension-cookies");
+                let extensions_dir = app.path().app_data_dir()?.join("extensions");
+                let logs = Arc::new(LogBuffer::new(LOG_BUFFER_CAPACITY));
+                let http_inspector = HttpInspector::default();
+
+                let mut host = WasmHost::default()
+                    .with_login_handler(WebviewLogin::new(app.clone()))
+                    .with_log_sink(logs.clone())
+                    .with_http_inspector(http_inspector.clone());
+                if self.log_files {
+                    let dir = app.path().app_log_dir()?.join("extensions");
+                    host = host.with_log_sink(Arc::new(LogFiles::new(dir)));
+                }
+
+                let state = PluginState {
+                    host,
+                    extension: RwLock::new(None),
+                    processor: Arc::new(processor),
+                    processor_handle: Mutex::new(None),
+                    settings_store: SettingsStore::new(settings_dir),
+                    cookies_dir,
+                    logs,
+                    log_streams: Mutex::default(),
+                    http_inspector,
+                    extension_store: ExtensionStore::new(extensions_dir),
+                    host_version: app.package_info().version.clone(),
+                };
+
+                let handle = tauri::async_runtime::block_on(state.processor.start())?;
+                tracing::debug!("extension processor listening on {}", handle.local_addr());
+                state.processor_handle.try_lock()?.replace(handle);
                 app.manage(state);
                 Ok(())
             })
+            .on_event(|app, event| {
+                if let RunEvent::Exit = event {
+                    let state = app.state::<PluginState>();
+                    tauri::async_runtime::block_on(async {
+                        if let Some(handle) = state.processor_handle.lock().await.take()
+                            && let Err(err) = handle.shutdown(PROCESSOR_DRAIN_TIMEOUT).await
+                        {
+                            tracing::error!("failed to shut down extension processor: {err}");
+                        }
+                    });
+                }
+            })
             .invoke_handler(tauri::generate_handler![
                 get_extension_metadata,
+                get_extension_manifest,
                 load_extension,
                 get_filters,
                 search,
                 get_series_info,
                 get_series_episodes,
-                get_series_videos
+                get_series_seasons,
+                get_season_episodes,
+                get_series_videos,
+                get_feeds,
+                get_feed,
+                get_settings,
+                set_setting,
+                clear_session,
+                get_logs,
+                clear_logs,
+                stream_logs,
+                stop_log_stream,
+                get_log_filter,
+                set_log_filter,
+                set_http_inspector_enabled,
+                get_http_exchanges,
+                clear_http_exchanges,
+                export_har,
+                browse_repository,
+                get_installed_extensions,
+                install_extension,
+                uninstall_extension
             ])
             .build()
     }
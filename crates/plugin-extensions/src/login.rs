@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use nero_extensions::login::{LoginError, LoginFuture, LoginHandler};
+use tauri::{AppHandle, Runtime, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tokio::sync::mpsc;
+use url::Url;
+
+/// Answers login requests of extensions by opening a webview window.
+pub struct WebviewLogin<R: Runtime> {
+    app: AppHandle<R>,
+    next_id: AtomicU32,
+}
+
+impl<R: Runtime> WebviewLogin<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self {
+            app,
+            next_id: AtomicU32::new(0),
+        }
+    }
+}
+
+enum LoginEvent {
+    Navigated(Url),
+    Closed,
+}
+
+impl<R: Runtime> LoginHandler for WebviewLogin<R> {
+    fn login(&self, url: Url, target_url: Url) -> LoginFuture<'_> {
+        Box::pin(async move {
+            let label = format!(
+                "extension-login-{}",
+                self.next_id.fetch_add(1, Ordering::Relaxed)
+            );
+            let (tx, mut rx) = mpsc::unbounded_channel();
+
+            let navigation_tx = tx.clone();
+            let window = WebviewWindowBuilder::new(&self.app, label, WebviewUrl::External(url))
+                .title("Sign in")
+                .on_navigation(move |url| {
+                    let _ = navigation_tx.send(LoginEvent::Navigated(url.clone()));
+                    true
+                })
+                .build()
+                .map_err(|err| LoginError::Other(err.to_string()))?;
+            window.on_window_event(move |event| {
+                if let WindowEvent::Destroyed = event {
+                    let _ = tx.send(LoginEvent::Closed);
+                }
+            });
+
+            // Cookies are read outside of the navigation handler, which runs
+            // on the main thread where reading them could deadlock.
+            while let Some(event) = rx.recv().await {
+                match event {
+                    LoginEvent::Navigated(url) if url.as_str().starts_with(target_url.as_str()) => {
+                        let cookies = window
+                            .cookies_for_url(target_url)
+                            .map_err(|err| LoginError::Other(err.to_string()))?;
+                        let _ = window.close();
+                        return Ok(cookies.iter().map(ToString::to_string).collect());
+                    }
+                    LoginEvent::Navigated(_) => {}
+                    LoginEvent::Closed => break,
+                }
+            }
+
+            Err(LoginError::Cancelled)
+        })
+    }
+}
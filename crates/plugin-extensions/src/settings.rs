@@ -0,0 +1,130 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Result;
+use nero_extensions::{
+    WasmExtension,
+    types::{Setting, SettingKind, SettingValue},
+};
+use nero_wasi_logging::extension_file_stem;
+
+use crate::types;
+
+const KEYRING_SERVICE: &str = "nero-extensions";
+
+/// Persists the setting values of extensions.
+///
+/// Secrets are kept in the platform keyring, every other value is written
+/// to a JSON file per extension under `dir`.
+pub struct SettingsStore {
+    dir: PathBuf,
+}
+
+impl SettingsStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the key under which the settings of an extension are stored,
+    /// derived from its id like the names of its log files. Its cookie jar
+    /// is stored under the same key.
+    pub fn extension_key(extension: &WasmExtension) -> String {
+        extension_file_stem(extension.id())
+    }
+
+    fn file_path(&self, extension: &str) -> PathBuf {
+        self.dir.join(format!("{extension}.json"))
+    }
+
+    async fn read_file(&self, extension: &str) -> Result<HashMap<String, types::SettingValue>> {
+        match tokio::fs::read(self.file_path(extension)).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn write_file(
+        &self,
+        extension: &str,
+        values: &HashMap<String, types::SettingValue>,
+    ) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.file_path(extension), serde_json::to_vec(values)?).await?;
+        Ok(())
+    }
+
+    /// Loads the stored values of `settings`.
+    pub async fn load(
+        &self,
+        extension: &str,
+        settings: &[Setting],
+    ) -> Result<HashMap<String, SettingValue>> {
+        let mut stored = self.read_file(extension).await?;
+        let mut values = HashMap::new();
+
+        for setting in settings {
+            let value = match setting.kind {
+                SettingKind::Secret => get_secret(extension, &setting.id)
+                    .await?
+                    .map(SettingValue::Secret),
+                _ => stored.remove(&setting.id).map(Into::into),
+            };
+            if let Some(value) = value {
+                values.insert(setting.id.clone(), value);
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Stores `value` for `setting`, or removes the stored value if `None`.
+    pub async fn save(
+        &self,
+        extension: &str,
+        setting: &Setting,
+        value: Option<SettingValue>,
+    ) -> Result<()> {
+        if let SettingKind::Secret = setting.kind {
+            let secret = match value {
+                Some(SettingValue::Secret(secret)) => Some(secret),
+                _ => None,
+            };
+            return set_secret(extension, &setting.id, secret).await;
+        }
+
+        let mut stored = self.read_file(extension).await?;
+        match value {
+            Some(value) => stored.insert(setting.id.clone(), value.into()),
+            None => stored.remove(&setting.id),
+        };
+        self.write_file(extension, &stored).await
+    }
+}
+
+fn keyring_entry(extension: &str, id: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("{extension}/{id}"))
+}
+
+async fn get_secret(extension: &str, id: &str) -> Result<Option<String>> {
+    let entry = keyring_entry(extension, id)?;
+    let secret = tokio::task::spawn_blocking(move || match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err),
+    })
+    .await??;
+    Ok(secret)
+}
+
+async fn set_secret(extension: &str, id: &str, secret: Option<String>) -> Result<()> {
+    let entry = keyring_entry(extension, id)?;
+    tokio::task::spawn_blocking(move || match secret {
+        Some(secret) => entry.set_password(&secret),
+        None => match entry.delete_credential() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            res => res,
+        },
+    })
+    .await??;
+    Ok(())
+}
@@ -1,9 +1,15 @@
+use std::path::PathBuf;
+
 use anyhow::bail;
-use nero_extensions::types::MediaResource;
+use nero_extensions::{repository::ExtensionStore, types::MediaResource};
+use nero_processor::SubtitleFormat as ProcessorSubtitleFormat;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::{PluginState, utils::AsyncTryFromWithState};
+use crate::{
+    PluginState,
+    utils::{AsyncTryFromWithState, AyncTryIntoWithState},
+};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +39,7 @@ where
 
 pub type SeriesPage = Page<Series>;
 pub type EpisodesPage = Page<Episode>;
+pub type FeedPage = Page<FeedItem>;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -42,6 +49,60 @@ pub struct Series {
     poster_url: Option<Url>,
     synopsis: Option<String>,
     r#type: Option<String>,
+    alternative_titles: Vec<String>,
+    genres: Vec<String>,
+    status: Option<SeriesStatus>,
+    start_year: Option<u16>,
+    end_year: Option<u16>,
+    total_episodes: Option<u16>,
+    rating: Option<f32>,
+    studios: Vec<String>,
+    banner_url: Option<Url>,
+    external_ids: ExternalIds,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesStatus {
+    Upcoming,
+    Airing,
+    Finished,
+    Hiatus,
+    Cancelled,
+}
+
+impl From<nero_extensions::types::SeriesStatus> for SeriesStatus {
+    fn from(status: nero_extensions::types::SeriesStatus) -> Self {
+        match status {
+            nero_extensions::types::SeriesStatus::Upcoming => Self::Upcoming,
+            nero_extensions::types::SeriesStatus::Airing => Self::Airing,
+            nero_extensions::types::SeriesStatus::Finished => Self::Finished,
+            nero_extensions::types::SeriesStatus::Hiatus => Self::Hiatus,
+            nero_extensions::types::SeriesStatus::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalIds {
+    anilist: Option<u32>,
+    my_anime_list: Option<u32>,
+    tmdb: Option<u32>,
+}
+
+impl From<Vec<nero_extensions::types::ExternalId>> for ExternalIds {
+    fn from(external_ids: Vec<nero_extensions::types::ExternalId>) -> Self {
+        let mut ids = Self::default();
+        for external_id in external_ids {
+            match external_id {
+                nero_extensions::types::ExternalId::Anilist(id) => ids.anilist = Some(id),
+                nero_extensions::types::ExternalId::MyAnimeList(id) => ids.my_anime_list = Some(id),
+                nero_extensions::types::ExternalId::Tmdb(id) => ids.tmdb = Some(id),
+            }
+        }
+        ids
+    }
 }
 
 impl AsyncTryFromWithState<nero_extensions::types::Series> for Series {
@@ -63,6 +124,24 @@ impl AsyncTryFromWithState<nero_extensions::types::Series> for Series {
             },
             synopsis: series.synopsis,
             r#type: series.r#type,
+            alternative_titles: series.alternative_titles,
+            genres: series.genres,
+            status: series.status.map(Into::into),
+            start_year: series.start_year,
+            end_year: series.end_year,
+            total_episodes: series.total_episodes,
+            rating: series.rating,
+            studios: series.studios,
+            banner_url: match series.banner_resource {
+                Some(MediaResource::HttpRequest(req)) => {
+                    Some(state.processor.register_image_request(*req).await?)
+                }
+                Some(MediaResource::MagnetUri(_)) => {
+                    bail!("Magnet URIs are not supported for images");
+                }
+                None => None,
+            },
+            external_ids: series.external_ids.into(),
         })
     }
 }
@@ -71,12 +150,55 @@ impl AsyncTryFromWithState<nero_extensions::types::Series> for Series {
 #[serde(rename_all = "camelCase")]
 pub struct Episode {
     id: String,
-    number: u16,
+    number: Option<f32>,
+    kind: EpisodeKind,
+    season: Option<Season>,
+    air_date: Option<String>,
+    duration: Option<u32>,
     title: Option<String>,
     thumbnail_url: Option<Url>,
     description: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EpisodeKind {
+    Regular,
+    Special,
+    Recap,
+    Ova,
+    Movie,
+}
+
+impl From<nero_extensions::types::EpisodeKind> for EpisodeKind {
+    fn from(kind: nero_extensions::types::EpisodeKind) -> Self {
+        match kind {
+            nero_extensions::types::EpisodeKind::Regular => Self::Regular,
+            nero_extensions::types::EpisodeKind::Special => Self::Special,
+            nero_extensions::types::EpisodeKind::Recap => Self::Recap,
+            nero_extensions::types::EpisodeKind::Ova => Self::Ova,
+            nero_extensions::types::EpisodeKind::Movie => Self::Movie,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Season {
+    id: String,
+    number: Option<u16>,
+    title: Option<String>,
+}
+
+impl From<nero_extensions::types::Season> for Season {
+    fn from(season: nero_extensions::types::Season) -> Self {
+        Self {
+            id: season.id,
+            number: season.number,
+            title: season.title,
+        }
+    }
+}
+
 impl AsyncTryFromWithState<nero_extensions::types::Episode> for Episode {
     async fn async_try_from_with_state(
         episode: nero_extensions::types::Episode,
@@ -85,6 +207,10 @@ impl AsyncTryFromWithState<nero_extensions::types::Episode> for Episode {
         Ok(Self {
             id: episode.id,
             number: episode.number,
+            kind: episode.kind.into(),
+            season: episode.season.map(Into::into),
+            air_date: episode.air_date,
+            duration: episode.duration,
             title: episode.title,
             thumbnail_url: match episode.thumbnail_resource {
                 Some(MediaResource::HttpRequest(req)) => {
@@ -103,10 +229,22 @@ impl AsyncTryFromWithState<nero_extensions::types::Episode> for Episode {
 type Resolution = (u16, u16);
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Video {
     url: Url,
     server: String,
     resolution: Resolution,
+    subtitles: Vec<Subtitle>,
+    audio_language: Option<String>,
+    is_dub: bool,
+    has_hardsubs: bool,
+    audio_tracks: Vec<AudioTrack>,
+}
+
+impl Video {
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
 }
 
 impl AsyncTryFromWithState<nero_extensions::types::Video> for Video {
@@ -123,6 +261,101 @@ impl AsyncTryFromWithState<nero_extensions::types::Video> for Video {
             },
             server: video.server,
             resolution: video.resolution,
+            subtitles: {
+                let mut subtitles = Vec::with_capacity(video.subtitles.len());
+                for subtitle in video.subtitles {
+                    subtitles.push(subtitle.async_try_into_with_state(state).await?);
+                }
+                subtitles
+            },
+            audio_language: video.audio_language,
+            is_dub: video.is_dub,
+            has_hardsubs: video.has_hardsubs,
+            audio_tracks: {
+                let mut audio_tracks = Vec::with_capacity(video.audio_tracks.len());
+                for audio_track in video.audio_tracks {
+                    audio_tracks.push(audio_track.async_try_into_with_state(state).await?);
+                }
+                audio_tracks
+            },
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTrack {
+    url: Url,
+    language: Option<String>,
+    label: String,
+    is_dub: bool,
+}
+
+impl AsyncTryFromWithState<nero_extensions::types::AudioTrack> for AudioTrack {
+    async fn async_try_from_with_state(
+        audio_track: nero_extensions::types::AudioTrack,
+        state: &PluginState,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            url: match audio_track.media_resource {
+                MediaResource::HttpRequest(req) => {
+                    state.processor.register_audio_request(*req).await?
+                }
+                MediaResource::MagnetUri(_) => {
+                    bail!("Magnet URIs are not supported for audio tracks");
+                }
+            },
+            language: audio_track.language,
+            label: audio_track.label,
+            is_dub: audio_track.is_dub,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Vtt,
+    Ass,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Subtitle {
+    url: Url,
+    language: Option<String>,
+    label: String,
+    format: SubtitleFormat,
+}
+
+impl AsyncTryFromWithState<nero_extensions::types::Subtitle> for Subtitle {
+    async fn async_try_from_with_state(
+        subtitle: nero_extensions::types::Subtitle,
+        state: &PluginState,
+    ) -> anyhow::Result<Self> {
+        let format = match subtitle.format {
+            nero_extensions::types::SubtitleFormat::Vtt => ProcessorSubtitleFormat::Vtt,
+            nero_extensions::types::SubtitleFormat::Srt => ProcessorSubtitleFormat::Srt,
+            nero_extensions::types::SubtitleFormat::Ass => ProcessorSubtitleFormat::Ass,
+        };
+
+        Ok(Self {
+            url: match subtitle.media_resource {
+                MediaResource::HttpRequest(req) => {
+                    state
+                        .processor
+                        .register_subtitle_request(*req, format)
+                        .await?
+                }
+                MediaResource::MagnetUri(_) => {
+                    bail!("Magnet URIs are not supported for subtitles");
+                }
+            },
+            language: subtitle.language,
+            label: subtitle.label,
+            format: match format.served_format() {
+                ProcessorSubtitleFormat::Ass => SubtitleFormat::Ass,
+                _ => SubtitleFormat::Vtt,
+            },
         })
     }
 }
@@ -143,12 +376,70 @@ impl From<nero_extensions::types::Filter> for Filter {
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FilterKind {
+    MultiSelect {
+        filters: Vec<Filter>,
+    },
+    SingleSelect {
+        filters: Vec<Filter>,
+    },
+    TriState {
+        filters: Vec<Filter>,
+    },
+    Range {
+        min: f64,
+        max: f64,
+        step: Option<f64>,
+    },
+    Sort {
+        filters: Vec<Filter>,
+    },
+    Text {
+        placeholder: Option<String>,
+    },
+}
+
+impl From<nero_extensions::types::FilterKind> for FilterKind {
+    fn from(kind: nero_extensions::types::FilterKind) -> Self {
+        use nero_extensions::types::FilterKind as Kind;
+
+        let filters = |filters: Vec<nero_extensions::types::Filter>| {
+            filters.into_iter().map(Into::into).collect()
+        };
+
+        match kind {
+            Kind::MultiSelect(items) => Self::MultiSelect {
+                filters: filters(items),
+            },
+            Kind::SingleSelect(items) => Self::SingleSelect {
+                filters: filters(items),
+            },
+            Kind::TriState(items) => Self::TriState {
+                filters: filters(items),
+            },
+            Kind::Range(bounds) => Self::Range {
+                min: bounds.min,
+                max: bounds.max,
+                step: bounds.step,
+            },
+            Kind::Sort(items) => Self::Sort {
+                filters: filters(items),
+            },
+            Kind::Text { placeholder } => Self::Text { placeholder },
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FilterCategory {
     id: String,
     display_name: String,
-    filters: Vec<Filter>,
+    required: bool,
+    #[serde(flatten)]
+    kind: FilterKind,
 }
 
 impl From<nero_extensions::types::FilterCategory> for FilterCategory {
@@ -156,7 +447,62 @@ impl From<nero_extensions::types::FilterCategory> for FilterCategory {
         Self {
             id: category.id,
             display_name: category.display_name,
-            filters: category.filters.into_iter().map(Into::into).collect(),
+            required: category.required,
+            kind: category.kind.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl From<SortDirection> for nero_extensions::types::SortDirection {
+    fn from(direction: SortDirection) -> Self {
+        match direction {
+            SortDirection::Ascending => Self::Ascending,
+            SortDirection::Descending => Self::Descending,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FilterValue {
+    Selected {
+        values: Vec<String>,
+    },
+    TriState {
+        included: Vec<String>,
+        excluded: Vec<String>,
+    },
+    Range {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Sort {
+        by: String,
+        direction: SortDirection,
+    },
+    Text {
+        value: String,
+    },
+}
+
+impl From<FilterValue> for nero_extensions::types::FilterValue {
+    fn from(value: FilterValue) -> Self {
+        match value {
+            FilterValue::Selected { values } => Self::Selected(values),
+            FilterValue::TriState { included, excluded } => Self::TriState { included, excluded },
+            FilterValue::Range { min, max } => Self::Range { min, max },
+            FilterValue::Sort { by, direction } => Self::Sort {
+                id: by,
+                direction: direction.into(),
+            },
+            FilterValue::Text { value } => Self::Text(value),
         }
     }
 }
@@ -164,14 +510,384 @@ impl From<nero_extensions::types::FilterCategory> for FilterCategory {
 #[derive(Debug, Deserialize)]
 pub struct SearchFilter {
     id: String,
-    values: Vec<String>,
+    #[serde(flatten)]
+    value: FilterValue,
 }
 
 impl From<SearchFilter> for nero_extensions::types::SearchFilter {
     fn from(filter: SearchFilter) -> Self {
         Self {
             id: filter.id,
-            values: filter.values,
+            value: filter.value.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+    id: String,
+    display_name: String,
+}
+
+impl From<nero_extensions::types::Feed> for Feed {
+    fn from(feed: nero_extensions::types::Feed) -> Self {
+        Self {
+            id: feed.id,
+            display_name: feed.display_name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FeedItem {
+    Series(Series),
+    Episode { series: Series, episode: Episode },
+}
+
+impl AsyncTryFromWithState<nero_extensions::types::FeedItem> for FeedItem {
+    async fn async_try_from_with_state(
+        item: nero_extensions::types::FeedItem,
+        state: &PluginState,
+    ) -> anyhow::Result<Self> {
+        match item {
+            nero_extensions::types::FeedItem::Series(series) => {
+                Ok(Self::Series(series.async_try_into_with_state(state).await?))
+            }
+            nero_extensions::types::FeedItem::Episode { series, episode } => Ok(Self::Episode {
+                series: series.async_try_into_with_state(state).await?,
+                episode: episode.async_try_into_with_state(state).await?,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingOption {
+    id: String,
+    display_name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SettingKind {
+    Text {
+        default: Option<String>,
+        placeholder: Option<String>,
+    },
+    Secret,
+    Toggle {
+        default: bool,
+    },
+    Choice {
+        options: Vec<SettingOption>,
+        default: Option<String>,
+    },
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        default: Option<f64>,
+    },
+}
+
+impl From<nero_extensions::types::SettingKind> for SettingKind {
+    fn from(kind: nero_extensions::types::SettingKind) -> Self {
+        use nero_extensions::types::SettingKind as Kind;
+
+        match kind {
+            Kind::Text {
+                default,
+                placeholder,
+            } => Self::Text {
+                default,
+                placeholder,
+            },
+            Kind::Secret => Self::Secret,
+            Kind::Toggle { default } => Self::Toggle { default },
+            Kind::Choice { options, default } => Self::Choice {
+                options: options
+                    .into_iter()
+                    .map(|option| SettingOption {
+                        id: option.id,
+                        display_name: option.display_name,
+                    })
+                    .collect(),
+                default,
+            },
+            Kind::Number { min, max, default } => Self::Number { min, max, default },
+        }
+    }
+}
+
+/// A setting along with its current value.
+///
+/// The value of a secret is never sent to the webview; `is_set` tells
+/// whether one is stored.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Setting {
+    id: String,
+    display_name: String,
+    description: Option<String>,
+    required: bool,
+    #[serde(flatten)]
+    kind: SettingKind,
+    value: Option<SettingValue>,
+    is_set: bool,
+}
+
+impl Setting {
+    pub fn new(
+        setting: nero_extensions::types::Setting,
+        value: Option<nero_extensions::types::SettingValue>,
+    ) -> Self {
+        let is_set = value.is_some();
+        let value = value
+            .filter(|value| !matches!(value, nero_extensions::types::SettingValue::Secret(_)))
+            .map(Into::into);
+
+        Self {
+            id: setting.id,
+            display_name: setting.display_name,
+            description: setting.description,
+            required: setting.required,
+            kind: setting.kind.into(),
+            value,
+            is_set,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum SettingValue {
+    Text(String),
+    Secret(String),
+    Toggle(bool),
+    Choice(String),
+    Number(f64),
+}
+
+impl From<SettingValue> for nero_extensions::types::SettingValue {
+    fn from(value: SettingValue) -> Self {
+        match value {
+            SettingValue::Text(text) => Self::Text(text),
+            SettingValue::Secret(secret) => Self::Secret(secret),
+            SettingValue::Toggle(toggle) => Self::Toggle(toggle),
+            SettingValue::Choice(choice) => Self::Choice(choice),
+            SettingValue::Number(number) => Self::Number(number),
+        }
+    }
+}
+
+impl From<nero_extensions::types::SettingValue> for SettingValue {
+    fn from(value: nero_extensions::types::SettingValue) -> Self {
+        use nero_extensions::types::SettingValue as Value;
+
+        match value {
+            Value::Text(text) => Self::Text(text),
+            Value::Secret(secret) => Self::Secret(secret),
+            Value::Toggle(toggle) => Self::Toggle(toggle),
+            Value::Choice(choice) => Self::Choice(choice),
+            Value::Number(number) => Self::Number(number),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Critical,
+}
+
+impl From<nero_wasi_logging::Level> for LogLevel {
+    fn from(level: nero_wasi_logging::Level) -> Self {
+        use nero_wasi_logging::Level;
+
+        match level {
+            Level::Trace => Self::Trace,
+            Level::Debug => Self::Debug,
+            Level::Info => Self::Info,
+            Level::Warn => Self::Warn,
+            Level::Error => Self::Error,
+            Level::Critical => Self::Critical,
+        }
+    }
+}
+
+/// A message logged by an extension, with its timestamp in milliseconds
+/// since the Unix epoch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    timestamp: u64,
+    extension: String,
+    operation: Option<String>,
+    level: LogLevel,
+    context: String,
+    message: String,
+    fields: Vec<LogField>,
+    spans: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogField {
+    key: String,
+    value: LogFieldValue,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum LogFieldValue {
+    Text(String),
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl From<&nero_wasi_logging::structured::Field> for LogField {
+    fn from(field: &nero_wasi_logging::structured::Field) -> Self {
+        use nero_wasi_logging::structured::Value;
+
+        let value = match &field.value {
+            Value::Text(text) => LogFieldValue::Text(text.clone()),
+            Value::Signed(value) => LogFieldValue::Signed(*value),
+            Value::Unsigned(value) => LogFieldValue::Unsigned(*value),
+            Value::Float(value) => LogFieldValue::Float(*value),
+            Value::Boolean(value) => LogFieldValue::Boolean(*value),
+        };
+        Self {
+            key: field.key.clone(),
+            value,
+        }
+    }
+}
+
+impl From<&nero_wasi_logging::LogRecord> for LogEntry {
+    fn from(record: &nero_wasi_logging::LogRecord) -> Self {
+        let timestamp = record
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self {
+            timestamp: timestamp.as_millis() as u64,
+            extension: record.extension.to_string(),
+            operation: record.operation.map(str::to_owned),
+            level: record.level.into(),
+            context: record.context.clone(),
+            message: record.message.clone(),
+            fields: record.fields.iter().map(Into::into).collect(),
+            spans: record.spans.clone(),
+        }
+    }
+}
+
+/// An outgoing request of an extension and its outcome, with its start in
+/// milliseconds since the Unix epoch and its duration in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpExchange {
+    extension: String,
+    operation: String,
+    call_id: u64,
+    started: u64,
+    duration: Option<f64>,
+    request: HttpRequest,
+    response: Option<HttpResponse>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: HttpBody,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: HttpBody,
+}
+
+/// A recorded body, decoded as UTF-8 with invalid sequences replaced.
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpBody {
+    text: String,
+    size: u64,
+    truncated: bool,
+}
+
+impl From<&nero_extensions::inspector::RecordedBody> for HttpBody {
+    fn from(body: &nero_extensions::inspector::RecordedBody) -> Self {
+        Self {
+            text: String::from_utf8_lossy(&body.data).into_owned(),
+            size: body.size,
+            truncated: body.is_truncated(),
+        }
+    }
+}
+
+impl From<&nero_extensions::inspector::HttpExchange> for HttpExchange {
+    fn from(exchange: &nero_extensions::inspector::HttpExchange) -> Self {
+        let started = exchange
+            .started
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self {
+            extension: exchange.extension.to_string(),
+            operation: exchange.operation.to_owned(),
+            call_id: exchange.call_id,
+            started: started.as_millis() as u64,
+            duration: exchange
+                .duration
+                .map(|duration| duration.as_secs_f64() * 1000.0),
+            request: HttpRequest {
+                method: exchange.request.method.clone(),
+                url: exchange.request.url.clone(),
+                headers: exchange.request.headers.clone(),
+                body: (&exchange.request.body).into(),
+            },
+            response: exchange.response.as_ref().map(|response| HttpResponse {
+                status: response.status,
+                headers: response.headers.clone(),
+                body: (&response.body).into(),
+            }),
+            error: exchange.error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledExtension {
+    #[serde(flatten)]
+    extension: nero_extensions::repository::InstalledExtension,
+    /// The package to load the extension from.
+    file_path: PathBuf,
+}
+
+impl InstalledExtension {
+    pub fn new(
+        extension: nero_extensions::repository::InstalledExtension,
+        store: &ExtensionStore,
+    ) -> Self {
+        let file_path = store.package_path(&extension.id);
+        Self {
+            extension,
+            file_path,
         }
     }
 }
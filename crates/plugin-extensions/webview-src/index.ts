@@ -1,4 +1,4 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 
 export interface Metadata {
   name?: string;
@@ -14,17 +14,97 @@ export interface Metadata {
   dependencies?: string[];
 }
 
+export interface Manifest {
+  id: string;
+  displayName: string;
+  icon?: string;
+  languages: string[];
+  contentTypes: string[];
+  imports: string[];
+  networkHosts: string[];
+}
+
+export interface RepositoryIndex {
+  format: number;
+  name: string;
+  extensions: RepositoryExtension[];
+}
+
+export interface RepositoryExtension {
+  id: string;
+  name: string;
+  description?: string;
+  icon?: string;
+  releases: Release[];
+}
+
+export interface Release {
+  version: string;
+  /** Absolute, or relative to the index. */
+  url: string;
+  sha256: string;
+  signature?: string;
+  /** The oldest version of the app the release works with. */
+  minHostVersion?: string;
+}
+
+export interface InstalledExtension {
+  id: string;
+  name: string;
+  version: string;
+  sha256: string;
+  /** The URL of the index it was installed from. */
+  repository: string;
+  /** The package to load the extension from. */
+  filePath: string;
+}
+
+export type SeriesStatus =
+  | "upcoming"
+  | "airing"
+  | "finished"
+  | "hiatus"
+  | "cancelled";
+
+export interface ExternalIds {
+  anilist?: number;
+  myAnimeList?: number;
+  tmdb?: number;
+}
+
 export interface Series {
   id: string;
   title: string;
   posterUrl?: string;
   synopsis?: string;
   type?: string;
+  alternativeTitles: string[];
+  genres: string[];
+  status?: SeriesStatus;
+  startYear?: number;
+  endYear?: number;
+  totalEpisodes?: number;
+  rating?: number;
+  studios: string[];
+  bannerUrl?: string;
+  externalIds: ExternalIds;
+}
+
+export type EpisodeKind = "regular" | "special" | "recap" | "ova" | "movie";
+
+export interface Season {
+  id: string;
+  number?: number;
+  title?: string;
 }
 
 export interface Episode {
   id: string;
-  number: number;
+  number?: number;
+  kind: EpisodeKind;
+  season?: Season;
+  airDate?: string;
+  duration?: number;
   title?: string;
   thumbnailUrl?: string;
   description?: string;
@@ -37,27 +117,153 @@ export interface Page<T> {
 
 export type SeriesPage = Page<Series>;
 export type EpisodesPage = Page<Episode>;
+export type FeedPage = Page<FeedItem>;
 
-export interface FilterCategory {
+export interface Feed {
   id: string;
   displayName: string;
-  filters: Filter[];
 }
 
+export type FeedItem =
+  | ({ type: "series" } & Series)
+  | { type: "episode"; series: Series; episode: Episode };
+
+export type FilterKind =
+  | { type: "multiSelect"; filters: Filter[] }
+  | { type: "singleSelect"; filters: Filter[] }
+  | { type: "triState"; filters: Filter[] }
+  | { type: "range"; min: number; max: number; step?: number }
+  | { type: "sort"; filters: Filter[] }
+  | { type: "text"; placeholder?: string };
+
+export type FilterCategory = {
+  id: string;
+  displayName: string;
+  required: boolean;
+} & FilterKind;
+
 export interface Filter {
   id: string;
   displayName: string;
 }
 
-export interface SearchFilter {
+export type SortDirection = "ascending" | "descending";
+
+export type FilterValue =
+  | { type: "selected"; values: string[] }
+  | { type: "triState"; included: string[]; excluded: string[] }
+  | { type: "range"; min?: number; max?: number }
+  | { type: "sort"; by: string; direction: SortDirection }
+  | { type: "text"; value: string };
+
+export type SearchFilter = { id: string } & FilterValue;
+
+export interface SettingOption {
   id: string;
-  values: string[];
+  displayName: string;
+}
+
+export type SettingKind =
+  | { type: "text"; default?: string; placeholder?: string }
+  | { type: "secret" }
+  | { type: "toggle"; default: boolean }
+  | { type: "choice"; options: SettingOption[]; default?: string }
+  | { type: "number"; min?: number; max?: number; default?: number };
+
+export type SettingValue =
+  | { type: "text"; value: string }
+  | { type: "secret"; value: string }
+  | { type: "toggle"; value: boolean }
+  | { type: "choice"; value: string }
+  | { type: "number"; value: number };
+
+export type Setting = {
+  id: string;
+  displayName: string;
+  description?: string;
+  required: boolean;
+  value?: SettingValue;
+  isSet: boolean;
+} & SettingKind;
+
+export type SubtitleFormat = "vtt" | "ass";
+
+export interface Subtitle {
+  url: string;
+  language?: string;
+  label: string;
+  format: SubtitleFormat;
+}
+
+export interface AudioTrack {
+  url: string;
+  language?: string;
+  label: string;
+  isDub: boolean;
 }
 
 export interface Video {
   url: string;
   server: string;
   resolution: [number, number];
+  subtitles: Subtitle[];
+  audioLanguage?: string;
+  isDub: boolean;
+  hasHardsubs: boolean;
+  audioTracks: AudioTrack[];
+}
+
+export type LogLevel =
+  | "trace"
+  | "debug"
+  | "info"
+  | "warn"
+  | "error"
+  | "critical";
+
+export interface LogEntry {
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number;
+  extension: string;
+  operation?: string;
+  level: LogLevel;
+  context: string;
+  message: string;
+  /** Key-value attributes attached by the extension. */
+  fields: { key: string; value: string | number | boolean }[];
+  /** Names of the open spans of the extension, outermost first. */
+  spans: string[];
+}
+
+export interface HttpBody {
+  /** The captured bytes, decoded as UTF-8. */
+  text: string;
+  /** Size of the whole body in bytes. */
+  size: number;
+  truncated: boolean;
+}
+
+export interface HttpExchange {
+  extension: string;
+  operation: string;
+  /** Identifies the call into the extension that sent the request. */
+  callId: number;
+  /** Milliseconds since the Unix epoch. */
+  started: number;
+  /** Milliseconds until the response headers were received. */
+  duration?: number;
+  request: {
+    method: string;
+    url: string;
+    headers: [string, string][];
+    body: HttpBody;
+  };
+  response?: {
+    status: number;
+    headers: [string, string][];
+    body: HttpBody;
+  };
+  error?: string;
 }
 
 export class Extension {
@@ -75,6 +281,12 @@ export class Extension {
     });
   }
 
+  static async getManifest(filePath: string): Promise<Manifest | null> {
+    return await invoke("plugin:nero-extensions|get_extension_manifest", {
+      filePath,
+    });
+  }
+
   static async load(filePath: string): Promise<Extension> {
     const metadata = await Extension.getMetadata(filePath);
     await invoke("plugin:nero-extensions|load_extension", { filePath });
@@ -113,10 +325,145 @@ export class Extension {
     });
   }
 
+  /** Returns the seasons of a series, empty if it is not split into seasons. */
+  async getSeriesSeasons(seriesId: string): Promise<Season[]> {
+    return await invoke("plugin:nero-extensions|get_series_seasons", {
+      seriesId,
+    });
+  }
+
+  async getSeasonEpisodes(
+    seriesId: string,
+    seasonId: string,
+    page?: number,
+  ): Promise<EpisodesPage> {
+    return await invoke("plugin:nero-extensions|get_season_episodes", {
+      seriesId,
+      seasonId,
+      page,
+    });
+  }
+
   async getSeriesVideos(seriesId: string, episodeId: string): Promise<Video[]> {
     return await invoke("plugin:nero-extensions|get_series_videos", {
       seriesId,
       episodeId,
     });
   }
+
+  async getFeeds(): Promise<Feed[]> {
+    return await invoke("plugin:nero-extensions|get_feeds");
+  }
+
+  async getFeed(feedId: string, page?: number): Promise<FeedPage> {
+    return await invoke("plugin:nero-extensions|get_feed", {
+      feedId,
+      page,
+    });
+  }
+
+  async getSettings(): Promise<Setting[]> {
+    return await invoke("plugin:nero-extensions|get_settings");
+  }
+
+  async setSetting(id: string, value: SettingValue | null): Promise<void> {
+    return await invoke("plugin:nero-extensions|set_setting", { id, value });
+  }
+
+  async clearSession(): Promise<void> {
+    return await invoke("plugin:nero-extensions|clear_session");
+  }
+
+  async getLogs(): Promise<LogEntry[]> {
+    return await invoke("plugin:nero-extensions|get_logs");
+  }
+
+  async clearLogs(): Promise<void> {
+    return await invoke("plugin:nero-extensions|clear_logs");
+  }
+
+  async getLogFilter(): Promise<string> {
+    return await invoke("plugin:nero-extensions|get_log_filter");
+  }
+
+  /**
+   * Sets which messages of the extension are kept, e.g. `warn,http=debug`:
+   * a minimum level followed by per-context levels, `off` disabling them.
+   */
+  async setLogFilter(filter: string): Promise<void> {
+    return await invoke("plugin:nero-extensions|set_log_filter", { filter });
+  }
+
+  /**
+   * Calls `onEntry` with every message logged from now on by any extension,
+   * until the returned function is called.
+   */
+  static async streamLogs(
+    onEntry: (entry: LogEntry) => void,
+  ): Promise<() => Promise<void>> {
+    const channel = new Channel<LogEntry>();
+    channel.onmessage = onEntry;
+    await invoke("plugin:nero-extensions|stream_logs", { channel });
+    return async () => {
+      await invoke("plugin:nero-extensions|stop_log_stream", {
+        channelId: channel.id,
+      });
+    };
+  }
+
+  /** Starts or stops recording the HTTP traffic of extensions. */
+  static async setHttpInspectorEnabled(enabled: boolean): Promise<void> {
+    return await invoke("plugin:nero-extensions|set_http_inspector_enabled", {
+      enabled,
+    });
+  }
+
+  static async getHttpExchanges(): Promise<HttpExchange[]> {
+    return await invoke("plugin:nero-extensions|get_http_exchanges");
+  }
+
+  static async clearHttpExchanges(): Promise<void> {
+    return await invoke("plugin:nero-extensions|clear_http_exchanges");
+  }
+
+  /** Writes the recorded HTTP traffic to `filePath` as a HAR file. */
+  static async exportHar(filePath: string): Promise<void> {
+    return await invoke("plugin:nero-extensions|export_har", { filePath });
+  }
+
+  static async browseRepository(url: string): Promise<RepositoryIndex> {
+    return await invoke("plugin:nero-extensions|browse_repository", { url });
+  }
+
+  static async getInstalled(): Promise<InstalledExtension[]> {
+    return await invoke("plugin:nero-extensions|get_installed_extensions");
+  }
+
+  /**
+   * Installs `version` of the extension `id` from the repository at `url`,
+   * or its latest release working with this app. The package must be
+   * signed with the secret key of `publicKey`, unless `allowUnsigned` is
+   * set and no public key is given.
+   */
+  static async install(
+    url: string,
+    id: string,
+    options: {
+      publicKey?: string;
+      allowUnsigned?: boolean;
+      version?: string;
+    } = {},
+  ): Promise<InstalledExtension> {
+    return await invoke("plugin:nero-extensions|install_extension", {
+      url,
+      id,
+      publicKey: options.publicKey ?? null,
+      allowUnsigned: options.allowUnsigned ?? false,
+      version: options.version ?? null,
+    });
+  }
+
+  static async uninstall(id: string): Promise<void> {
+    return await invoke("plugin:nero-extensions|uninstall_extension", { id });
+  }
 }
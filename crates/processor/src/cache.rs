@@ -1,22 +1,72 @@
 use std::{
     collections::HashMap,
     hash::Hash,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, Instant},
 };
 use tokio::{sync::RwLock, time};
 
-#[derive(Debug, Clone)]
+/// Strategy used to make room for a new entry once a cache is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evict the least recently used entry.
+    #[default]
+    Lru,
+    /// Evict the entry closest to expiring, falling back to the least
+    /// recently used one for entries without a TTL.
+    Ttl,
+    /// Never evict; inserts into a full cache are rejected.
+    Reject,
+}
+
+/// A point-in-time snapshot of the counters of a [`Cache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+    pub rejections: u64,
+    pub len: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+    rejections: AtomicU64,
+}
+
+impl CacheMetrics {
+    fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn add(counter: &AtomicU64, value: usize) {
+        counter.fetch_add(value as u64, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
 struct CacheEntry<V> {
     value: V,
     expires_at: Option<Instant>,
+    /// Updated through a shared reference, so that reads only need the
+    /// read lock of the cache.
+    last_access: AtomicU64,
 }
 
 impl<V> CacheEntry<V> {
-    fn new(value: V, ttl: Option<Duration>) -> Self {
+    fn new(value: V, ttl: Option<Duration>, tick: u64) -> Self {
         Self {
             value,
             expires_at: ttl.map(|duration| Instant::now() + duration),
+            last_access: AtomicU64::new(tick),
         }
     }
 
@@ -27,11 +77,72 @@ impl<V> CacheEntry<V> {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug)]
+struct CacheInner<K, V> {
+    entries: HashMap<K, CacheEntry<V>>,
+    tick: AtomicU64,
+}
+
+impl<K, V> Default for CacheInner<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            tick: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> CacheInner<K, V> {
+    fn next_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn purge_expired(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| !entry.is_expired());
+        before - self.entries.len()
+    }
+
+    fn victim(&self, policy: EvictionPolicy) -> Option<K> {
+        let entries = self.entries.iter();
+        let victim = match policy {
+            EvictionPolicy::Lru => {
+                entries.min_by_key(|(_, entry)| entry.last_access.load(Ordering::Relaxed))
+            }
+            EvictionPolicy::Ttl => entries.min_by_key(|(_, entry)| {
+                // `None` sorts before `Some`, so entries without a TTL are
+                // flipped to be considered last.
+                (
+                    entry.expires_at.is_none(),
+                    entry.expires_at,
+                    entry.last_access.load(Ordering::Relaxed),
+                )
+            }),
+            EvictionPolicy::Reject => None,
+        };
+        victim.map(|(key, _)| key.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Cache<K, V> {
-    map: Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
+    inner: Arc<RwLock<CacheInner<K, V>>>,
+    metrics: Arc<CacheMetrics>,
     ttl: Option<Duration>,
     max_capacity: Option<usize>,
+    policy: EvictionPolicy,
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            metrics: Arc::default(),
+            ttl: None,
+            max_capacity: None,
+            policy: EvictionPolicy::default(),
+        }
+    }
 }
 
 impl<K, V> Cache<K, V>
@@ -50,76 +161,204 @@ where
         self
     }
 
+    pub fn with_eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     fn spawn_cleanup_task(&self) {
         if self.ttl.is_none() {
             return;
         }
 
-        let map = self.map.clone();
+        let inner = self.inner.clone();
+        let metrics = self.metrics.clone();
 
         tokio::spawn(async move {
             let mut interval = time::interval(Duration::from_secs(60));
 
             loop {
                 interval.tick().await;
-                let mut entries = map.write().await;
-                entries.retain(|_, entry| !entry.is_expired());
+                let purged = inner.write().await.purge_expired();
+                CacheMetrics::add(&metrics.expirations, purged);
             }
         });
     }
 
+    /// Inserts `value` under `key`, replacing and resetting the TTL of any
+    /// existing entry.
+    ///
+    /// When the cache is full, expired entries are purged first and then an
+    /// entry is evicted according to the configured [`EvictionPolicy`].
+    /// Returns `false` if the value could not be stored.
     pub async fn insert(&self, key: K, value: V) -> bool {
-        let mut map = self.map.write().await;
+        let mut inner = self.inner.write().await;
+        let tick = inner.next_tick();
 
         if let Some(max) = self.max_capacity
-            && map.len() >= max
-            && !map.contains_key(&key)
+            && !inner.entries.contains_key(&key)
+            && inner.entries.len() >= max
         {
-            return false;
-        }
+            let purged = inner.purge_expired();
+            CacheMetrics::add(&self.metrics.expirations, purged);
 
-        if map.contains_key(&key) {
-            return false;
+            while inner.entries.len() >= max {
+                let Some(victim) = inner.victim(self.policy) else {
+                    CacheMetrics::incr(&self.metrics.rejections);
+                    return false;
+                };
+                inner.entries.remove(&victim);
+                CacheMetrics::incr(&self.metrics.evictions);
+            }
         }
 
-        map.insert(key, CacheEntry::new(value, self.ttl));
+        inner
+            .entries
+            .insert(key, CacheEntry::new(value, self.ttl, tick));
         true
     }
 
+    /// Returns the value under `key`, counting a hit or a miss.
+    ///
+    /// Only takes the read lock: expired entries are treated as missing and
+    /// left for the next insert into a full cache or the cleanup task to
+    /// purge.
     pub async fn get(&self, key: &K) -> Option<V> {
-        let map = self.map.read().await;
-        let entry = map.get(key)?;
+        let inner = self.inner.read().await;
 
-        if entry.is_expired() {
-            return None;
+        match inner.entries.get(key) {
+            Some(entry) if !entry.is_expired() => {
+                entry
+                    .last_access
+                    .store(inner.next_tick(), Ordering::Relaxed);
+                CacheMetrics::incr(&self.metrics.hits);
+                Some(entry.value.clone())
+            }
+            _ => {
+                CacheMetrics::incr(&self.metrics.misses);
+                None
+            }
         }
-
-        Some(entry.value.clone())
     }
 
+    /// Removes and returns the value under `key`, without counting a hit or
+    /// a miss.
     pub async fn remove(&self, key: &K) -> Option<V> {
-        let mut map = self.map.write().await;
-        let entry = map.remove(key)?;
+        let mut inner = self.inner.write().await;
 
+        let entry = inner.entries.remove(key)?;
         if entry.is_expired() {
+            CacheMetrics::incr(&self.metrics.expirations);
             return None;
         }
-
         Some(entry.value)
     }
 
     pub async fn clear(&self) {
-        let mut map = self.map.write().await;
-        map.clear();
+        let mut inner = self.inner.write().await;
+        inner.entries.clear();
     }
 
     pub async fn len(&self) -> usize {
-        let map = self.map.read().await;
-        map.len()
+        let inner = self.inner.read().await;
+        inner.entries.len()
     }
 
     pub async fn is_empty(&self) -> bool {
-        let map = self.map.read().await;
-        map.is_empty()
+        let inner = self.inner.read().await;
+        inner.entries.is_empty()
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+            evictions: self.metrics.evictions.load(Ordering::Relaxed),
+            expirations: self.metrics.expirations.load(Ordering::Relaxed),
+            rejections: self.metrics.rejections.load(Ordering::Relaxed),
+            len: self.len().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lru_evicts_least_recently_used() {
+        let cache = Cache::default().with_capacity(2);
+        cache.insert("a", 1).await;
+        cache.insert("b", 2).await;
+        cache.get(&"a").await;
+
+        assert!(cache.insert("c", 3).await);
+
+        assert_eq!(cache.get(&"a").await, Some(1));
+        assert_eq!(cache.get(&"b").await, None);
+        assert_eq!(cache.get(&"c").await, Some(3));
+        assert_eq!(cache.stats().await.evictions, 1);
+    }
+
+    #[tokio::test]
+    async fn ttl_policy_evicts_entry_expiring_first() {
+        let cache = Cache::default()
+            .with_capacity(2)
+            .with_eviction_policy(EvictionPolicy::Ttl)
+            .with_ttl(Duration::from_secs(60));
+        cache.insert("a", 1).await;
+        cache.insert("b", 2).await;
+        cache.get(&"a").await;
+
+        cache.insert("c", 3).await;
+
+        assert_eq!(cache.get(&"a").await, None);
+        assert_eq!(cache.get(&"b").await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn reject_policy_keeps_entries() {
+        let cache = Cache::default()
+            .with_capacity(1)
+            .with_eviction_policy(EvictionPolicy::Reject);
+        assert!(cache.insert("a", 1).await);
+
+        assert!(!cache.insert("b", 2).await);
+        assert!(cache.insert("a", 3).await);
+
+        assert_eq!(cache.get(&"a").await, Some(3));
+        assert_eq!(cache.stats().await.rejections, 1);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_miss_and_are_purged() {
+        let cache = Cache::default()
+            .with_capacity(1)
+            .with_ttl(Duration::from_millis(10));
+        cache.insert("a", 1).await;
+        time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(cache.get(&"a").await, None);
+        assert!(cache.insert("b", 2).await);
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.expirations, 1);
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.len, 1);
+    }
+
+    #[tokio::test]
+    async fn only_get_counts_hits_and_misses() {
+        let cache = Cache::default();
+        cache.insert("a", 1).await;
+
+        assert_eq!(cache.get(&"a").await, Some(1));
+        assert_eq!(cache.get(&"b").await, None);
+        assert_eq!(cache.remove(&"a").await, Some(1));
+        assert_eq!(cache.remove(&"a").await, None);
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(stats.len, 0);
     }
 }
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, sync::Arc};
+
+use http::StatusCode;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
+use url::Url;
+
+use crate::{HttpRequest, config::RetryConfig, error::Error, routes::IntoReqwestRequest};
+
+/// The outbound HTTP client shared by MIME detection and the streaming
+/// routes, enforcing the per-origin concurrency limit.
+pub struct HttpClient {
+    client: reqwest::Client,
+    retry: RetryConfig,
+    origin_limit: Option<usize>,
+    origins: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HttpClient {
+    pub fn new(client: reqwest::Client, retry: RetryConfig, origin_limit: Option<usize>) -> Self {
+        Self {
+            client,
+            retry,
+            origin_limit,
+            origins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn retry_config(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    /// Waits for a free slot for the origin of `url`.
+    ///
+    /// The returned permit must be kept alive for as long as the upstream
+    /// connection is in use. Returns `None` when no limit is configured.
+    pub async fn acquire(&self, url: &Url) -> Option<OwnedSemaphorePermit> {
+        let limit = self.origin_limit?;
+
+        let semaphore = {
+            let mut origins = self.origins.lock().await;
+            origins
+                .entry(url.origin().ascii_serialization())
+                .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                .clone()
+        };
+
+        // The semaphore is never closed.
+        semaphore.acquire_owned().await.ok()
+    }
+
+    pub async fn execute(
+        &self,
+        request: reqwest::Request,
+    ) -> reqwest::Result<(reqwest::Response, Option<OwnedSemaphorePermit>)> {
+        let permit = self.acquire(request.url()).await;
+        let response = self.client.execute(request).await?;
+        Ok((response, permit))
+    }
+
+    /// Sends `request`, retrying connection errors and transient responses
+    /// with exponential backoff.
+    ///
+    /// Non-success responses that are not retried are returned as
+    /// [`Error::RemoteServer`].
+    pub async fn send(
+        &self,
+        request: &HttpRequest,
+    ) -> Result<(reqwest::Response, Option<OwnedSemaphorePermit>), Error> {
+        let mut backoff = self.retry.initial_backoff;
+        let mut attempt = 0;
+
+        loop {
+            let reqwest_request = request.clone().into_reqwest_request(self.client.clone())?;
+            let result = self.execute(reqwest_request).await;
+
+            let transient = match &result {
+                Ok((response, _)) => is_transient_status(response.status()),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+
+            if !transient || attempt >= self.retry.max_retries {
+                let (response, permit) = result?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(Error::RemoteServer(status));
+                }
+                return Ok((response, permit));
+            }
+
+            match &result {
+                Ok((response, _)) => warn!(
+                    "upstream {} returned {}, retrying in {:?}",
+                    request.uri(),
+                    response.status(),
+                    backoff
+                ),
+                Err(err) => warn!(
+                    "upstream {} failed: {err}, retrying in {:?}",
+                    request.uri(),
+                    backoff
+                ),
+            }
+            drop(result);
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.retry.max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
@@ -0,0 +1,166 @@
+use std::{hash::Hash, time::Duration};
+
+use url::Url;
+
+use crate::cache::{Cache, EvictionPolicy};
+
+#[derive(Debug, Clone, Default)]
+pub struct CacheConfig {
+    pub image_ttl: Option<Duration>,
+    pub image_capacity: Option<usize>,
+    pub video_ttl: Option<Duration>,
+    pub video_capacity: Option<usize>,
+    pub eviction_policy: EvictionPolicy,
+}
+
+impl CacheConfig {
+    pub(crate) fn build_cache<K, V>(
+        &self,
+        ttl: Option<Duration>,
+        capacity: Option<usize>,
+    ) -> Cache<K, V>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        let mut cache = Cache::default().with_eviction_policy(self.eviction_policy);
+        if let Some(ttl) = ttl {
+            cache = cache.with_ttl(ttl);
+        }
+        if let Some(capacity) = capacity {
+            cache = cache.with_capacity(capacity);
+        }
+        cache
+    }
+}
+
+/// How upstream failures of the streaming routes are retried.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Attempts made after the first one for connection errors and
+    /// transient (5xx, 429) responses.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Times a video stream is reconnected with a `Range` request after
+    /// failing mid-stream.
+    pub max_resumes: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            max_resumes: 5,
+        }
+    }
+}
+
+/// Configuration of a [`Processor`](crate::Processor) and of the HTTP client
+/// it uses for every outbound request, including MIME detection.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessorConfig {
+    pub(crate) cache: CacheConfig,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) proxy: Option<Url>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) max_connections_per_origin: Option<usize>,
+    pub(crate) retry: RetryConfig,
+}
+
+impl ProcessorConfig {
+    pub fn with_cache_config(mut self, cache: CacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Routes every outbound request through `proxy`.
+    ///
+    /// Supports `http://`, `https://`, `socks5://` and `socks5h://` URLs.
+    /// Credentials may be embedded in the URL.
+    pub fn with_proxy(mut self, proxy: Url) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum time to wait between two reads of a response body.
+    ///
+    /// Unlike a total request timeout this does not cut off long-running
+    /// video streams.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Trusts an additional PEM encoded root certificate.
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Limits the number of concurrent upstream requests per origin
+    /// (scheme, host and port).
+    pub fn with_max_connections_per_origin(mut self, max: usize) -> Self {
+        self.max_connections_per_origin = Some(max);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub(crate) fn build_http_client(&self) -> reqwest::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        for pem in &self.root_certificates {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(pem)?);
+        }
+
+        builder.build()
+    }
+}
@@ -0,0 +1,114 @@
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use tokio::{sync::oneshot, task::JoinHandle};
+use tracing::{debug, warn};
+
+use crate::ServerState;
+
+/// A handle to a running [`Processor`](crate::Processor) server.
+///
+/// Dropping the handle leaves the server running; use
+/// [`ProcessorHandle::shutdown`] to stop it.
+pub struct ProcessorHandle {
+    local_addr: SocketAddr,
+    state: Arc<ServerState>,
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<io::Result<()>>,
+}
+
+impl ProcessorHandle {
+    pub(crate) fn new(
+        local_addr: SocketAddr,
+        state: Arc<ServerState>,
+        shutdown_tx: oneshot::Sender<()>,
+        task: JoinHandle<io::Result<()>>,
+    ) -> Self {
+        Self {
+            local_addr,
+            state,
+            shutdown_tx,
+            task,
+        }
+    }
+
+    /// The address the server is actually listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stops accepting new connections and waits up to `drain_timeout` for
+    /// in-flight requests to finish before aborting them, closing their
+    /// connections.
+    pub async fn shutdown(self, drain_timeout: Duration) -> io::Result<()> {
+        // The receiver is gone if the server already exited on its own.
+        let _ = self.shutdown_tx.send(());
+
+        let abort_handle = self.task.abort_handle();
+        let res = match tokio::time::timeout(drain_timeout, self.task).await {
+            Ok(Ok(res)) => {
+                debug!("processor on {} shut down", self.local_addr);
+                res
+            }
+            Ok(Err(err)) => Err(io::Error::other(err)),
+            Err(_) => {
+                warn!(
+                    "processor on {} did not drain within {:?}, aborting",
+                    self.local_addr, drain_timeout
+                );
+                // Aborting the server drops its connections with it, but
+                // also the clearing of its address at the end of the task.
+                abort_handle.abort();
+                Ok(())
+            }
+        };
+        self.state.clear_local_addr(self.local_addr).await;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use tokio::net::TcpListener;
+
+    use crate::{Processor, SubtitleFormat};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_aborts_stalled_requests() {
+        // An upstream accepting connections without ever answering.
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let upstream_url = format!("http://{}/track.srt", upstream.local_addr().unwrap());
+        let _upstream = tokio::spawn(async move {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = upstream.accept().await {
+                streams.push(stream);
+            }
+        });
+
+        let processor = Processor::new(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)));
+        let handle = processor.start().await.unwrap();
+        let request = http::Request::get(upstream_url).body(None).unwrap();
+        let url = processor
+            .register_subtitle_request(request, SubtitleFormat::Srt)
+            .await
+            .unwrap();
+        let client = tokio::spawn(reqwest::get(url));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        handle.shutdown(Duration::from_millis(50)).await.unwrap();
+
+        assert_eq!(processor.local_addr().await, None);
+        let response = tokio::time::timeout(Duration::from_secs(5), client)
+            .await
+            .expect("the stalled request is aborted")
+            .unwrap();
+        assert!(response.is_err());
+    }
+}
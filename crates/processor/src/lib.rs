@@ -1,46 +1,69 @@
 mod cache;
+mod client;
+mod config;
 mod error;
+mod handle;
 mod mime_detector;
 mod routes;
+mod server;
+mod subtitles;
 mod utils;
 
-use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+use std::{io, net::SocketAddr, sync::Arc};
 
 use anyhow::bail;
 use axum::{Router, routing::get};
 use bytes::Bytes;
 use http::{Request, uri::Scheme};
-use tokio::{net::TcpListener, sync::RwLock};
+use tokio::{
+    net::TcpListener,
+    sync::{RwLock, oneshot},
+};
 use tracing::debug;
 use url::Url;
 
+pub use crate::{
+    cache::{Cache, CacheStats, EvictionPolicy},
+    config::{CacheConfig, ProcessorConfig, RetryConfig},
+    handle::ProcessorHandle,
+    subtitles::{SubtitleFormat, srt_to_vtt},
+};
+
 use crate::{
-    cache::Cache,
+    client::HttpClient,
     mime_detector::mime_type,
-    routes::{handle_image_request, handle_video_request},
+    routes::{
+        handle_health_request, handle_image_request, handle_subtitle_request, handle_video_request,
+    },
     utils::get_request_hash,
 };
 
 type HttpRequest = Request<Option<Bytes>>;
 
-#[derive(Debug, Clone, Default)]
-pub struct CacheConfig {
-    pub image_ttl: Option<Duration>,
-    pub image_capacity: Option<usize>,
-    pub video_ttl: Option<Duration>,
-    pub video_capacity: Option<usize>,
-}
-
 struct ServerState {
     addr: SocketAddr,
-    http_client: reqwest::Client,
+    local_addr: RwLock<Option<SocketAddr>>,
+    http_client: HttpClient,
 
     image_requests: Cache<u64, HttpRequest>,
     video_requests: Cache<u64, HttpRequest>,
+    video_alternatives: Cache<u64, Vec<u64>>,
+    subtitle_requests: Cache<u64, (HttpRequest, SubtitleFormat)>,
 
     current_video: RwLock<Option<HttpRequest>>,
 }
 
+impl ServerState {
+    /// Forgets `addr` once the server bound to it stopped, unless the
+    /// server was started again since.
+    async fn clear_local_addr(&self, addr: SocketAddr) {
+        let mut local_addr = self.local_addr.write().await;
+        if *local_addr == Some(addr) {
+            local_addr.take();
+        }
+    }
+}
+
 pub struct Processor {
     state: Arc<ServerState>,
 }
@@ -51,46 +74,86 @@ impl Processor {
     }
 
     pub fn with_cache_config(addr: SocketAddr, cache_config: CacheConfig) -> Self {
+        Self::with_config(
+            addr,
+            ProcessorConfig::default().with_cache_config(cache_config),
+        )
+        .expect("default HTTP client configuration is valid")
+    }
+
+    pub fn with_config(addr: SocketAddr, config: ProcessorConfig) -> anyhow::Result<Self> {
+        let cache_config = &config.cache;
         let state = ServerState {
             addr,
-            http_client: reqwest::Client::new(),
-            image_requests: {
-                let mut cache = Cache::default();
-                if let Some(ttl) = cache_config.image_ttl {
-                    cache = cache.with_ttl(ttl);
-                }
-                if let Some(capacity) = cache_config.image_capacity {
-                    cache = cache.with_capacity(capacity);
-                }
-                cache
-            },
-            video_requests: {
-                let mut cache = Cache::default();
-                if let Some(ttl) = cache_config.video_ttl {
-                    cache = cache.with_ttl(ttl);
-                }
-                if let Some(capacity) = cache_config.video_capacity {
-                    cache = cache.with_capacity(capacity);
-                }
-                cache
-            },
+            local_addr: RwLock::new(None),
+            http_client: HttpClient::new(
+                config.build_http_client()?,
+                config.retry.clone(),
+                config.max_connections_per_origin,
+            ),
+            image_requests: cache_config
+                .build_cache(cache_config.image_ttl, cache_config.image_capacity),
+            video_requests: cache_config
+                .build_cache(cache_config.video_ttl, cache_config.video_capacity),
+            video_alternatives: cache_config
+                .build_cache(cache_config.video_ttl, cache_config.video_capacity),
+            subtitle_requests: cache_config
+                .build_cache(cache_config.video_ttl, cache_config.video_capacity),
             current_video: RwLock::new(None),
         };
 
-        Self {
+        Ok(Self {
             state: Arc::new(state),
-        }
+        })
     }
 
-    pub async fn run(&self) -> io::Result<()> {
+    /// Binds the server and serves it in a background task.
+    ///
+    /// The configured address may use port `0`, in which case the OS picks a
+    /// free port; the actual address is reported by the returned handle and
+    /// used for the URLs handed out by `register_*`. After shutting a handle
+    /// down, calling `start` again rebinds the server.
+    pub async fn start(&self) -> io::Result<ProcessorHandle> {
         let app = Router::new()
+            .route("/health", get(handle_health_request))
             .route("/image/{request_hash}", get(handle_image_request))
             .route("/video/{request_hash}", get(handle_video_request))
+            .route("/audio/{request_hash}", get(handle_video_request))
+            .route("/subtitle/{request_hash}", get(handle_subtitle_request))
             .with_state(self.state.clone());
 
         let listener = TcpListener::bind(self.state.addr).await?;
-        debug!("listening on {}", listener.local_addr().unwrap());
-        axum::serve(listener, app).await
+        let local_addr = listener.local_addr()?;
+        debug!("listening on {}", local_addr);
+        self.state.local_addr.write().await.replace(local_addr);
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let state = self.state.clone();
+        let task = tokio::spawn(async move {
+            let res = server::serve(listener, app, shutdown_rx).await;
+            state.clear_local_addr(local_addr).await;
+            res
+        });
+
+        Ok(ProcessorHandle::new(
+            local_addr,
+            self.state.clone(),
+            shutdown_tx,
+            task,
+        ))
+    }
+
+    /// The address the server is listening on, if it is running.
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        *self.state.local_addr.read().await
+    }
+
+    async fn base_url(&self) -> anyhow::Result<Url> {
+        let addr = self
+            .local_addr()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Processor is not running"))?;
+        Ok(Url::parse(&format!("{}://{}", Scheme::HTTP, addr))?)
     }
 
     pub async fn register_image_request(&self, request: HttpRequest) -> anyhow::Result<Url> {
@@ -107,21 +170,36 @@ impl Processor {
         }
 
         let request_hash = get_request_hash(&request);
-        let url = Url::parse(&format!(
-            "{}://{}/image/{request_hash}",
-            Scheme::HTTP,
-            self.state.addr,
-        ))?;
+        let mut url = self.base_url().await?;
+        url.set_path(&format!("/image/{request_hash}"));
 
-        self.state
+        if !self
+            .state
             .image_requests
             .insert(request_hash, request)
-            .await;
+            .await
+        {
+            bail!("Image request cache is full");
+        }
 
         Ok(url)
     }
 
     pub async fn register_video_request(&self, request: HttpRequest) -> anyhow::Result<Url> {
+        self.register_stream_request(request, mime::VIDEO).await
+    }
+
+    /// Registers an audio track that is streamed like a video, including
+    /// retries and resumption.
+    pub async fn register_audio_request(&self, request: HttpRequest) -> anyhow::Result<Url> {
+        self.register_stream_request(request, mime::AUDIO).await
+    }
+
+    async fn register_stream_request(
+        &self,
+        request: HttpRequest,
+        media_type: mime::Name<'static>,
+    ) -> anyhow::Result<Url> {
         if request.headers().is_empty() {
             return Ok(Url::parse(&request.uri().to_string())?);
         }
@@ -131,18 +209,86 @@ impl Processor {
             .ok_or(anyhow::anyhow!("Could not detect mime type"))?;
 
         let request_hash = get_request_hash(&request);
-        let mut base = Url::parse(&format!("{}://{}", Scheme::HTTP, self.state.addr))?;
+        let mut base = self.base_url().await?;
 
         match mime_type.type_() {
-            mime::VIDEO => base.set_path(&format!("/video/{request_hash}")),
+            t if t == media_type => base.set_path(&format!("/{t}/{request_hash}")),
             _ => bail!("Unsupported media type"),
         }
 
-        self.state
+        if !self
+            .state
             .video_requests
             .insert(request_hash, request)
-            .await;
+            .await
+        {
+            bail!("Video request cache is full");
+        }
 
         Ok(base)
     }
+
+    /// Registers a subtitle track and returns the URL it is served from.
+    ///
+    /// SRT tracks are always proxied so they can be served as WebVTT, see
+    /// [`SubtitleFormat::served_format`].
+    pub async fn register_subtitle_request(
+        &self,
+        request: HttpRequest,
+        format: SubtitleFormat,
+    ) -> anyhow::Result<Url> {
+        if request.headers().is_empty() && format == format.served_format() {
+            return Ok(Url::parse(&request.uri().to_string())?);
+        }
+
+        let request_hash = get_request_hash(&request);
+        let mut url = self.base_url().await?;
+        url.set_path(&format!("/subtitle/{request_hash}"));
+
+        if !self
+            .state
+            .subtitle_requests
+            .insert(request_hash, (request, format))
+            .await
+        {
+            bail!("Subtitle request cache is full");
+        }
+
+        Ok(url)
+    }
+
+    /// Marks videos returned by [`Processor::register_video_request`] as
+    /// alternative sources of the same content.
+    ///
+    /// When a video cannot be fetched from its origin, the video route falls
+    /// back to its alternatives in the given order. URLs that are not served
+    /// by the processor are ignored.
+    pub async fn link_video_alternatives(&self, urls: &[Url]) {
+        let hashes = urls
+            .iter()
+            .filter_map(|url| url.path().strip_prefix("/video/")?.parse::<u64>().ok())
+            .collect::<Vec<_>>();
+
+        for hash in &hashes {
+            let alternatives = hashes
+                .iter()
+                .copied()
+                .filter(|other| other != hash)
+                .collect::<Vec<_>>();
+            if !alternatives.is_empty() {
+                self.state
+                    .video_alternatives
+                    .insert(*hash, alternatives)
+                    .await;
+            }
+        }
+    }
+
+    pub async fn image_cache_stats(&self) -> CacheStats {
+        self.state.image_requests.stats().await
+    }
+
+    pub async fn video_cache_stats(&self) -> CacheStats {
+        self.state.video_requests.stats().await
+    }
 }
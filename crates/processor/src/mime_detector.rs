@@ -1,11 +1,13 @@
 use http::Request;
 use mime::Mime;
-use reqwest::Client;
 use std::str::FromStr;
 use tracing::{debug, warn};
+use url::Url;
+
+use crate::client::HttpClient;
 
 pub async fn mime_type<T>(
-    client: &Client,
+    client: &HttpClient,
     request: &Request<T>,
 ) -> Result<Option<Mime>, reqwest::Error> {
     if let Some(mime) = detect_from_path(request) {
@@ -40,10 +42,14 @@ fn detect_from_path<T>(request: &Request<T>) -> Option<Mime> {
 }
 
 async fn detect_from_head<T>(
-    client: &Client,
+    client: &HttpClient,
     request: &Request<T>,
 ) -> Result<Option<Mime>, reqwest::Error> {
-    let res = client.head(request.uri().to_string()).send().await?;
+    let Ok(url) = Url::parse(&request.uri().to_string()) else {
+        return Ok(None);
+    };
+    let _permit = client.acquire(&url).await;
+    let res = client.inner().head(url).send().await?;
 
     if !res.status().is_success() {
         debug!("HEAD request failed with status: {}", res.status());
@@ -66,7 +72,7 @@ async fn detect_from_head<T>(
 
 #[allow(unused_variables)]
 async fn detect_from_content<T>(
-    client: &Client,
+    client: &HttpClient,
     request: &Request<T>,
 ) -> Result<Option<Mime>, reqwest::Error> {
     todo!()
@@ -0,0 +1,5 @@
+use http::StatusCode;
+
+pub async fn handle_health_request() -> StatusCode {
+    StatusCode::OK
+}
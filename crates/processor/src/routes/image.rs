@@ -5,13 +5,10 @@ use axum::{
     extract::{Path, Request, State},
     response::Response,
 };
+use futures_util::StreamExt;
 use http::header::HOST;
 
-use crate::{
-    ServerState,
-    error::Error,
-    routes::{HopByHopHeadersExt, IntoReqwestRequest},
-};
+use crate::{ServerState, error::Error, routes::HopByHopHeadersExt};
 
 pub async fn handle_image_request(
     State(state): State<Arc<ServerState>>,
@@ -35,18 +32,17 @@ pub async fn handle_image_request(
 
     stored_request.headers_mut().remove_hop_by_hop_headers();
 
-    let request = stored_request.into_reqwest_request(state.http_client.clone())?;
-    let response = state.http_client.execute(request).await?;
-
+    let (response, permit) = state.http_client.send(&stored_request).await?;
     let status = response.status();
-    if !status.is_success() {
-        return Err(Error::RemoteServer(status));
-    }
 
     let mut headers = response.headers().clone();
     headers.remove_hop_by_hop_headers();
 
-    let stream = response.bytes_stream();
+    // Hold the origin permit until the body has been fully streamed.
+    let stream = response.bytes_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
     let body = Body::from_stream(stream);
 
     let mut response = Response::new(body);
@@ -1,11 +1,15 @@
+mod health;
 mod image;
+mod subtitle;
 mod video;
 
+pub use health::*;
 use http::{
     HeaderMap, HeaderName,
     header::{CONNECTION, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION, TE, TRANSFER_ENCODING, UPGRADE},
 };
 pub use image::*;
+pub use subtitle::*;
 pub use video::*;
 
 use bytes::Bytes;
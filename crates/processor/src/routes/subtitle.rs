@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use http::header::CONTENT_TYPE;
+
+use crate::{
+    ServerState,
+    error::Error,
+    subtitles::{SubtitleFormat, srt_to_vtt},
+};
+
+pub async fn handle_subtitle_request(
+    State(state): State<Arc<ServerState>>,
+    Path(request_hash): Path<u64>,
+) -> Result<Response, Error> {
+    let (request, format) = state
+        .subtitle_requests
+        .get(&request_hash)
+        .await
+        .ok_or(Error::NotFound)?;
+
+    let (response, _permit) = state.http_client.send(&request).await?;
+    let text = response.text().await?;
+
+    let body = match format {
+        SubtitleFormat::Srt => srt_to_vtt(&text),
+        _ => text,
+    };
+
+    Ok(([(CONTENT_TYPE, format.mime_type().to_string())], body).into_response())
+}
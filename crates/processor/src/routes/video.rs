@@ -1,62 +1,250 @@
-use std::sync::Arc;
+use std::{io, sync::Arc};
 
 use axum::{
     body::Body,
     extract::{Path, Request, State},
     response::Response,
 };
-
-use crate::{
-    ServerState,
-    error::Error,
-    routes::{HopByHopHeadersExt, IntoReqwestRequest},
+use bytes::Bytes;
+use futures_util::{StreamExt, stream::BoxStream};
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{CONTENT_RANGE, HOST, RANGE},
 };
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::warn;
+
+use crate::{HttpRequest, ServerState, error::Error, routes::HopByHopHeadersExt};
 
 pub async fn handle_video_request(
     State(state): State<Arc<ServerState>>,
     Path(request_hash): Path<u64>,
     incoming_request: Request,
 ) -> Result<Response, Error> {
-    let mut stored_request = state
-        .image_requests
-        .remove(&request_hash)
-        .await
-        .ok_or(Error::NotFound)?;
-
-    state
-        .current_video
-        .write()
-        .await
-        .replace(stored_request.clone());
-
-    for (name, value) in incoming_request.headers().iter() {
-        if name == http::header::HOST {
+    let mut candidates = vec![request_hash];
+    // Alternatives may be different encodes, so a byte offset into one does
+    // not point to the same content in another.
+    if allows_fallback(incoming_request.headers())
+        && let Some(alternatives) = state.video_alternatives.get(&request_hash).await
+    {
+        candidates.extend(alternatives);
+    }
+
+    let mut last_error = Error::NotFound;
+    for hash in candidates {
+        let Some(mut stored_request) = state.video_requests.get(&hash).await else {
             continue;
+        };
+
+        if hash != request_hash {
+            warn!("falling back to alternative video {hash} for {request_hash}");
         }
-        stored_request
-            .headers_mut()
-            .insert(name.clone(), value.clone());
-    }
 
-    stored_request.headers_mut().remove_hop_by_hop_headers();
+        state
+            .current_video
+            .write()
+            .await
+            .replace(stored_request.clone());
 
-    let request = stored_request.into_reqwest_request(state.http_client.clone())?;
-    let response = state.http_client.execute(request).await?;
+        for (name, value) in incoming_request.headers().iter() {
+            if name == HOST {
+                continue;
+            }
+            stored_request
+                .headers_mut()
+                .insert(name.clone(), value.clone());
+        }
 
-    let status = response.status();
-    if !status.is_success() {
-        return Err(Error::RemoteServer(status));
+        stored_request.headers_mut().remove_hop_by_hop_headers();
+
+        match state.http_client.send(&stored_request).await {
+            Ok((response, permit)) => {
+                return Ok(into_resumable_response(
+                    state.clone(),
+                    stored_request,
+                    response,
+                    permit,
+                ));
+            }
+            Err(err) => {
+                warn!("video {hash} is unavailable: {err}");
+                last_error = err;
+            }
+        }
     }
 
+    Err(last_error)
+}
+
+fn into_resumable_response(
+    state: Arc<ServerState>,
+    request: HttpRequest,
+    response: reqwest::Response,
+    permit: Option<OwnedSemaphorePermit>,
+) -> Response {
+    let status = response.status();
     let mut headers = response.headers().clone();
     headers.remove_hop_by_hop_headers();
 
-    let stream = response.bytes_stream();
-    let body = Body::from_stream(stream);
+    let (offset, end) = match status {
+        StatusCode::PARTIAL_CONTENT => content_range(&headers).unwrap_or((0, None)),
+        _ => (0, None),
+    };
+
+    let stream = ResumableStream {
+        state,
+        request,
+        offset,
+        end,
+        stream: response.bytes_stream().boxed(),
+        permit,
+        resumes: 0,
+        done: false,
+    };
 
-    let mut response = Response::new(body);
+    let body = futures_util::stream::unfold(stream, |mut stream| async move {
+        let chunk = stream.next_chunk().await?;
+        Some((chunk, stream))
+    });
+
+    let mut response = Response::new(Body::from_stream(body));
     *response.status_mut() = status;
     *response.headers_mut() = headers;
+    response
+}
+
+/// An upstream body that reconnects with a `Range` request starting at the
+/// first undelivered byte when the connection fails mid-stream.
+struct ResumableStream {
+    state: Arc<ServerState>,
+    request: HttpRequest,
+    offset: u64,
+    end: Option<u64>,
+    stream: BoxStream<'static, reqwest::Result<Bytes>>,
+    permit: Option<OwnedSemaphorePermit>,
+    resumes: u32,
+    done: bool,
+}
+
+impl ResumableStream {
+    async fn next_chunk(&mut self) -> Option<io::Result<Bytes>> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            match self.stream.next().await? {
+                Ok(chunk) => {
+                    self.offset += chunk.len() as u64;
+                    return Some(Ok(chunk));
+                }
+                Err(err) => {
+                    if let Err(err) = self.resume(err).await {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+        }
+    }
+
+    async fn resume(&mut self, err: reqwest::Error) -> io::Result<()> {
+        if self.resumes >= self.state.http_client.retry_config().max_resumes {
+            return Err(io::Error::other(err));
+        }
+        self.resumes += 1;
+
+        warn!(
+            "video stream {} failed at byte {}: {err}, resuming",
+            self.request.uri(),
+            self.offset
+        );
+
+        let range = match self.end {
+            Some(end) => format!("bytes={}-{end}", self.offset),
+            None => format!("bytes={}-", self.offset),
+        };
+        let mut request = self.request.clone();
+        request.headers_mut().insert(
+            RANGE,
+            HeaderValue::from_str(&range).map_err(io::Error::other)?,
+        );
+
+        // Release the origin slot before reconnecting so that a limit of one
+        // connection per origin does not deadlock.
+        self.permit = None;
+        let (response, permit) = self
+            .state
+            .http_client
+            .send(&request)
+            .await
+            .map_err(io::Error::other)?;
+
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(io::Error::other(format!(
+                "upstream does not support resuming, returned {}",
+                response.status()
+            )));
+        }
+        let start = content_range(response.headers()).map(|(start, _)| start);
+        if start != Some(self.offset) {
+            return Err(io::Error::other(format!(
+                "upstream resumed at byte {start:?} instead of {}",
+                self.offset
+            )));
+        }
+
+        self.stream = response.bytes_stream().boxed();
+        self.permit = permit;
+        Ok(())
+    }
+}
+
+/// Whether a request may be answered by an alternative video: only when it
+/// asks for the whole video or for a range starting at its first byte.
+fn allows_fallback(headers: &HeaderMap) -> bool {
+    let Some(range) = headers.get(RANGE) else {
+        return true;
+    };
+    range
+        .to_str()
+        .ok()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.split_once('-'))
+        .is_some_and(|(start, _)| start.trim() == "0")
+}
+
+/// Parses `Content-Range: bytes START-END/TOTAL` into `(START, Some(END))`.
+fn content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.strip_prefix("bytes ")?.split('/').next()?;
+    let (start, end) = range.split_once('-')?;
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(response)
+    fn headers(name: http::HeaderName, value: &'static str) -> HeaderMap {
+        HeaderMap::from_iter([(name, HeaderValue::from_static(value))])
+    }
+
+    #[test]
+    fn falls_back_only_from_the_start() {
+        assert!(allows_fallback(&HeaderMap::new()));
+        assert!(allows_fallback(&headers(RANGE, "bytes=0-")));
+        assert!(allows_fallback(&headers(RANGE, "bytes=0-1023")));
+        assert!(!allows_fallback(&headers(RANGE, "bytes=1024-")));
+        assert!(!allows_fallback(&headers(RANGE, "bytes=-500")));
+    }
+
+    #[test]
+    fn parses_content_range() {
+        assert_eq!(
+            content_range(&headers(CONTENT_RANGE, "bytes 100-199/1000")),
+            Some((100, Some(199)))
+        );
+        assert_eq!(content_range(&headers(CONTENT_RANGE, "bytes */1000")), None);
+    }
 }
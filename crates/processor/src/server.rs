@@ -0,0 +1,56 @@
+use std::{io, net::SocketAddr, time::Duration};
+
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::{rt::TokioIo, server::graceful::GracefulShutdown, service::TowerToHyperService};
+use tokio::{net::TcpListener, sync::oneshot, task::JoinSet, time};
+use tracing::{debug, trace};
+
+/// How long to wait before accepting again after a failed accept, e.g. when
+/// the process is out of file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// Serves `app` until `shutdown` fires, then waits for the open connections
+/// to finish their in-flight requests.
+///
+/// Connections run in a [`JoinSet`] owned by the returned future, so
+/// dropping it, e.g. by aborting the task it runs in, also aborts every
+/// connection. Only HTTP/1 is served, which is what players speak to a plain
+/// `http://` origin, so every request runs inside its connection task.
+pub(crate) async fn serve(
+    listener: TcpListener,
+    app: Router,
+    mut shutdown: oneshot::Receiver<()>,
+) -> io::Result<()> {
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+
+    loop {
+        let (stream, remote_addr): (_, SocketAddr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    debug!("failed to accept connection: {err}");
+                    time::sleep(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        while connections.try_join_next().is_some() {}
+
+        let connection = http1::Builder::new()
+            .serve_connection(TokioIo::new(stream), TowerToHyperService::new(app.clone()));
+        let connection = graceful.watch(connection);
+        connections.spawn(async move {
+            if let Err(err) = connection.await {
+                trace!("connection from {remote_addr} failed: {err}");
+            }
+        });
+    }
+
+    drop(listener);
+    trace!("draining {} connection(s)", graceful.count());
+    graceful.shutdown().await;
+    Ok(())
+}
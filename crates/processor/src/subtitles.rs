@@ -0,0 +1,117 @@
+use mime::Mime;
+
+/// The file format of a subtitle track registered with the processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Vtt,
+    Srt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    /// The format the track is served as; SRT is converted to WebVTT.
+    pub fn served_format(self) -> Self {
+        match self {
+            SubtitleFormat::Srt => SubtitleFormat::Vtt,
+            format => format,
+        }
+    }
+
+    pub(crate) fn mime_type(self) -> Mime {
+        match self.served_format() {
+            SubtitleFormat::Vtt => "text/vtt; charset=utf-8".parse().unwrap(),
+            SubtitleFormat::Srt => "application/x-subrip".parse().unwrap(),
+            SubtitleFormat::Ass => "text/x-ssa; charset=utf-8".parse().unwrap(),
+        }
+    }
+}
+
+/// Converts a SubRip document into WebVTT.
+///
+/// Cue indices are dropped and the decimal comma of the timestamps is
+/// replaced with a period; cue text is kept as is.
+pub fn srt_to_vtt(srt: &str) -> String {
+    let srt = srt
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+
+    let mut vtt = String::from("WEBVTT\n");
+
+    for block in srt.split("\n\n") {
+        let mut lines = block
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .peekable();
+
+        if lines.peek().is_some_and(|line| !line.contains("-->")) {
+            lines.next();
+        }
+
+        let Some(timing) = lines.next().filter(|line| line.contains("-->")) else {
+            continue;
+        };
+
+        vtt.push('\n');
+        vtt.push_str(&timing.replace(',', "."));
+        vtt.push('\n');
+        for line in lines {
+            vtt.push_str(line);
+            vtt.push('\n');
+        }
+    }
+
+    vtt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_cues() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\nHello\nworld\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n";
+
+        assert_eq!(
+            srt_to_vtt(srt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\nHello\nworld\n\n00:00:03.000 --> 00:00:04.000\nBye\n"
+        );
+    }
+
+    #[test]
+    fn handles_bom_and_crlf() {
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\nHello\r\n\r\n";
+
+        assert_eq!(
+            srt_to_vtt(srt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello\n"
+        );
+    }
+
+    #[test]
+    fn accepts_cues_without_index() {
+        let srt = "\n\n00:00:01,000 --> 00:00:02,000\nHello\n";
+
+        assert_eq!(
+            srt_to_vtt(srt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello\n"
+        );
+    }
+
+    #[test]
+    fn skips_blocks_without_timing() {
+        let srt = "garbage\n\n1\nnot a timing\ntext\n\n2\n00:00:01,000 --> 00:00:02,000\nKept\n";
+
+        assert_eq!(
+            srt_to_vtt(srt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nKept\n"
+        );
+    }
+
+    #[test]
+    fn keeps_commas_in_text() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nWell, hello\n";
+
+        assert!(srt_to_vtt(srt).ends_with("\nWell, hello\n"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::{LogRecord, LogSink};
+
+type Subscriber = Box<dyn Fn(&LogRecord) -> bool + Send + Sync>;
+
+#[derive(Default)]
+struct Subscribers {
+    next_id: u64,
+    subscribers: Vec<(u64, Subscriber)>,
+}
+
+/// Keeps the latest messages of each extension in memory.
+pub struct LogBuffer {
+    capacity: usize,
+    records: Mutex<HashMap<Arc<str>, VecDeque<LogRecord>>>,
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl LogBuffer {
+    /// Creates a buffer keeping up to `capacity` messages per extension.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::default(),
+            subscribers: Arc::default(),
+        }
+    }
+
+    /// Returns the buffered messages of `extension`, oldest first.
+    pub fn records(&self, extension: &str) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .get(extension)
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self, extension: &str) {
+        self.records.lock().unwrap().remove(extension);
+    }
+
+    /// Calls `subscriber` with every message logged from now on, until it
+    /// returns `false`, e.g. once the channel it sends to is closed, or the
+    /// returned [`LogSubscription`] is dropped.
+    #[must_use = "dropping the subscription unsubscribes"]
+    pub fn subscribe(
+        &self,
+        subscriber: impl Fn(&LogRecord) -> bool + Send + Sync + 'static,
+    ) -> LogSubscription {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let id = subscribers.next_id;
+        subscribers.next_id += 1;
+        subscribers.subscribers.push((id, Box::new(subscriber)));
+        LogSubscription {
+            subscribers: Arc::downgrade(&self.subscribers),
+            id,
+        }
+    }
+}
+
+/// A subscription to the messages of a [`LogBuffer`], ended when dropped.
+pub struct LogSubscription {
+    subscribers: Weak<Mutex<Subscribers>>,
+    id: u64,
+}
+
+impl Drop for LogSubscription {
+    fn drop(&mut self) {
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            subscribers
+                .lock()
+                .unwrap()
+                .subscribers
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+impl LogSink for LogBuffer {
+    fn log(&self, record: &LogRecord) {
+        {
+            let mut records = self.records.lock().unwrap();
+            let records = records.entry(record.extension.clone()).or_default();
+            if records.len() >= self.capacity {
+                records.pop_front();
+            }
+            records.push_back(record.clone());
+        }
+
+        self.subscribers
+            .lock()
+            .unwrap()
+            .subscribers
+            .retain(|(_, subscriber)| subscriber(record));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::SystemTime};
+
+    use super::*;
+    use crate::Level;
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            timestamp: SystemTime::now(),
+            extension: "test".into(),
+            operation: None,
+            level: Level::Info,
+            context: String::new(),
+            message: message.to_owned(),
+            fields: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    fn subscriber_count(buffer: &LogBuffer) -> usize {
+        buffer.subscribers.lock().unwrap().subscribers.len()
+    }
+
+    #[test]
+    fn unsubscribes_when_the_subscription_is_dropped() {
+        let buffer = LogBuffer::new(10);
+        let (sender, receiver) = mpsc::channel();
+        let subscription =
+            buffer.subscribe(move |record| sender.send(record.message.clone()).is_ok());
+
+        buffer.log(&record("first"));
+        drop(subscription);
+        buffer.log(&record("second"));
+
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), ["first"]);
+        assert_eq!(subscriber_count(&buffer), 0);
+    }
+
+    #[test]
+    fn unsubscribes_when_the_channel_is_closed() {
+        let buffer = LogBuffer::new(10);
+        let (sender, receiver) = mpsc::channel();
+        let _subscription =
+            buffer.subscribe(move |record| sender.send(record.message.clone()).is_ok());
+        let (other_sender, other_receiver) = mpsc::channel();
+        let _other =
+            buffer.subscribe(move |record| other_sender.send(record.message.clone()).is_ok());
+
+        drop(receiver);
+        buffer.log(&record("first"));
+
+        assert_eq!(subscriber_count(&buffer), 1);
+        assert_eq!(other_receiver.try_iter().collect::<Vec<_>>(), ["first"]);
+    }
+}
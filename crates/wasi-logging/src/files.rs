@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use crate::{LogRecord, LogSink, spans::DisplayFields};
+
+/// Returns the name under which the files of `extension` are stored.
+///
+/// Extension ids come from the extensions themselves, so lowercase ASCII
+/// letters, digits and `-` are kept and every other byte is escaped as `_`
+/// followed by its hex value. The name cannot escape its directory, and
+/// distinct ids never share one, even on case-insensitive file systems.
+pub fn extension_file_stem(extension: &str) -> String {
+    let mut stem = String::with_capacity(extension.len());
+    for byte in extension.bytes() {
+        if byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' {
+            stem.push(char::from(byte));
+        } else {
+            stem.push_str(&format!("_{byte:02x}"));
+        }
+    }
+    stem
+}
+
+struct LogFile {
+    file: File,
+    len: u64,
+}
+
+/// Appends the messages of each extension to `<extension>.log` in a
+/// directory, rotating the file once it grows past a size limit.
+///
+/// Rotated files are renamed to `<extension>.log.1`, `<extension>.log.2`,
+/// and so on, the oldest being removed.
+pub struct LogFiles {
+    dir: PathBuf,
+    max_len: u64,
+    max_files: usize,
+    files: Mutex<HashMap<String, LogFile>>,
+}
+
+impl LogFiles {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_len: 1024 * 1024,
+            max_files: 3,
+            files: Mutex::default(),
+        }
+    }
+
+    /// Sets the size in bytes past which a file is rotated.
+    pub fn with_max_len(mut self, max_len: u64) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Sets the number of rotated files kept besides the current one.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    fn path(&self, extension: &str, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(format!("{extension}.log")),
+            index => self.dir.join(format!("{extension}.log.{index}")),
+        }
+    }
+
+    fn open(&self, extension: &str) -> io::Result<LogFile> {
+        fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(extension, 0))?;
+        let len = file.metadata()?.len();
+        Ok(LogFile { file, len })
+    }
+
+    fn rotate(&self, extension: &str) -> io::Result<()> {
+        if self.max_files == 0 {
+            return fs::remove_file(self.path(extension, 0));
+        }
+
+        for index in (0..self.max_files).rev() {
+            let from = self.path(extension, index);
+            if from.exists() {
+                fs::rename(from, self.path(extension, index + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, record: &LogRecord) -> io::Result<()> {
+        let extension = extension_file_stem(&record.extension);
+        let timestamp = record
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut line = format!(
+            "{}.{:03} {:?} [{}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            record.level,
+            record.operation.unwrap_or("-"),
+        );
+        for span in &record.spans {
+            line.push(':');
+            line.push_str(span);
+        }
+        line.push_str(&format!("] {}: {}", record.context, record.message));
+        if !record.fields.is_empty() {
+            line.push_str(&format!(" {}", DisplayFields(&record.fields)));
+        }
+        line.push('\n');
+
+        let mut files = self.files.lock().unwrap();
+        let file = match files.remove(&extension) {
+            Some(file) => file,
+            None => self.open(&extension)?,
+        };
+        let file = if file.len > 0 && file.len + line.len() as u64 > self.max_len {
+            drop(file);
+            self.rotate(&extension)?;
+            self.open(&extension)?
+        } else {
+            file
+        };
+
+        let LogFile { mut file, len } = file;
+        file.write_all(line.as_bytes())?;
+        files.insert(
+            extension,
+            LogFile {
+                file,
+                len: len + line.len() as u64,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl LogSink for LogFiles {
+    fn log(&self, record: &LogRecord) {
+        if let Err(err) = self.write(record) {
+            tracing::warn!(
+                "failed to write log of extension {}: {err}",
+                record.extension
+            );
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{fmt, str::FromStr};
+
+use crate::Level;
+
+/// Orders levels from the least to the most severe.
+pub(crate) fn severity(level: Level) -> u8 {
+    match level {
+        Level::Trace => 0,
+        Level::Debug => 1,
+        Level::Info => 2,
+        Level::Warn => 3,
+        Level::Error => 4,
+        Level::Critical => 5,
+    }
+}
+
+fn level_name(level: Option<Level>) -> &'static str {
+    match level {
+        Some(Level::Trace) => "trace",
+        Some(Level::Debug) => "debug",
+        Some(Level::Info) => "info",
+        Some(Level::Warn) => "warn",
+        Some(Level::Error) => "error",
+        Some(Level::Critical) => "critical",
+        None => "off",
+    }
+}
+
+fn parse_level(level: &str) -> Result<Option<Level>, InvalidLogFilter> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "trace" => Ok(Some(Level::Trace)),
+        "debug" => Ok(Some(Level::Debug)),
+        "info" => Ok(Some(Level::Info)),
+        "warn" => Ok(Some(Level::Warn)),
+        "error" => Ok(Some(Level::Error)),
+        "critical" => Ok(Some(Level::Critical)),
+        "off" => Ok(None),
+        level => Err(InvalidLogFilter(format!("unknown level `{level}`"))),
+    }
+}
+
+/// A log filter that could not be parsed.
+#[derive(Debug)]
+pub struct InvalidLogFilter(String);
+
+impl fmt::Display for InvalidLogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidLogFilter {}
+
+/// Decides which messages of an extension are kept, by minimum level.
+///
+/// Contexts can be given their own minimum level; the longest context
+/// prefix matching a message wins. `None` turns messages off.
+///
+/// Filters are written like `warn,http=debug,parser=off`: an optional
+/// default level followed by `context=level` directives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    level: Option<Level>,
+    contexts: Vec<(String, Option<Level>)>,
+}
+
+impl Default for LogFilter {
+    /// Keeps every message.
+    fn default() -> Self {
+        Self::new(Some(Level::Trace))
+    }
+}
+
+impl LogFilter {
+    pub fn new(level: Option<Level>) -> Self {
+        Self {
+            level,
+            contexts: Vec::new(),
+        }
+    }
+
+    /// Sets the minimum level of the contexts starting with `context`.
+    pub fn with_context(mut self, context: impl Into<String>, level: Option<Level>) -> Self {
+        let context = context.into();
+        self.contexts.retain(|(other, _)| *other != context);
+        self.contexts.push((context, level));
+        self
+    }
+
+    pub fn enabled(&self, level: Level, context: &str) -> bool {
+        let min_level = self
+            .contexts
+            .iter()
+            .filter(|(prefix, _)| context.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.level, |(_, level)| *level);
+
+        min_level.is_some_and(|min_level| severity(level) >= severity(min_level))
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = InvalidLogFilter;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        let mut result = LogFilter::default();
+        for directive in filter.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                _ if directive.is_empty() => {}
+                Some((context, level)) => {
+                    result = result.with_context(context.trim(), parse_level(level)?);
+                }
+                None => result.level = parse_level(directive)?,
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(level_name(self.level))?;
+        for (context, level) in &self.contexts {
+            write!(f, ",{context}={}", level_name(*level))?;
+        }
+        Ok(())
+    }
+}
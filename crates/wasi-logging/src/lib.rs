@@ -1,36 +1,477 @@
+mod buffer;
+mod files;
+mod filter;
+mod limiter;
+mod spans;
+
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
 use anyhow::Result;
-use wasmtime::component::HasData;
+use tracing::{Span, field};
+use wasmtime::component::{HasData, Resource};
 
-pub use self::generated::wasi::*;
+pub use self::{
+    buffer::{LogBuffer, LogSubscription},
+    files::{LogFiles, extension_file_stem},
+    filter::{InvalidLogFilter, LogFilter},
+    generated::{nero::wasi_logging::structured, wasi::*},
+    limiter::RateLimiter,
+    logging::logging::Level,
+    spans::ExtensionSpan,
+};
+use self::{
+    limiter::Admission,
+    spans::{JsonFields, OpenSpan},
+    structured::{Field, Value},
+};
 
 mod generated {
     wasmtime::component::bindgen!({
         path: "wit",
-        world: "wasi:logging/imports",
+        world: "nero:wasi-logging/imports",
+        with: {
+            "nero:wasi-logging/structured/span": crate::ExtensionSpan,
+        },
     });
 }
 
-pub struct WasiLogging;
+/// A message logged by an extension.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub timestamp: SystemTime,
+    /// The id of the extension that logged the message.
+    pub extension: Arc<str>,
+    /// The operation the host was running, e.g. `search`.
+    pub operation: Option<&'static str>,
+    pub level: Level,
+    pub context: String,
+    pub message: String,
+    /// The fields attached with the `structured` interface.
+    pub fields: Vec<Field>,
+    /// The names of the open spans of the extension, outermost first.
+    pub spans: Vec<String>,
+}
+
+/// Receives the messages logged by extensions, in addition to `tracing`.
+pub trait LogSink: Send + Sync {
+    fn log(&self, record: &LogRecord);
+}
+
+/// Longest message kept by default, in bytes.
+const DEFAULT_MAX_MESSAGE_LEN: usize = 8 * 1024;
+
+/// The per-store state of the `wasi:logging` and `structured` imports.
+///
+/// Messages are emitted as `tracing` events, and spans as `tracing` spans.
+/// `tracing` only records fields named at compile time, so the fields of
+/// the `structured` interface are recorded together as a JSON object under
+/// the `fields` key, e.g. `fields={"status":200,"cached":false}`.
+pub struct WasiLoggingCtx {
+    extension: Arc<str>,
+    operation: Option<&'static str>,
+    sinks: Vec<Arc<dyn LogSink>>,
+    filter: Arc<LogFilter>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    max_message_len: usize,
+    spans: HashMap<u32, OpenSpan>,
+    /// The ids of the open spans, innermost last.
+    span_stack: Vec<u32>,
+    next_span_id: u32,
+}
+
+impl Default for WasiLoggingCtx {
+    fn default() -> Self {
+        Self {
+            extension: Arc::from(""),
+            operation: None,
+            sinks: Vec::new(),
+            filter: Arc::default(),
+            rate_limiter: None,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            spans: HashMap::new(),
+            span_stack: Vec::new(),
+            next_span_id: 0,
+        }
+    }
+}
+
+impl WasiLoggingCtx {
+    pub fn new(extension: impl Into<Arc<str>>) -> Self {
+        let mut ctx = Self::default();
+        ctx.extension = extension.into();
+        ctx
+    }
+
+    /// Tags the messages with the operation the host is running.
+    pub fn with_operation(mut self, operation: &'static str) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    pub fn with_sinks(mut self, sinks: impl IntoIterator<Item = Arc<dyn LogSink>>) -> Self {
+        self.sinks.extend(sinks);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Arc<LogFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Drops the messages beyond the rate of `rate_limiter`, which is
+    /// usually shared by every store of an extension. `Critical` messages
+    /// are never dropped.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sets the length in bytes past which messages are truncated.
+    pub fn with_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Returns the innermost enabled span of the extension, or the span of
+    /// the host operation if there is none.
+    fn parent_span(&self) -> Span {
+        self.span_stack
+            .iter()
+            .rev()
+            .map(|id| &self.spans[id].span)
+            .find(|span| !span.is_disabled())
+            .cloned()
+            .unwrap_or_else(Span::current)
+    }
+
+    /// Reports the messages suppressed in windows of the rate limiter that
+    /// have expired since the last message was admitted.
+    fn report_expired_suppressed(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            self.report_suppressed(rate_limiter.take_expired());
+        }
+    }
+
+    fn report_suppressed(&self, suppressed: u64) {
+        if suppressed > 0 {
+            self.emit(
+                Level::Warn,
+                String::new(),
+                format!("{suppressed} messages suppressed by the rate limit"),
+                Vec::new(),
+            );
+        }
+    }
+
+    fn log(&self, level: Level, context: String, message: String, fields: Vec<Field>) {
+        self.report_expired_suppressed();
+        if !self.filter.enabled(level, &context) {
+            return;
+        }
+
+        if level != Level::Critical
+            && let Some(rate_limiter) = &self.rate_limiter
+        {
+            match rate_limiter.admit() {
+                Admission::Suppressed => return,
+                Admission::Allowed { suppressed } => self.report_suppressed(suppressed),
+            }
+        }
+
+        let fields = self.truncate_fields(fields);
+        self.emit(
+            level,
+            context,
+            truncate(message, self.max_message_len),
+            fields,
+        );
+    }
+
+    fn truncate_fields(&self, fields: Vec<Field>) -> Vec<Field> {
+        fields
+            .into_iter()
+            .map(|field| match field.value {
+                Value::Text(text) => Field {
+                    key: field.key,
+                    value: Value::Text(truncate(text, self.max_message_len)),
+                },
+                _ => field,
+            })
+            .collect()
+    }
+
+    fn emit(&self, level: Level, context: String, message: String, fields: Vec<Field>) {
+        let parent = self.parent_span();
+        let extension = &*self.extension;
+        let operation = self.operation.unwrap_or_default();
+        let display_fields = (!fields.is_empty()).then(|| field::display(JsonFields(&fields)));
+
+        macro_rules! event {
+            ($macro:ident $(, $key:ident = $value:expr)?) => {
+                tracing::$macro!(
+                    parent: &parent,
+                    extension,
+                    operation,
+                    $($key = $value,)?
+                    context = %context,
+                    fields = display_fields,
+                    "{message}"
+                )
+            };
+        }
 
-impl logging::logging::Host for WasiLogging {
-    fn log(&mut self, level: logging::logging::Level, context: String, message: String) {
         match level {
-            logging::logging::Level::Trace => tracing::trace!(context = %context, "{message}"),
-            logging::logging::Level::Debug => tracing::debug!(context = %context, "{message}"),
-            logging::logging::Level::Info => tracing::info!(context = %context, "{message}"),
-            logging::logging::Level::Warn => tracing::warn!(context = %context, "{message}"),
-            logging::logging::Level::Error | logging::logging::Level::Critical => {
-                tracing::error!(context = %context, "{message}")
+            Level::Trace => event!(trace),
+            Level::Debug => event!(debug),
+            Level::Info => event!(info),
+            Level::Warn => event!(warn),
+            Level::Error => event!(error),
+            // `tracing` has no level above error, so mark critical messages
+            // with a field instead.
+            Level::Critical => event!(error, critical = true),
+        }
+
+        if self.sinks.is_empty() {
+            return;
+        }
+
+        let record = LogRecord {
+            timestamp: SystemTime::now(),
+            extension: self.extension.clone(),
+            operation: self.operation,
+            level,
+            context,
+            message,
+            fields,
+            spans: self
+                .span_stack
+                .iter()
+                .map(|id| self.spans[id].name.clone())
+                .collect(),
+        };
+        for sink in &self.sinks {
+            sink.log(&record);
+        }
+    }
+
+    fn open_span(&mut self, level: Level, name: String, fields: Vec<Field>) -> u32 {
+        self.report_expired_suppressed();
+        let span = if self.filter.enabled(level, "") {
+            let parent = self.parent_span();
+            let extension = &*self.extension;
+            let operation = self.operation.unwrap_or_default();
+
+            macro_rules! span {
+                ($macro:ident) => {
+                    tracing::$macro!(
+                        parent: &parent,
+                        "extension_span",
+                        extension,
+                        operation,
+                        span = %name,
+                        fields = field::Empty,
+                    )
+                };
+            }
+
+            match level {
+                Level::Trace => span!(trace_span),
+                Level::Debug => span!(debug_span),
+                Level::Info => span!(info_span),
+                Level::Warn => span!(warn_span),
+                Level::Error | Level::Critical => span!(error_span),
             }
+        } else {
+            Span::none()
+        };
+
+        let id = self.next_span_id;
+        self.next_span_id += 1;
+
+        let mut open_span = OpenSpan {
+            name,
+            span,
+            fields: Vec::new(),
+        };
+        self.record_span_fields(&mut open_span, fields);
+        self.spans.insert(id, open_span);
+        self.span_stack.push(id);
+        id
+    }
+
+    fn record_span_fields(&self, open_span: &mut OpenSpan, fields: Vec<Field>) {
+        if fields.is_empty() {
+            return;
+        }
+
+        open_span.fields.extend(self.truncate_fields(fields));
+        open_span
+            .span
+            .record("fields", field::display(JsonFields(&open_span.fields)));
+    }
+
+    fn close_span(&mut self, id: u32) {
+        self.report_expired_suppressed();
+        self.spans.remove(&id);
+        self.span_stack.retain(|open| *open != id);
+    }
+}
+
+impl Drop for WasiLoggingCtx {
+    fn drop(&mut self) {
+        // No later call of this store would report them.
+        if let Some(rate_limiter) = &self.rate_limiter {
+            self.report_suppressed(rate_limiter.take_suppressed());
+        }
+    }
+}
+
+fn truncate(mut message: String, max_len: usize) -> String {
+    if message.len() <= max_len {
+        return message;
+    }
+
+    let mut len = max_len;
+    while !message.is_char_boundary(len) {
+        len -= 1;
+    }
+    let truncated = message.len() - len;
+    message.truncate(len);
+    message.push_str(&format!("… ({truncated} bytes truncated)"));
+    message
+}
+
+pub trait WasiLoggingView: Send {
+    fn logging(&mut self) -> &mut WasiLoggingCtx;
+}
+
+pub struct WasiLogging<'a>(&'a mut WasiLoggingCtx);
+
+impl logging::logging::Host for WasiLogging<'_> {
+    fn log(&mut self, level: Level, context: String, message: String) {
+        self.0.log(level, context, message, Vec::new());
+    }
+}
+
+impl structured::Host for WasiLogging<'_> {
+    fn log(&mut self, level: Level, context: String, message: String, fields: Vec<Field>) {
+        self.0.log(level, context, message, fields);
+    }
+}
+
+impl structured::HostSpan for WasiLogging<'_> {
+    fn new(&mut self, level: Level, name: String, fields: Vec<Field>) -> Resource<ExtensionSpan> {
+        Resource::new_own(self.0.open_span(level, name, fields))
+    }
+
+    fn add_fields(&mut self, span: Resource<ExtensionSpan>, fields: Vec<Field>) {
+        let ctx = &mut *self.0;
+        if let Some(mut open_span) = ctx.spans.remove(&span.rep()) {
+            ctx.record_span_fields(&mut open_span, fields);
+            ctx.spans.insert(span.rep(), open_span);
         }
     }
+
+    fn drop(&mut self, span: Resource<ExtensionSpan>) -> wasmtime::Result<()> {
+        self.0.close_span(span.rep());
+        Ok(())
+    }
 }
 
-pub fn add_to_linker<T>(l: &mut wasmtime::component::Linker<T>) -> Result<()> {
-    logging::logging::add_to_linker::<T, HasWasiLogging>(l, |_| WasiLogging)
+pub fn add_to_linker<T: WasiLoggingView + 'static>(
+    l: &mut wasmtime::component::Linker<T>,
+) -> Result<()> {
+    logging::logging::add_to_linker::<T, HasWasiLogging>(l, |state| WasiLogging(state.logging()))?;
+    structured::add_to_linker::<T, HasWasiLogging>(l, |state| WasiLogging(state.logging()))
 }
 
 struct HasWasiLogging;
 impl HasData for HasWasiLogging {
-    type Data<'a> = WasiLogging;
+    type Data<'a> = WasiLogging<'a>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn ctx(buffer: &Arc<LogBuffer>) -> WasiLoggingCtx {
+        WasiLoggingCtx::new("test")
+            .with_sinks([buffer.clone() as Arc<dyn LogSink>])
+            .with_rate_limiter(Arc::new(RateLimiter::new(1)))
+    }
+
+    fn messages(buffer: &LogBuffer) -> Vec<String> {
+        buffer
+            .records("test")
+            .into_iter()
+            .map(|record| record.message)
+            .collect()
+    }
+
+    fn log(ctx: &WasiLoggingCtx, message: &str) {
+        ctx.log(Level::Info, String::new(), message.to_owned(), Vec::new());
+    }
+
+    #[test]
+    fn reports_suppressed_messages_with_the_next_admitted_one() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let ctx = ctx(&buffer);
+
+        for message in ["first", "second", "third"] {
+            log(&ctx, message);
+        }
+        std::thread::sleep(Duration::from_millis(1100));
+        log(&ctx, "fourth");
+
+        assert_eq!(
+            messages(&buffer),
+            ["first", "2 messages suppressed by the rate limit", "fourth"]
+        );
+    }
+
+    #[test]
+    fn reports_suppressed_messages_once_the_window_expires() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let mut ctx = ctx(&buffer);
+
+        for message in ["first", "second", "third"] {
+            log(&ctx, message);
+        }
+        std::thread::sleep(Duration::from_millis(1100));
+        let span = ctx.open_span(Level::Info, "span".to_owned(), Vec::new());
+        ctx.close_span(span);
+
+        assert_eq!(
+            messages(&buffer),
+            ["first", "2 messages suppressed by the rate limit"]
+        );
+    }
+
+    #[test]
+    fn reports_suppressed_messages_when_dropped() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let ctx = ctx(&buffer);
+
+        for message in ["first", "second"] {
+            log(&ctx, message);
+        }
+        drop(ctx);
+
+        assert_eq!(
+            messages(&buffer),
+            ["first", "1 messages suppressed by the rate limit"]
+        );
+    }
+
+    #[test]
+    fn escapes_extension_file_stems() {
+        assert_eq!(extension_file_stem("anime-site"), "anime-site");
+        assert_eq!(extension_file_stem("a.b"), "a_2eb");
+        assert_eq!(extension_file_stem("a_b"), "a_5fb");
+        assert_eq!(extension_file_stem("A"), "_41");
+        assert_eq!(extension_file_stem("../x"), "_2e_2e_2fx");
+        assert_eq!(extension_file_stem("é"), "_c3_a9");
+    }
 }
@@ -0,0 +1,90 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+struct Window {
+    start: Instant,
+    count: u32,
+    suppressed: u64,
+}
+
+/// Limits how many messages an extension may log per second.
+pub struct RateLimiter {
+    per_second: u32,
+    window: Mutex<Window>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+pub(crate) enum Admission {
+    /// The message may be logged, after a summary of the `suppressed`
+    /// messages dropped before it if there are any.
+    Allowed {
+        suppressed: u64,
+    },
+    Suppressed,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32) -> Self {
+        Self {
+            per_second,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                count: 0,
+                suppressed: 0,
+            }),
+        }
+    }
+
+    pub(crate) fn admit(&self) -> Admission {
+        let mut window = self.window.lock().unwrap();
+        window.roll(Instant::now());
+
+        if window.count >= self.per_second {
+            window.suppressed += 1;
+            return Admission::Suppressed;
+        }
+
+        window.count += 1;
+        Admission::Allowed {
+            suppressed: std::mem::take(&mut window.suppressed),
+        }
+    }
+
+    /// Takes the number of messages suppressed in windows that have since
+    /// expired, so that they are reported even if no message follows.
+    pub(crate) fn take_expired(&self) -> u64 {
+        let mut window = self.window.lock().unwrap();
+        if window.roll(Instant::now()) {
+            std::mem::take(&mut window.suppressed)
+        } else {
+            0
+        }
+    }
+
+    /// Takes the number of messages suppressed so far, expired or not.
+    pub(crate) fn take_suppressed(&self) -> u64 {
+        std::mem::take(&mut self.window.lock().unwrap().suppressed)
+    }
+}
+
+impl Window {
+    /// Starts a new window if the current one has expired, returning
+    /// whether it did.
+    fn roll(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.start) < WINDOW {
+            return false;
+        }
+        self.start = now;
+        self.count = 0;
+        true
+    }
+}
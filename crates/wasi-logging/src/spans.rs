@@ -0,0 +1,99 @@
+use std::fmt;
+
+use tracing::Span;
+
+use crate::structured::{Field, Value};
+
+/// The host representation of the `span` resource of the `structured`
+/// interface; open spans live in the [`WasiLoggingCtx`](crate::WasiLoggingCtx).
+pub struct ExtensionSpan;
+
+pub(crate) struct OpenSpan {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+    pub(crate) fields: Vec<Field>,
+}
+
+/// Formats fields as `key=value` pairs, quoting text values.
+pub(crate) struct DisplayFields<'a>(pub(crate) &'a [Field]);
+
+impl fmt::Display for DisplayFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, field) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}=", field.key)?;
+            match &field.value {
+                Value::Text(text) => write!(f, "{text:?}")?,
+                Value::Signed(value) => write!(f, "{value}")?,
+                Value::Unsigned(value) => write!(f, "{value}")?,
+                Value::Float(value) => write!(f, "{value}")?,
+                Value::Boolean(value) => write!(f, "{value}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats fields as a JSON object, keeping the type of their values.
+pub(crate) struct JsonFields<'a>(pub(crate) &'a [Field]);
+
+impl fmt::Display for JsonFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let object = self
+            .0
+            .iter()
+            .map(|field| {
+                let value = match &field.value {
+                    Value::Text(text) => serde_json::Value::from(text.as_str()),
+                    Value::Signed(value) => serde_json::Value::from(*value),
+                    Value::Unsigned(value) => serde_json::Value::from(*value),
+                    Value::Float(value) => serde_json::Value::from(*value),
+                    Value::Boolean(value) => serde_json::Value::from(*value),
+                };
+                (field.key.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        write!(f, "{}", serde_json::Value::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(key: &str, value: Value) -> Field {
+        Field {
+            key: key.to_owned(),
+            value,
+        }
+    }
+
+    #[test]
+    fn formats_fields_as_json() {
+        let fields = [
+            field(
+                "url",
+                Value::Text("https://example.com/?q=\"a\"".to_owned()),
+            ),
+            field("status", Value::Unsigned(200)),
+            field("offset", Value::Signed(-1)),
+            field("ratio", Value::Float(0.5)),
+            field("cached", Value::Boolean(false)),
+        ];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&JsonFields(&fields).to_string()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "url": "https://example.com/?q=\"a\"",
+                "status": 200,
+                "offset": -1,
+                "ratio": 0.5,
+                "cached": false,
+            })
+        );
+    }
+}
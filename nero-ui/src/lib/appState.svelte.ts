@@ -4,6 +4,7 @@ export interface AppConfig {
   playerPath: string | null;
   allowUntrustedExtensions: boolean;
   extensionPath: string | null;
+  preferredAudioLanguage: string | null;
 }
 
 class AppState {
@@ -11,6 +12,7 @@ class AppState {
     playerPath: null,
     allowUntrustedExtensions: true,
     extensionPath: null,
+    preferredAudioLanguage: null,
   });
 
   extension = $state<Extension | null>(null);
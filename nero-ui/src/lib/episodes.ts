@@ -0,0 +1,19 @@
+import type { Episode, EpisodeKind, Season } from "@nero/plugin-extensions";
+
+const EPISODE_KIND_LABELS: Record<EpisodeKind, string> = {
+  regular: "Episode",
+  special: "Special",
+  recap: "Recap",
+  ova: "OVA",
+  movie: "Movie",
+};
+
+export function episodeLabel(episode: Episode): string {
+  const kind = EPISODE_KIND_LABELS[episode.kind];
+  return episode.number != null ? `${kind} ${episode.number}` : kind;
+}
+
+export function seasonLabel(season: Season): string {
+  if (season.title) return season.title;
+  return season.number != null ? `Season ${season.number}` : "Season";
+}